@@ -0,0 +1,8 @@
+DROP INDEX IF EXISTS "edges_from_node_id_to_node_id_unique_idx";
+CREATE UNIQUE INDEX "edges_from_node_id_to_node_id_unique_idx" ON "edges" ("from_node_id", "to_node_id");
+
+DROP INDEX IF EXISTS "edges_deleted_at_idx";
+ALTER TABLE "edges" DROP COLUMN "deleted_at";
+
+DROP INDEX IF EXISTS "nodes_deleted_at_idx";
+ALTER TABLE "nodes" DROP COLUMN "deleted_at";
\ No newline at end of file
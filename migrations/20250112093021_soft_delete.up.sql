@@ -0,0 +1,8 @@
+ALTER TABLE "nodes" ADD COLUMN "deleted_at" INTEGER;
+CREATE INDEX "nodes_deleted_at_idx" ON "nodes" ("deleted_at");
+
+ALTER TABLE "edges" ADD COLUMN "deleted_at" INTEGER;
+CREATE INDEX "edges_deleted_at_idx" ON "edges" ("deleted_at");
+
+DROP INDEX "edges_from_node_id_to_node_id_unique_idx";
+CREATE UNIQUE INDEX "edges_from_node_id_to_node_id_unique_idx" ON "edges" ("from_node_id", "to_node_id") WHERE "deleted_at" IS NULL;
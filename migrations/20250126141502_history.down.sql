@@ -0,0 +1,7 @@
+DROP TRIGGER IF EXISTS "edges_history_delete_trigger";
+DROP TRIGGER IF EXISTS "edges_history_update_trigger";
+DROP TRIGGER IF EXISTS "nodes_history_delete_trigger";
+DROP TRIGGER IF EXISTS "nodes_history_update_trigger";
+DROP TABLE IF EXISTS "edges_history";
+DROP TABLE IF EXISTS "nodes_history";
+DROP TABLE IF EXISTS "settings";
\ No newline at end of file
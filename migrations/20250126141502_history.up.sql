@@ -0,0 +1,60 @@
+CREATE TABLE "settings" (
+  "key" TEXT NOT NULL PRIMARY KEY,
+  "value" TEXT NOT NULL
+) STRICT;
+
+CREATE TABLE "nodes_history" (
+  "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  "node_id" INTEGER NOT NULL,
+  "uri" TEXT NOT NULL,
+  "data" TEXT NOT NULL,
+  "deleted_at" INTEGER,
+  "updated_at" INTEGER NOT NULL,
+  "created_at" INTEGER NOT NULL,
+  "recorded_at" INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+) STRICT;
+CREATE INDEX "nodes_history_node_id_idx" ON "nodes_history" ("node_id");
+CREATE INDEX "nodes_history_recorded_at_idx" ON "nodes_history" ("recorded_at");
+
+CREATE TABLE "edges_history" (
+  "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  "edge_id" INTEGER NOT NULL,
+  "from_node_id" INTEGER NOT NULL,
+  "to_node_id" INTEGER NOT NULL,
+  "uri" TEXT NOT NULL,
+  "data" TEXT,
+  "deleted_at" INTEGER,
+  "updated_at" INTEGER NOT NULL,
+  "created_at" INTEGER NOT NULL,
+  "recorded_at" INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+) STRICT;
+CREATE INDEX "edges_history_edge_id_idx" ON "edges_history" ("edge_id");
+CREATE INDEX "edges_history_recorded_at_idx" ON "edges_history" ("recorded_at");
+
+CREATE TRIGGER "nodes_history_update_trigger" AFTER UPDATE OF "data" ON "nodes"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "nodes_history" ("node_id", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+CREATE TRIGGER "nodes_history_delete_trigger" BEFORE DELETE ON "nodes"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "nodes_history" ("node_id", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+CREATE TRIGGER "edges_history_update_trigger" AFTER UPDATE OF "data" ON "edges"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "edges_history" ("edge_id", "from_node_id", "to_node_id", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."from_node_id", OLD."to_node_id", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+CREATE TRIGGER "edges_history_delete_trigger" BEFORE DELETE ON "edges"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "edges_history" ("edge_id", "from_node_id", "to_node_id", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."from_node_id", OLD."to_node_id", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
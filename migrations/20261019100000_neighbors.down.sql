@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS "edges_to_node_id_uri_idx";
+DROP INDEX IF EXISTS "edges_from_node_id_uri_idx";
@@ -0,0 +1,2 @@
+CREATE INDEX "edges_from_node_id_uri_idx" ON "edges" ("from_node_id", "uri");
+CREATE INDEX "edges_to_node_id_uri_idx" ON "edges" ("to_node_id", "uri");
@@ -0,0 +1,43 @@
+DROP TRIGGER IF EXISTS "edges_namespace_insert_trigger";
+
+DROP TRIGGER IF EXISTS "nodes_history_update_trigger";
+CREATE TRIGGER "nodes_history_update_trigger" AFTER UPDATE OF "data" ON "nodes"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "nodes_history" ("node_id", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+DROP TRIGGER IF EXISTS "nodes_history_delete_trigger";
+CREATE TRIGGER "nodes_history_delete_trigger" BEFORE DELETE ON "nodes"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "nodes_history" ("node_id", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+DROP TRIGGER IF EXISTS "edges_history_update_trigger";
+CREATE TRIGGER "edges_history_update_trigger" AFTER UPDATE OF "data" ON "edges"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "edges_history" ("edge_id", "from_node_id", "to_node_id", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."from_node_id", OLD."to_node_id", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+DROP TRIGGER IF EXISTS "edges_history_delete_trigger";
+CREATE TRIGGER "edges_history_delete_trigger" BEFORE DELETE ON "edges"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "edges_history" ("edge_id", "from_node_id", "to_node_id", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."from_node_id", OLD."to_node_id", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+ALTER TABLE "edges_history" DROP COLUMN "namespace";
+ALTER TABLE "nodes_history" DROP COLUMN "namespace";
+
+DROP INDEX IF EXISTS "edges_namespace_uri_idx";
+ALTER TABLE "edges" DROP COLUMN "cross_namespace";
+ALTER TABLE "edges" DROP COLUMN "namespace";
+
+DROP INDEX IF EXISTS "nodes_namespace_uri_idx";
+ALTER TABLE "nodes" DROP COLUMN "namespace";
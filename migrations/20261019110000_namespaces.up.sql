@@ -0,0 +1,50 @@
+ALTER TABLE "nodes" ADD COLUMN "namespace" TEXT NOT NULL DEFAULT '';
+CREATE INDEX "nodes_namespace_uri_idx" ON "nodes" ("namespace", "uri");
+
+ALTER TABLE "edges" ADD COLUMN "namespace" TEXT NOT NULL DEFAULT '';
+ALTER TABLE "edges" ADD COLUMN "cross_namespace" INTEGER NOT NULL DEFAULT 0;
+CREATE INDEX "edges_namespace_uri_idx" ON "edges" ("namespace", "uri");
+
+ALTER TABLE "nodes_history" ADD COLUMN "namespace" TEXT NOT NULL DEFAULT '';
+ALTER TABLE "edges_history" ADD COLUMN "namespace" TEXT NOT NULL DEFAULT '';
+
+DROP TRIGGER "nodes_history_update_trigger";
+CREATE TRIGGER "nodes_history_update_trigger" AFTER UPDATE OF "data" ON "nodes"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "nodes_history" ("node_id", "namespace", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."namespace", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+DROP TRIGGER "nodes_history_delete_trigger";
+CREATE TRIGGER "nodes_history_delete_trigger" BEFORE DELETE ON "nodes"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "nodes_history" ("node_id", "namespace", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."namespace", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+DROP TRIGGER "edges_history_update_trigger";
+CREATE TRIGGER "edges_history_update_trigger" AFTER UPDATE OF "data" ON "edges"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "edges_history" ("edge_id", "namespace", "from_node_id", "to_node_id", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."namespace", OLD."from_node_id", OLD."to_node_id", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+DROP TRIGGER "edges_history_delete_trigger";
+CREATE TRIGGER "edges_history_delete_trigger" BEFORE DELETE ON "edges"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "edges_history" ("edge_id", "namespace", "from_node_id", "to_node_id", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."namespace", OLD."from_node_id", OLD."to_node_id", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+CREATE TRIGGER "edges_namespace_insert_trigger" BEFORE INSERT ON "edges"
+WHEN NOT NEW."cross_namespace" AND (
+  (SELECT "namespace" FROM "nodes" WHERE "id" = NEW."from_node_id") != NEW."namespace" OR
+  (SELECT "namespace" FROM "nodes" WHERE "id" = NEW."to_node_id") != NEW."namespace"
+)
+BEGIN
+  SELECT RAISE(ABORT, 'edge crosses namespaces');
+END;
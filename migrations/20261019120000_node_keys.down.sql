@@ -0,0 +1,20 @@
+DROP TRIGGER IF EXISTS "nodes_history_update_trigger";
+CREATE TRIGGER "nodes_history_update_trigger" AFTER UPDATE OF "data" ON "nodes"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "nodes_history" ("node_id", "namespace", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."namespace", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+DROP TRIGGER IF EXISTS "nodes_history_delete_trigger";
+CREATE TRIGGER "nodes_history_delete_trigger" BEFORE DELETE ON "nodes"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "nodes_history" ("node_id", "namespace", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."namespace", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+ALTER TABLE "nodes_history" DROP COLUMN "key";
+
+DROP INDEX IF EXISTS "nodes_key_idx";
+ALTER TABLE "nodes" DROP COLUMN "key";
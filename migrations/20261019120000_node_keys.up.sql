@@ -0,0 +1,20 @@
+ALTER TABLE "nodes" ADD COLUMN "key" TEXT;
+CREATE UNIQUE INDEX "nodes_key_idx" ON "nodes" ("key");
+
+ALTER TABLE "nodes_history" ADD COLUMN "key" TEXT;
+
+DROP TRIGGER "nodes_history_update_trigger";
+CREATE TRIGGER "nodes_history_update_trigger" AFTER UPDATE OF "data" ON "nodes"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "nodes_history" ("node_id", "namespace", "key", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."namespace", OLD."key", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+DROP TRIGGER "nodes_history_delete_trigger";
+CREATE TRIGGER "nodes_history_delete_trigger" BEFORE DELETE ON "nodes"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "nodes_history" ("node_id", "namespace", "key", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."namespace", OLD."key", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS "node_labels_label_node_id_idx";
+DROP TABLE IF EXISTS "node_labels";
@@ -0,0 +1,8 @@
+CREATE TABLE "node_labels" (
+  "node_id" INTEGER NOT NULL,
+  "label" TEXT NOT NULL,
+  "created_at" INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+  PRIMARY KEY ("node_id", "label"),
+  FOREIGN KEY ("node_id") REFERENCES "nodes" ("id") ON DELETE CASCADE
+) STRICT;
+CREATE INDEX "node_labels_label_node_id_idx" ON "node_labels" ("label", "node_id");
@@ -0,0 +1,153 @@
+-- SQLite cannot change column defaults, so every table with a timestamp is
+-- rebuilt and its rows converted
+
+CREATE TABLE "nodes_new" (
+  "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  "uri" TEXT NOT NULL,
+  "data" TEXT NOT NULL,
+  "updated_at" INTEGER NOT NULL DEFAULT (CAST(unixepoch('subsec') * 1000 AS INTEGER)),
+  "created_at" INTEGER NOT NULL DEFAULT (CAST(unixepoch('subsec') * 1000 AS INTEGER)),
+  "deleted_at" INTEGER,
+  "namespace" TEXT NOT NULL DEFAULT '',
+  "key" TEXT
+) STRICT;
+INSERT INTO "nodes_new" ("id", "uri", "data", "updated_at", "created_at", "deleted_at", "namespace", "key")
+SELECT "id", "uri", "data", "updated_at" * 1000, "created_at" * 1000, "deleted_at" * 1000, "namespace", "key" FROM "nodes";
+
+CREATE TABLE "edges_new" (
+  "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  "from_node_id" INTEGER NOT NULL,
+  "to_node_id" INTEGER NOT NULL,
+  "uri" TEXT NOT NULL,
+  "data" TEXT,
+  "updated_at" INTEGER NOT NULL DEFAULT (CAST(unixepoch('subsec') * 1000 AS INTEGER)),
+  "created_at" INTEGER NOT NULL DEFAULT (CAST(unixepoch('subsec') * 1000 AS INTEGER)),
+  "deleted_at" INTEGER,
+  "namespace" TEXT NOT NULL DEFAULT '',
+  "cross_namespace" INTEGER NOT NULL DEFAULT 0,
+  FOREIGN KEY ("from_node_id") REFERENCES "nodes_new" ("id") ON DELETE CASCADE,
+  FOREIGN KEY ("to_node_id") REFERENCES "nodes_new" ("id") ON DELETE CASCADE
+) STRICT;
+INSERT INTO "edges_new" ("id", "from_node_id", "to_node_id", "uri", "data", "updated_at", "created_at", "deleted_at", "namespace", "cross_namespace")
+SELECT "id", "from_node_id", "to_node_id", "uri", "data", "updated_at" * 1000, "created_at" * 1000, "deleted_at" * 1000, "namespace", "cross_namespace" FROM "edges";
+
+CREATE TABLE "node_labels_new" (
+  "node_id" INTEGER NOT NULL,
+  "label" TEXT NOT NULL,
+  "created_at" INTEGER NOT NULL DEFAULT (CAST(unixepoch('subsec') * 1000 AS INTEGER)),
+  PRIMARY KEY ("node_id", "label"),
+  FOREIGN KEY ("node_id") REFERENCES "nodes_new" ("id") ON DELETE CASCADE
+) STRICT;
+INSERT INTO "node_labels_new" ("node_id", "label", "created_at")
+SELECT "node_id", "label", "created_at" * 1000 FROM "node_labels";
+
+CREATE TABLE "nodes_history_new" (
+  "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  "node_id" INTEGER NOT NULL,
+  "uri" TEXT NOT NULL,
+  "data" TEXT NOT NULL,
+  "deleted_at" INTEGER,
+  "updated_at" INTEGER NOT NULL,
+  "created_at" INTEGER NOT NULL,
+  "recorded_at" INTEGER NOT NULL DEFAULT (CAST(unixepoch('subsec') * 1000 AS INTEGER)),
+  "namespace" TEXT NOT NULL DEFAULT '',
+  "key" TEXT
+) STRICT;
+INSERT INTO "nodes_history_new" ("id", "node_id", "uri", "data", "deleted_at", "updated_at", "created_at", "recorded_at", "namespace", "key")
+SELECT "id", "node_id", "uri", "data", "deleted_at" * 1000, "updated_at" * 1000, "created_at" * 1000, "recorded_at" * 1000, "namespace", "key" FROM "nodes_history";
+
+CREATE TABLE "edges_history_new" (
+  "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  "edge_id" INTEGER NOT NULL,
+  "from_node_id" INTEGER NOT NULL,
+  "to_node_id" INTEGER NOT NULL,
+  "uri" TEXT NOT NULL,
+  "data" TEXT,
+  "deleted_at" INTEGER,
+  "updated_at" INTEGER NOT NULL,
+  "created_at" INTEGER NOT NULL,
+  "recorded_at" INTEGER NOT NULL DEFAULT (CAST(unixepoch('subsec') * 1000 AS INTEGER)),
+  "namespace" TEXT NOT NULL DEFAULT ''
+) STRICT;
+INSERT INTO "edges_history_new" ("id", "edge_id", "from_node_id", "to_node_id", "uri", "data", "deleted_at", "updated_at", "created_at", "recorded_at", "namespace")
+SELECT "id", "edge_id", "from_node_id", "to_node_id", "uri", "data", "deleted_at" * 1000, "updated_at" * 1000, "created_at" * 1000, "recorded_at" * 1000, "namespace" FROM "edges_history";
+
+-- keep AUTOINCREMENT from reusing ids of rows deleted before the rebuild
+DELETE FROM "sqlite_sequence" WHERE "name" = 'nodes_new';
+INSERT INTO "sqlite_sequence" ("name", "seq") SELECT 'nodes_new', "seq" FROM "sqlite_sequence" WHERE "name" = 'nodes';
+DELETE FROM "sqlite_sequence" WHERE "name" = 'edges_new';
+INSERT INTO "sqlite_sequence" ("name", "seq") SELECT 'edges_new', "seq" FROM "sqlite_sequence" WHERE "name" = 'edges';
+DELETE FROM "sqlite_sequence" WHERE "name" = 'nodes_history_new';
+INSERT INTO "sqlite_sequence" ("name", "seq") SELECT 'nodes_history_new', "seq" FROM "sqlite_sequence" WHERE "name" = 'nodes_history';
+DELETE FROM "sqlite_sequence" WHERE "name" = 'edges_history_new';
+INSERT INTO "sqlite_sequence" ("name", "seq") SELECT 'edges_history_new', "seq" FROM "sqlite_sequence" WHERE "name" = 'edges_history';
+
+DROP TABLE "node_labels";
+DROP TABLE "edges";
+DROP TABLE "nodes";
+DROP TABLE "edges_history";
+DROP TABLE "nodes_history";
+
+ALTER TABLE "nodes_new" RENAME TO "nodes";
+ALTER TABLE "edges_new" RENAME TO "edges";
+ALTER TABLE "node_labels_new" RENAME TO "node_labels";
+ALTER TABLE "nodes_history_new" RENAME TO "nodes_history";
+ALTER TABLE "edges_history_new" RENAME TO "edges_history";
+
+CREATE UNIQUE INDEX "nodes_id_unique_idx" ON "nodes" ("id");
+CREATE INDEX "nodes_uri_idx" ON "nodes" ("uri");
+CREATE INDEX "nodes_deleted_at_idx" ON "nodes" ("deleted_at");
+CREATE INDEX "nodes_namespace_uri_idx" ON "nodes" ("namespace", "uri");
+CREATE UNIQUE INDEX "nodes_key_idx" ON "nodes" ("key");
+
+CREATE UNIQUE INDEX "edges_id_unique_idx" ON "edges" ("id");
+CREATE UNIQUE INDEX "edges_from_node_id_to_node_id_unique_idx" ON "edges" ("from_node_id", "to_node_id") WHERE "deleted_at" IS NULL;
+CREATE INDEX "edges_uri_idx" ON "edges" ("uri");
+CREATE INDEX "edges_deleted_at_idx" ON "edges" ("deleted_at");
+CREATE INDEX "edges_from_node_id_uri_idx" ON "edges" ("from_node_id", "uri");
+CREATE INDEX "edges_to_node_id_uri_idx" ON "edges" ("to_node_id", "uri");
+CREATE INDEX "edges_namespace_uri_idx" ON "edges" ("namespace", "uri");
+
+CREATE INDEX "node_labels_label_node_id_idx" ON "node_labels" ("label", "node_id");
+
+CREATE INDEX "nodes_history_node_id_idx" ON "nodes_history" ("node_id");
+CREATE INDEX "nodes_history_recorded_at_idx" ON "nodes_history" ("recorded_at");
+CREATE INDEX "edges_history_edge_id_idx" ON "edges_history" ("edge_id");
+CREATE INDEX "edges_history_recorded_at_idx" ON "edges_history" ("recorded_at");
+
+CREATE TRIGGER "nodes_history_update_trigger" AFTER UPDATE OF "data" ON "nodes"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "nodes_history" ("node_id", "namespace", "key", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."namespace", OLD."key", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+CREATE TRIGGER "nodes_history_delete_trigger" BEFORE DELETE ON "nodes"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "nodes_history" ("node_id", "namespace", "key", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."namespace", OLD."key", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+CREATE TRIGGER "edges_history_update_trigger" AFTER UPDATE OF "data" ON "edges"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "edges_history" ("edge_id", "namespace", "from_node_id", "to_node_id", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."namespace", OLD."from_node_id", OLD."to_node_id", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+CREATE TRIGGER "edges_history_delete_trigger" BEFORE DELETE ON "edges"
+WHEN EXISTS (SELECT 1 FROM "settings" WHERE "key" = 'history' AND "value" = 'true')
+BEGIN
+  INSERT INTO "edges_history" ("edge_id", "namespace", "from_node_id", "to_node_id", "uri", "data", "deleted_at", "updated_at", "created_at")
+  VALUES (OLD."id", OLD."namespace", OLD."from_node_id", OLD."to_node_id", OLD."uri", OLD."data", OLD."deleted_at", OLD."updated_at", OLD."created_at");
+END;
+
+CREATE TRIGGER "edges_namespace_insert_trigger" BEFORE INSERT ON "edges"
+WHEN NOT NEW."cross_namespace" AND (
+  (SELECT "namespace" FROM "nodes" WHERE "id" = NEW."from_node_id") != NEW."namespace" OR
+  (SELECT "namespace" FROM "nodes" WHERE "id" = NEW."to_node_id") != NEW."namespace"
+)
+BEGIN
+  SELECT RAISE(ABORT, 'edge crosses namespaces');
+END;
@@ -0,0 +1,2 @@
+DROP INDEX "nodes_key_idx";
+CREATE UNIQUE INDEX "nodes_namespace_key_idx" ON "nodes" ("namespace", "key");
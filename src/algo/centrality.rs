@@ -0,0 +1,70 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::model::neighbor::Direction;
+
+use super::graph::Graph;
+
+// degree divided by the number of other nodes
+pub fn degree_centrality(graph: &Graph, direction: Direction) -> HashMap<i64, f64> {
+  let n = graph.node_count();
+  let scale = if n > 1 { 1.0 / (n - 1) as f64 } else { 1.0 };
+  let scores = (0..n)
+    .map(|i| {
+      let degree = match direction {
+        Direction::Outgoing => graph.out_neighbors(i).len(),
+        Direction::Incoming => graph.in_neighbors(i).len(),
+        Direction::Both => graph.out_neighbors(i).len() + graph.in_neighbors(i).len(),
+      };
+      degree as f64 * scale
+    })
+    .collect();
+  graph.by_node_id(scores)
+}
+
+// Brandes' algorithm over directed, unweighted shortest paths. Scores are not
+// normalized, divide by (n - 1)(n - 2) to compare graphs of different sizes.
+pub fn betweenness_centrality(graph: &Graph) -> HashMap<i64, f64> {
+  let n = graph.node_count();
+  let mut scores = vec![0.0; n];
+  let mut stack = Vec::with_capacity(n);
+  let mut queue = VecDeque::with_capacity(n);
+  let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+  let mut paths = vec![0.0f64; n];
+  let mut distance = vec![-1i64; n];
+  let mut dependency = vec![0.0; n];
+
+  for source in 0..n {
+    stack.clear();
+    for i in 0..n {
+      predecessors[i].clear();
+      paths[i] = 0.0;
+      distance[i] = -1;
+      dependency[i] = 0.0;
+    }
+    paths[source] = 1.0;
+    distance[source] = 0;
+    queue.push_back(source);
+    while let Some(node) = queue.pop_front() {
+      stack.push(node);
+      for &target in graph.out_neighbors(node) {
+        if distance[target] < 0 {
+          distance[target] = distance[node] + 1;
+          queue.push_back(target);
+        }
+        if distance[target] == distance[node] + 1 {
+          paths[target] += paths[node];
+          predecessors[target].push(node);
+        }
+      }
+    }
+    while let Some(node) = stack.pop() {
+      for &predecessor in &predecessors[node] {
+        dependency[predecessor] += paths[predecessor] / paths[node] * (1.0 + dependency[node]);
+      }
+      if node != source {
+        scores[node] += dependency[node];
+      }
+    }
+  }
+  graph.by_node_id(scores)
+}
@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::repo;
+
+use super::{graph::Graph, relabel};
+
+#[derive(Debug, Clone, Copy)]
+pub struct LouvainOptions {
+  // values above 1 favour smaller communities
+  pub resolution: f64,
+  pub max_passes: usize,
+}
+
+impl Default for LouvainOptions {
+  fn default() -> Self {
+    Self {
+      resolution: 1.0,
+      max_passes: 10,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LabelPropagationOptions {
+  pub max_iterations: usize,
+  pub seed: u64,
+}
+
+impl Default for LabelPropagationOptions {
+  fn default() -> Self {
+    Self {
+      max_iterations: 20,
+      seed: 0,
+    }
+  }
+}
+
+// sweeps over all nodes before giving up on convergence
+const MAX_SWEEPS: usize = 100;
+
+// Symmetric weighted adjacency, self loops are stored once and count twice
+// towards the degree like any other edge seen from both ends.
+type Adjacency = Vec<Vec<(usize, f64)>>;
+
+// Edge direction is ignored and edge weights are used when the graph was
+// loaded with `Graph::load_weighted`. Communities are labelled 0.. in order of their
+// first node in the projection.
+pub fn louvain(graph: &Graph, options: LouvainOptions) -> HashMap<i64, usize> {
+  let n = graph.node_count();
+  let mut adjacency = undirected(graph);
+  let mut membership: Vec<usize> = (0..n).collect();
+  for _ in 0..options.max_passes {
+    let Some(community) = local_moving(&adjacency, options.resolution) else {
+      break;
+    };
+    let community = relabel(&community);
+    for member in membership.iter_mut() {
+      *member = community[*member];
+    }
+    adjacency = aggregate(&adjacency, &community);
+  }
+  graph.by_node_id(relabel(&membership))
+}
+
+// Nodes are visited in a shuffled order each iteration and ties between
+// equally frequent labels are broken at random, both drawn from `seed` so
+// results are reproducible.
+pub fn label_propagation(graph: &Graph, options: LabelPropagationOptions) -> HashMap<i64, usize> {
+  let n = graph.node_count();
+  let mut random = SplitMix64(options.seed);
+  let mut labels: Vec<usize> = (0..n).collect();
+  let mut order: Vec<usize> = (0..n).collect();
+  for _ in 0..options.max_iterations {
+    for i in (1..n).rev() {
+      order.swap(i, random.below(i + 1));
+    }
+    let mut changed = false;
+    for &i in &order {
+      let mut counts = BTreeMap::new();
+      for &j in graph.out_neighbors(i).iter().chain(graph.in_neighbors(i)) {
+        if j != i {
+          *counts.entry(labels[j]).or_insert(0) += 1;
+        }
+      }
+      let Some(&max) = counts.values().max() else {
+        continue;
+      };
+      // a node keeps its label while it is among the most frequent ones
+      if counts.get(&labels[i]) == Some(&max) {
+        continue;
+      }
+      let candidates: Vec<usize> = counts
+        .into_iter()
+        .filter(|&(_, count)| count == max)
+        .map(|(label, _)| label)
+        .collect();
+      labels[i] = candidates[random.below(candidates.len())];
+      changed = true;
+    }
+    if !changed {
+      break;
+    }
+  }
+  graph.by_node_id(relabel(&labels))
+}
+
+// Creates one `node_uri` node per community holding `{"community", "size"}`
+// and links every member to it with an `edge_uri` edge. Soft deleted members
+// are left out. The node is created in the namespace its members share, or in
+// the default namespace with cross namespace edges when they span several.
+// Returns the id of the node created for each community with live members.
+pub async fn write_community_nodes(
+  pool: &sqlx::SqlitePool,
+  communities: &HashMap<i64, usize>,
+  node_uri: &str,
+  edge_uri: &str,
+) -> sqlx::Result<HashMap<usize, i64>> {
+  let mut members: BTreeMap<usize, Vec<i64>> = BTreeMap::new();
+  for (&node_id, &community) in communities {
+    members.entry(community).or_default().push(node_id);
+  }
+  let mut community_nodes = HashMap::new();
+  let mut tx = pool.begin().await?;
+  for (community, node_ids) in members {
+    let live = repo::node::live_node_namespaces(&mut *tx, &node_ids).await?;
+    let Some((_, first)) = live.first() else {
+      continue;
+    };
+    let namespace = if live.iter().all(|(_, namespace)| namespace == first) {
+      first.clone()
+    } else {
+      String::new()
+    };
+    let data = serde_json::json!({"community": community, "size": live.len()});
+    let row = repo::node::create_node_in(&mut *tx, &namespace, node_uri, &data.to_string()).await?;
+    for (node_id, member_namespace) in &live {
+      repo::edge::create_edge_in(
+        &mut *tx,
+        &namespace,
+        *node_id,
+        row.id,
+        edge_uri,
+        None,
+        *member_namespace != namespace,
+      )
+      .await?;
+    }
+    community_nodes.insert(community, row.id);
+  }
+  tx.commit().await?;
+  Ok(community_nodes)
+}
+
+fn undirected(graph: &Graph) -> Adjacency {
+  let mut weights: Vec<HashMap<usize, f64>> = vec![HashMap::new(); graph.node_count()];
+  for i in 0..graph.node_count() {
+    for (&j, &w) in graph.out_neighbors(i).iter().zip(graph.out_weights(i)) {
+      if i == j {
+        *weights[i].entry(i).or_default() += 2.0 * w;
+      } else {
+        *weights[i].entry(j).or_default() += w;
+        *weights[j].entry(i).or_default() += w;
+      }
+    }
+  }
+  weights.into_iter().map(sorted).collect()
+}
+
+// Moves nodes to the neighbouring community with the best modularity gain
+// until a sweep changes nothing. Returns `None` if no node moved at all.
+fn local_moving(adjacency: &Adjacency, resolution: f64) -> Option<Vec<usize>> {
+  let n = adjacency.len();
+  let degrees: Vec<f64> = adjacency
+    .iter()
+    .map(|edges| edges.iter().map(|(_, w)| w).sum())
+    .collect();
+  let total: f64 = degrees.iter().sum();
+  if total == 0.0 {
+    return None;
+  }
+  let mut community: Vec<usize> = (0..n).collect();
+  let mut community_degree = degrees.clone();
+  let mut moved = false;
+
+  for _ in 0..MAX_SWEEPS {
+    let mut changed = false;
+    for i in 0..n {
+      let current = community[i];
+      community_degree[current] -= degrees[i];
+      let mut links: BTreeMap<usize, f64> = BTreeMap::new();
+      links.insert(current, 0.0);
+      for &(j, w) in &adjacency[i] {
+        if j != i {
+          *links.entry(community[j]).or_default() += w;
+        }
+      }
+      let gain = |c: usize, w: f64| w - resolution * community_degree[c] * degrees[i] / total;
+      let mut best = (current, gain(current, links[&current]));
+      for (&c, &w) in &links {
+        let g = gain(c, w);
+        if g > best.1 + 1e-12 {
+          best = (c, g);
+        }
+      }
+      community_degree[best.0] += degrees[i];
+      if best.0 != current {
+        community[i] = best.0;
+        changed = true;
+        moved = true;
+      }
+    }
+    if !changed {
+      break;
+    }
+  }
+  if moved {
+    Some(community)
+  } else {
+    None
+  }
+}
+
+fn aggregate(adjacency: &Adjacency, community: &[usize]) -> Adjacency {
+  let k = community.iter().max().map_or(0, |max| max + 1);
+  let mut weights: Vec<HashMap<usize, f64>> = vec![HashMap::new(); k];
+  for (i, edges) in adjacency.iter().enumerate() {
+    for &(j, w) in edges {
+      *weights[community[i]].entry(community[j]).or_default() += w;
+    }
+  }
+  weights.into_iter().map(sorted).collect()
+}
+
+fn sorted(weights: HashMap<usize, f64>) -> Vec<(usize, f64)> {
+  let mut edges: Vec<(usize, f64)> = weights.into_iter().collect();
+  edges.sort_unstable_by_key(|(j, _)| *j);
+  edges
+}
+
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+  fn below(&mut self, bound: usize) -> usize {
+    self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = self.0;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    ((z ^ (z >> 31)) % bound as u64) as usize
+  }
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use super::{graph::Graph, relabel};
+
+// Components are labelled 0.. in order of their first node in the projection.
+pub fn weakly_connected_components(graph: &Graph) -> HashMap<i64, usize> {
+  let n = graph.node_count();
+  let mut parent: Vec<usize> = (0..n).collect();
+  fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+      parent[i] = parent[parent[i]];
+      i = parent[i];
+    }
+    i
+  }
+  for i in 0..n {
+    for &j in graph.out_neighbors(i) {
+      let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+      if a != b {
+        parent[a.max(b)] = a.min(b);
+      }
+    }
+  }
+  let roots: Vec<usize> = (0..n).map(|i| find(&mut parent, i)).collect();
+  graph.by_node_id(relabel(&roots))
+}
+
+// Tarjan's algorithm with an explicit stack so deep graphs cannot overflow.
+pub fn strongly_connected_components(graph: &Graph) -> HashMap<i64, usize> {
+  let n = graph.node_count();
+  let mut index = vec![usize::MAX; n];
+  let mut low = vec![0; n];
+  let mut on_stack = vec![false; n];
+  let mut stack = Vec::new();
+  let mut component = vec![0; n];
+  let mut next_index = 0;
+  let mut next_component = 0;
+
+  for start in 0..n {
+    if index[start] != usize::MAX {
+      continue;
+    }
+    // (node, position in its adjacency list)
+    let mut calls = vec![(start, 0)];
+    index[start] = next_index;
+    low[start] = next_index;
+    next_index += 1;
+    stack.push(start);
+    on_stack[start] = true;
+
+    while let Some(&mut (node, ref mut position)) = calls.last_mut() {
+      let targets = graph.out_neighbors(node);
+      if let Some(&target) = targets.get(*position) {
+        *position += 1;
+        if index[target] == usize::MAX {
+          index[target] = next_index;
+          low[target] = next_index;
+          next_index += 1;
+          stack.push(target);
+          on_stack[target] = true;
+          calls.push((target, 0));
+        } else if on_stack[target] {
+          low[node] = low[node].min(index[target]);
+        }
+        continue;
+      }
+      calls.pop();
+      if let Some(&(caller, _)) = calls.last() {
+        low[caller] = low[caller].min(low[node]);
+      }
+      if low[node] == index[node] {
+        while let Some(member) = stack.pop() {
+          on_stack[member] = false;
+          component[member] = next_component;
+          if member == node {
+            break;
+          }
+        }
+        next_component += 1;
+      }
+    }
+  }
+  graph.by_node_id(relabel(&component))
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use futures_util::TryStreamExt;
+
+use crate::query::ScopedQuery;
+
+// Compressed sparse row adjacency over the nodes touched by the projected
+// edges. Nodes are addressed by a dense index, `node_id` maps back to ids.
+#[derive(Debug, Default, Clone)]
+pub struct Graph {
+  node_ids: Vec<i64>,
+  index: HashMap<i64, usize>,
+  out_offsets: Vec<usize>,
+  out_targets: Vec<usize>,
+  out_edge_ids: Vec<i64>,
+  out_weights: Vec<f64>,
+  in_offsets: Vec<usize>,
+  in_targets: Vec<usize>,
+}
+
+impl Graph {
+  // `query` selects the projected edges, e.g. `{"edge.uri": {"eq": "follows"}}`
+  pub async fn load(pool: &sqlx::SqlitePool, query: &ScopedQuery) -> sqlx::Result<Self> {
+    Self::load_edges(pool, query, None).await
+  }
+
+  // Weights are read from a JSON path in edge data, e.g. `distance`. Edges
+  // without the field cost 1, negative and non-numeric weights are rejected.
+  pub async fn load_weighted(
+    pool: &sqlx::SqlitePool,
+    query: &ScopedQuery,
+    weight: &str,
+  ) -> sqlx::Result<Self> {
+    Self::load_edges(pool, query, Some(weight)).await
+  }
+
+  async fn load_edges(
+    pool: &sqlx::SqlitePool,
+    query: &ScopedQuery,
+    weight: Option<&str>,
+  ) -> sqlx::Result<Self> {
+    // integer JSON values come back as INTEGER, which does not decode as f64
+    let weight = match weight {
+      Some(weight) => {
+        let path = format!("'$.{}'", weight.replace('\'', "''"));
+        format!("json_type(data, {path}), CAST(json_extract(data, {path}) AS REAL)")
+      }
+      None => "NULL, NULL".to_owned(),
+    };
+    query.validate()?;
+    let sql = format!(
+      "SELECT id, from_node_id, to_node_id, {weight} FROM ({})",
+      query.edges_sql()
+    );
+    let mut rows =
+      sqlx::query_as::<_, (i64, i64, i64, Option<String>, Option<f64>)>(&sql).fetch(pool);
+    let mut edges = Vec::new();
+    while let Some((id, from, to, kind, weight)) = rows.try_next().await? {
+      let weight = match kind.as_deref() {
+        None | Some("null") => 1.0,
+        Some("integer" | "real") => weight.unwrap_or(1.0),
+        Some(kind) => {
+          return Err(sqlx::Error::Decode(
+            format!("edge {id} has non-numeric weight of type {kind}").into(),
+          ))
+        }
+      };
+      if weight < 0.0 {
+        return Err(sqlx::Error::Decode(
+          format!("edge {id} has negative weight {weight}").into(),
+        ));
+      }
+      edges.push((id, from, to, weight));
+    }
+    Ok(Self::from_weighted_edges(edges))
+  }
+
+  pub fn from_edges<I>(edges: I) -> Self
+  where
+    I: IntoIterator<Item = (i64, i64)>,
+  {
+    Self::from_weighted_edges(edges.into_iter().map(|(from, to)| (0, from, to, 1.0)))
+  }
+
+  // (edge id, from node id, to node id, weight)
+  pub fn from_weighted_edges<I>(edges: I) -> Self
+  where
+    I: IntoIterator<Item = (i64, i64, i64, f64)>,
+  {
+    let mut graph = Self::default();
+    let mut from = Vec::new();
+    let mut to = Vec::new();
+    let mut edge_ids = Vec::new();
+    let mut weights = Vec::new();
+    for (edge_id, from_node_id, to_node_id, weight) in edges {
+      from.push(graph.insert(from_node_id));
+      to.push(graph.insert(to_node_id));
+      edge_ids.push(edge_id);
+      weights.push(weight);
+    }
+    let n = graph.node_ids.len();
+    let (offsets, order) = csr(n, &from);
+    graph.out_offsets = offsets;
+    graph.out_targets = order.iter().map(|&e| to[e]).collect();
+    graph.out_edge_ids = order.iter().map(|&e| edge_ids[e]).collect();
+    graph.out_weights = order.iter().map(|&e| weights[e]).collect();
+    let (offsets, order) = csr(n, &to);
+    graph.in_offsets = offsets;
+    graph.in_targets = order.iter().map(|&e| from[e]).collect();
+    graph
+  }
+
+  fn insert(&mut self, node_id: i64) -> usize {
+    *self.index.entry(node_id).or_insert_with(|| {
+      self.node_ids.push(node_id);
+      self.node_ids.len() - 1
+    })
+  }
+
+  pub fn node_count(&self) -> usize {
+    self.node_ids.len()
+  }
+
+  pub fn edge_count(&self) -> usize {
+    self.out_targets.len()
+  }
+
+  pub fn node_id(&self, index: usize) -> i64 {
+    self.node_ids[index]
+  }
+
+  pub fn index_of(&self, node_id: i64) -> Option<usize> {
+    self.index.get(&node_id).copied()
+  }
+
+  pub fn out_neighbors(&self, index: usize) -> &[usize] {
+    &self.out_targets[self.out_offsets[index]..self.out_offsets[index + 1]]
+  }
+
+  // ids of the edges to `out_neighbors`, 0 for graphs built from id pairs
+  pub fn out_edge_ids(&self, index: usize) -> &[i64] {
+    &self.out_edge_ids[self.out_offsets[index]..self.out_offsets[index + 1]]
+  }
+
+  pub fn out_weights(&self, index: usize) -> &[f64] {
+    &self.out_weights[self.out_offsets[index]..self.out_offsets[index + 1]]
+  }
+
+  pub fn in_neighbors(&self, index: usize) -> &[usize] {
+    &self.in_targets[self.in_offsets[index]..self.in_offsets[index + 1]]
+  }
+
+  // maps per-index results back to node ids
+  pub fn by_node_id<V>(&self, values: Vec<V>) -> HashMap<i64, V> {
+    self.node_ids.iter().copied().zip(values).collect()
+  }
+}
+
+// offsets into the edge positions grouped by `keys`, and those positions
+fn csr(n: usize, keys: &[usize]) -> (Vec<usize>, Vec<usize>) {
+  let mut offsets = vec![0; n + 1];
+  for &key in keys {
+    offsets[key + 1] += 1;
+  }
+  for i in 0..n {
+    offsets[i + 1] += offsets[i];
+  }
+  let mut next = offsets.clone();
+  let mut order = vec![0; keys.len()];
+  for (position, &key) in keys.iter().enumerate() {
+    order[next[key]] = position;
+    next[key] += 1;
+  }
+  (offsets, order)
+}
@@ -0,0 +1,67 @@
+pub mod centrality;
+pub mod community;
+pub mod components;
+pub mod graph;
+pub mod pagerank;
+pub mod path;
+pub mod triangles;
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::repo::NOW_MILLIS_SQL;
+
+pub use centrality::{betweenness_centrality, degree_centrality};
+pub use community::{
+  label_propagation, louvain, write_community_nodes, LabelPropagationOptions, LouvainOptions,
+};
+pub use components::{strongly_connected_components, weakly_connected_components};
+pub use graph::Graph;
+pub use pagerank::{pagerank, PageRankOptions};
+pub use path::{a_star, dijkstra, search, shortest_path, Path, PathIds};
+pub use triangles::{triangle_count, Triangles};
+
+// Stores each score under `data.<key>` of its node, e.g. `scores.pagerank`,
+// which also persists community ids.
+pub async fn write_scores<V>(
+  pool: &sqlx::SqlitePool,
+  scores: &HashMap<i64, V>,
+  key: &str,
+) -> sqlx::Result<u64>
+where
+  V: Serialize,
+{
+  let path = format!("$.{key}");
+  let mut updated = 0;
+  let mut tx = pool.begin().await?;
+  for (node_id, score) in scores {
+    let score = match serde_json::to_string(score) {
+      Ok(json) => json,
+      Err(e) => return Err(sqlx::Error::Encode(Box::new(e))),
+    };
+    updated += sqlx::query(&format!(
+      "UPDATE nodes SET data = json_set(data, $1, json($2)), updated_at = {NOW_MILLIS_SQL} WHERE id = $3 AND deleted_at IS NULL;"
+    ))
+    .bind(&path)
+    .bind(score)
+    .bind(node_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+  }
+  tx.commit().await?;
+  Ok(updated)
+}
+
+// Renumbers labels densely in order of first appearance.
+pub(crate) fn relabel(labels: &[usize]) -> Vec<usize> {
+  let mut ids = HashMap::new();
+  labels
+    .iter()
+    .map(|label| {
+      let next = ids.len();
+      *ids.entry(*label).or_insert(next)
+    })
+    .collect()
+}
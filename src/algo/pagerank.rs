@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use super::graph::Graph;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PageRankOptions {
+  pub damping: f64,
+  pub max_iterations: usize,
+  // stops once the L1 change between iterations drops below this
+  pub tolerance: f64,
+}
+
+impl Default for PageRankOptions {
+  fn default() -> Self {
+    Self {
+      damping: 0.85,
+      max_iterations: 100,
+      tolerance: 1e-6,
+    }
+  }
+}
+
+pub fn pagerank(graph: &Graph, options: PageRankOptions) -> HashMap<i64, f64> {
+  let n = graph.node_count();
+  if n == 0 {
+    return HashMap::new();
+  }
+  let base = (1.0 - options.damping) / n as f64;
+  let mut ranks = vec![1.0 / n as f64; n];
+  for _ in 0..options.max_iterations {
+    // rank of nodes without outgoing edges is spread over every node
+    let dangling: f64 = (0..n)
+      .filter(|&i| graph.out_neighbors(i).is_empty())
+      .map(|i| ranks[i])
+      .sum();
+    let mut next = vec![base + options.damping * dangling / n as f64; n];
+    for (i, rank) in ranks.iter().enumerate() {
+      let targets = graph.out_neighbors(i);
+      let share = options.damping * rank / targets.len().max(1) as f64;
+      for &target in targets {
+        next[target] += share;
+      }
+    }
+    let change: f64 = next.iter().zip(&ranks).map(|(a, b)| (a - b).abs()).sum();
+    ranks = next;
+    if change < options.tolerance {
+      break;
+    }
+  }
+  graph.by_node_id(ranks)
+}
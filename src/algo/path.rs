@@ -0,0 +1,228 @@
+use std::{
+  cmp::Ordering,
+  collections::{BinaryHeap, HashMap},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+  model::{
+    edge::{get_edge, Edge},
+    node::{get_node, Node},
+  },
+  query::{Query, QueryExpr, QueryField, QueryOp, QueryValue},
+};
+
+use super::graph::Graph;
+
+// node ids kept per query when preloading node data for A*
+const PRELOAD_BATCH_SIZE: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PathIds {
+  pub node_ids: Vec<i64>,
+  pub edge_ids: Vec<i64>,
+  pub cost: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Path<N, E> {
+  pub nodes: Vec<Node<N>>,
+  pub edges: Vec<Edge<E>>,
+  pub cost: f64,
+}
+
+pub fn dijkstra(graph: &Graph, from_node_id: i64, to_node_id: i64) -> Option<PathIds> {
+  search(graph, from_node_id, to_node_id, |_| 0.0)
+}
+
+// A* over the graph's edge weights. `heuristic` estimates the remaining cost
+// from a node index to the target and must never overestimate it for the
+// result to be the cheapest path; a constant 0 makes this Dijkstra.
+pub fn search<H>(
+  graph: &Graph,
+  from_node_id: i64,
+  to_node_id: i64,
+  mut heuristic: H,
+) -> Option<PathIds>
+where
+  H: FnMut(usize) -> f64,
+{
+  let source = graph.index_of(from_node_id)?;
+  let target = graph.index_of(to_node_id)?;
+  let mut cost = vec![f64::INFINITY; graph.node_count()];
+  // (previous node, position of the edge in the previous node's out edges)
+  let mut previous: Vec<Option<(usize, usize)>> = vec![None; graph.node_count()];
+  let mut queue = BinaryHeap::new();
+  cost[source] = 0.0;
+  queue.push(Candidate {
+    estimate: heuristic(source),
+    cost: 0.0,
+    node: source,
+  });
+
+  while let Some(Candidate { cost: c, node, .. }) = queue.pop() {
+    if node == target {
+      break;
+    }
+    if c > cost[node] {
+      continue;
+    }
+    let edges = graph
+      .out_neighbors(node)
+      .iter()
+      .zip(graph.out_weights(node));
+    for (position, (&next, &weight)) in edges.enumerate() {
+      let next_cost = c + weight;
+      if next_cost < cost[next] {
+        cost[next] = next_cost;
+        previous[next] = Some((node, position));
+        queue.push(Candidate {
+          estimate: next_cost + heuristic(next),
+          cost: next_cost,
+          node: next,
+        });
+      }
+    }
+  }
+
+  if cost[target].is_infinite() {
+    return None;
+  }
+  let mut node_ids = vec![graph.node_id(target)];
+  let mut edge_ids = Vec::new();
+  let mut node = target;
+  while let Some((prev, position)) = previous[node] {
+    node_ids.push(graph.node_id(prev));
+    edge_ids.push(graph.out_edge_ids(prev)[position]);
+    node = prev;
+  }
+  node_ids.reverse();
+  edge_ids.reverse();
+  Some(PathIds {
+    node_ids,
+    edge_ids,
+    cost: cost[target],
+  })
+}
+
+pub async fn shortest_path<N, E>(
+  pool: &sqlx::SqlitePool,
+  graph: &Graph,
+  from_node_id: i64,
+  to_node_id: i64,
+) -> sqlx::Result<Option<Path<N, E>>>
+where
+  N: DeserializeOwned,
+  E: DeserializeOwned,
+{
+  match dijkstra(graph, from_node_id, to_node_id) {
+    Some(ids) => Ok(Some(load_path(pool, ids).await?)),
+    None => Ok(None),
+  }
+}
+
+// `heuristic(node data, target data)` estimates the remaining cost, e.g. the
+// straight line distance between coordinates stored on both nodes. Data of
+// every node in the graph is loaded up front.
+pub async fn a_star<N, E, H>(
+  pool: &sqlx::SqlitePool,
+  graph: &Graph,
+  from_node_id: i64,
+  to_node_id: i64,
+  heuristic: H,
+) -> sqlx::Result<Option<Path<N, E>>>
+where
+  N: DeserializeOwned,
+  E: DeserializeOwned,
+  H: Fn(&N, &N) -> f64,
+{
+  let Some(target) = get_node::<N>(pool, to_node_id).await? else {
+    return Ok(None);
+  };
+  let mut estimates = vec![0.0; graph.node_count()];
+  let node_ids: Vec<i64> = (0..graph.node_count()).map(|i| graph.node_id(i)).collect();
+  for chunk in node_ids.chunks(PRELOAD_BATCH_SIZE) {
+    let mut conditions = HashMap::new();
+    conditions.insert(
+      QueryField::NodeId,
+      QueryExpr::Op(QueryOp::In(
+        chunk
+          .iter()
+          .map(|&id| QueryExpr::Value(QueryValue::Number(id.into())))
+          .collect(),
+      )),
+    );
+    for node in Query::from(conditions)
+      .with_deleted(true)
+      .nodes::<N>(pool)
+      .await?
+    {
+      if let Some(index) = graph.index_of(node.id) {
+        estimates[index] = heuristic(&node.data, &target.data);
+      }
+    }
+  }
+  match search(graph, from_node_id, to_node_id, |index| estimates[index]) {
+    Some(ids) => Ok(Some(load_path(pool, ids).await?)),
+    None => Ok(None),
+  }
+}
+
+async fn load_path<N, E>(pool: &sqlx::SqlitePool, ids: PathIds) -> sqlx::Result<Path<N, E>>
+where
+  N: DeserializeOwned,
+  E: DeserializeOwned,
+{
+  let mut path = Path {
+    nodes: Vec::with_capacity(ids.node_ids.len()),
+    edges: Vec::with_capacity(ids.edge_ids.len()),
+    cost: ids.cost,
+  };
+  for node_id in ids.node_ids {
+    path.nodes.push(
+      get_node(pool, node_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?,
+    );
+  }
+  for edge_id in ids.edge_ids {
+    path.edges.push(
+      get_edge(pool, edge_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?,
+    );
+  }
+  Ok(path)
+}
+
+struct Candidate {
+  estimate: f64,
+  cost: f64,
+  node: usize,
+}
+
+impl PartialEq for Candidate {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+// reversed so the max-heap pops the lowest estimate first
+impl Ord for Candidate {
+  fn cmp(&self, other: &Self) -> Ordering {
+    other
+      .estimate
+      .partial_cmp(&self.estimate)
+      .unwrap_or(Ordering::Equal)
+      .then_with(|| other.node.cmp(&self.node))
+  }
+}
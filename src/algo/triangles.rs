@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use super::graph::Graph;
+
+#[derive(Debug, Default, Clone)]
+pub struct Triangles {
+  pub total: usize,
+  pub per_node: HashMap<i64, usize>,
+}
+
+// Edge direction is ignored and self loops are skipped. Each triangle is found
+// once by only following neighbours that rank higher by (degree, index).
+pub fn triangle_count(graph: &Graph) -> Triangles {
+  let n = graph.node_count();
+  let neighbors: Vec<Vec<usize>> = (0..n)
+    .map(|i| {
+      let mut adjacent: Vec<usize> = graph
+        .out_neighbors(i)
+        .iter()
+        .chain(graph.in_neighbors(i))
+        .copied()
+        .filter(|&j| j != i)
+        .collect();
+      adjacent.sort_unstable();
+      adjacent.dedup();
+      adjacent
+    })
+    .collect();
+  let rank = |i: usize| (neighbors[i].len(), i);
+  let higher: Vec<Vec<usize>> = (0..n)
+    .map(|i| {
+      let mut adjacent: Vec<usize> = neighbors[i]
+        .iter()
+        .copied()
+        .filter(|&j| rank(j) > rank(i))
+        .collect();
+      adjacent.sort_unstable();
+      adjacent
+    })
+    .collect();
+
+  let mut counts = vec![0; n];
+  let mut total = 0;
+  for a in 0..n {
+    for &b in &higher[a] {
+      for c in intersect(&higher[a], &higher[b]) {
+        counts[a] += 1;
+        counts[b] += 1;
+        counts[c] += 1;
+        total += 1;
+      }
+    }
+  }
+  Triangles {
+    total,
+    per_node: graph.by_node_id(counts),
+  }
+}
+
+fn intersect<'a>(a: &'a [usize], b: &'a [usize]) -> impl Iterator<Item = usize> + 'a {
+  let (mut i, mut j) = (0, 0);
+  std::iter::from_fn(move || {
+    while i < a.len() && j < b.len() {
+      match a[i].cmp(&b[j]) {
+        std::cmp::Ordering::Less => i += 1,
+        std::cmp::Ordering::Greater => j += 1,
+        std::cmp::Ordering::Equal => {
+          i += 1;
+          j += 1;
+          return Some(a[i - 1]);
+        }
+      }
+    }
+    None
+  })
+}
@@ -0,0 +1,285 @@
+use std::{
+  error::Error,
+  fs::File,
+  io::{self, BufReader, Read},
+  path::{Path, PathBuf},
+};
+
+use clap::{Args, Parser, Subcommand};
+use hypergraphsql::*;
+use serde_json::Value;
+
+use output::{
+  print_edges, print_found, print_json, print_node_edges, print_nodes, print_uri_counts, Format,
+  QueryKind,
+};
+
+mod output;
+mod repl;
+
+#[derive(Parser)]
+#[command(
+  name = "hypergraphsql",
+  version,
+  about = "Inspect and edit a hypergraphsql database"
+)]
+struct Cli {
+  #[arg(short, long, default_value = "hypergraphsql.db")]
+  database: String,
+  #[arg(long)]
+  create: bool,
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// List node and edge uris with their counts
+  Uris,
+  /// Get, create, update, delete or restore nodes
+  #[command(subcommand)]
+  Node(NodeCommand),
+  /// Get, create, update, delete or restore edges
+  #[command(subcommand)]
+  Edge(EdgeCommand),
+  /// Run a Query read as JSON from a file or stdin
+  Query(QueryArgs),
+  /// Export the graph as JSON Lines
+  Export {
+    file: Option<PathBuf>,
+    /// Query JSON file restricting the exported nodes
+    #[arg(long)]
+    query: Option<PathBuf>,
+  },
+  /// Import JSON Lines produced by `export`
+  Import { file: Option<PathBuf> },
+  /// Start an interactive shell for Query JSON and MATCH patterns
+  Repl,
+  /// Serve the graph as a REST API
+  #[cfg(feature = "server")]
+  Serve {
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    addr: String,
+  },
+}
+
+#[derive(Subcommand)]
+enum NodeCommand {
+  Get {
+    id: i64,
+  },
+  /// Create a node, reading its data JSON from stdin when omitted or `-`
+  Create {
+    uri: String,
+    data: Option<String>,
+  },
+  Update {
+    id: i64,
+    data: Option<String>,
+  },
+  Delete {
+    id: i64,
+    #[arg(long)]
+    soft: bool,
+  },
+  Restore {
+    id: i64,
+  },
+}
+
+#[derive(Subcommand)]
+enum EdgeCommand {
+  Get {
+    id: i64,
+  },
+  /// Create an edge, with no data unless given as JSON or `-` for stdin
+  Create {
+    from_node_id: i64,
+    to_node_id: i64,
+    uri: String,
+    data: Option<String>,
+  },
+  Update {
+    id: i64,
+    data: Option<String>,
+  },
+  Delete {
+    id: i64,
+    #[arg(long)]
+    soft: bool,
+  },
+  Restore {
+    id: i64,
+  },
+}
+
+#[derive(Args)]
+struct QueryArgs {
+  /// Query JSON file, stdin when omitted or `-`
+  file: Option<PathBuf>,
+  #[arg(short, long, value_enum, default_value = "node-edges")]
+  kind: QueryKind,
+  #[arg(short, long, value_enum, default_value = "json")]
+  format: Format,
+  #[arg(long)]
+  with_deleted: bool,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+  let cli = Cli::parse();
+  if let Err(e) = run(cli).await {
+    eprintln!("error: {e}");
+    std::process::exit(1);
+  }
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+  let pool = create(&cli.database, cli.create).await?;
+  match cli.command {
+    Command::Uris => print_uri_counts(&uri_counts(&pool).await?),
+    Command::Node(command) => match command {
+      NodeCommand::Get { id } => print_found(get_node::<Value>(&pool, id).await?, "node", id)?,
+      NodeCommand::Create { uri, data } => {
+        print_json(&create_node(&pool, &uri, read_data(data.as_deref())?).await?)?
+      }
+      NodeCommand::Update { id, data } => {
+        print_json(&update_node(&pool, id, read_data(data.as_deref())?).await?)?
+      }
+      NodeCommand::Delete { id, soft } => {
+        let node = if soft {
+          soft_delete_node::<Value>(&pool, id).await?
+        } else {
+          delete_node::<Value>(&pool, id).await?
+        };
+        print_found(node, "node", id)?
+      }
+      NodeCommand::Restore { id } => {
+        print_found(restore_node::<Value>(&pool, id).await?, "deleted node", id)?
+      }
+    },
+    Command::Edge(command) => match command {
+      EdgeCommand::Get { id } => print_found(get_edge::<Value>(&pool, id).await?, "edge", id)?,
+      EdgeCommand::Create {
+        from_node_id,
+        to_node_id,
+        uri,
+        data,
+      } => {
+        let data = match data {
+          Some(data) => Some(read_data(Some(&data))?),
+          None => None,
+        };
+        print_json(&create_edge_with_ids(&pool, from_node_id, to_node_id, &uri, data).await?)?
+      }
+      EdgeCommand::Update { id, data } => {
+        let data = read_data(data.as_deref())?;
+        print_json(&update_edge(&pool, id, Some(data)).await?)?
+      }
+      EdgeCommand::Delete { id, soft } => {
+        let edge = if soft {
+          soft_delete_edge::<Value>(&pool, id).await?
+        } else {
+          delete_edge::<Value>(&pool, id).await?
+        };
+        print_found(edge, "edge", id)?
+      }
+      EdgeCommand::Restore { id } => {
+        print_found(restore_edge::<Value>(&pool, id).await?, "deleted edge", id)?
+      }
+    },
+    Command::Query(args) => run_query(&pool, args).await?,
+    Command::Repl => repl::run(&pool).await?,
+    #[cfg(feature = "server")]
+    Command::Serve { addr } => {
+      eprintln!("listening on {addr}");
+      hypergraphsql::server::serve(pool, addr.as_str()).await?
+    }
+    Command::Export { file, query } => {
+      let query = match query {
+        Some(path) => Some(serde_json::from_reader::<_, ScopedQuery>(BufReader::new(
+          File::open(path)?,
+        ))?),
+        None => None,
+      };
+      let report = match file {
+        Some(path) => {
+          export_jsonl(
+            &pool,
+            io::BufWriter::new(File::create(path)?),
+            query.as_ref(),
+          )
+          .await?
+        }
+        None => export_jsonl(&pool, io::stdout().lock(), query.as_ref()).await?,
+      };
+      eprintln!(
+        "exported {} nodes, {} edges and {} labels",
+        report.nodes, report.edges, report.labels
+      );
+    }
+    Command::Import { file } => {
+      let report = match file {
+        Some(path) => import_jsonl(&pool, BufReader::new(File::open(path)?)).await?,
+        None => import_jsonl(&pool, io::stdin().lock()).await?,
+      };
+      for failure in &report.failures {
+        eprintln!("line {}: {}", failure.line, failure.error);
+      }
+      eprintln!(
+        "imported {} nodes, {} edges and {} labels, {} failures",
+        report.nodes,
+        report.edges,
+        report.labels,
+        report.failures.len()
+      );
+    }
+  }
+  Ok(())
+}
+
+async fn run_query(pool: &sqlx::SqlitePool, args: QueryArgs) -> Result<(), Box<dyn Error>> {
+  let json = read_input(args.file.as_deref())?;
+  let mut query = serde_json::from_str::<ScopedQuery>(&json)?;
+  if args.with_deleted {
+    query = query.with_deleted(true);
+  }
+  execute_query(pool, &query, args.kind, args.format).await
+}
+
+async fn execute_query(
+  pool: &sqlx::SqlitePool,
+  query: &ScopedQuery,
+  kind: QueryKind,
+  format: Format,
+) -> Result<(), Box<dyn Error>> {
+  match kind {
+    QueryKind::Nodes => print_nodes(&query.nodes::<Value>(pool).await?, format),
+    QueryKind::Edges => print_edges(&query.edges::<Value>(pool).await?, format),
+    QueryKind::NodeEdges => print_node_edges(
+      &query.node_edges::<Value, Value, Value>(pool).await?,
+      format,
+    ),
+  }
+}
+
+fn read_input(path: Option<&Path>) -> io::Result<String> {
+  let mut input = String::new();
+  match path {
+    Some(path) if path != Path::new("-") => {
+      File::open(path)?.read_to_string(&mut input)?;
+    }
+    _ => {
+      io::stdin().read_to_string(&mut input)?;
+    }
+  }
+  Ok(input)
+}
+
+fn read_data(data: Option<&str>) -> Result<Value, Box<dyn Error>> {
+  let json = match data {
+    Some(data) if data != "-" => data.to_owned(),
+    _ => read_input(None)?,
+  };
+  Ok(serde_json::from_str(&json)?)
+}
@@ -0,0 +1,229 @@
+use std::{
+  error::Error,
+  io::{self, Write},
+};
+
+use clap::ValueEnum;
+use hypergraphsql::*;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum QueryKind {
+  Nodes,
+  Edges,
+  NodeEdges,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+  Json,
+  Table,
+}
+
+pub fn print_nodes(nodes: &[Node<Value>], format: Format) -> Result<(), Box<dyn Error>> {
+  match format {
+    Format::Json => print_json(nodes),
+    Format::Table => {
+      print_table(
+        &[
+          "id",
+          "uri",
+          "data",
+          "deleted_at",
+          "updated_at",
+          "created_at",
+        ],
+        &nodes
+          .iter()
+          .map(|node| {
+            vec![
+              node.id.to_string(),
+              node.uri.clone(),
+              node.data.to_string(),
+              optional(node.deleted_at.map(|t| t.to_rfc3339())),
+              node.updated_at.to_rfc3339(),
+              node.created_at.to_rfc3339(),
+            ]
+          })
+          .collect::<Vec<_>>(),
+      );
+      Ok(())
+    }
+  }
+}
+
+pub fn print_edges(edges: &[Edge<Value>], format: Format) -> Result<(), Box<dyn Error>> {
+  match format {
+    Format::Json => print_json(edges),
+    Format::Table => {
+      print_table(
+        &[
+          "id",
+          "from_node_id",
+          "to_node_id",
+          "uri",
+          "data",
+          "deleted_at",
+          "updated_at",
+          "created_at",
+        ],
+        &edges
+          .iter()
+          .map(|edge| {
+            vec![
+              edge.id.to_string(),
+              edge.from_node_id.to_string(),
+              edge.to_node_id.to_string(),
+              edge.uri.clone(),
+              optional(edge.data.as_ref()),
+              optional(edge.deleted_at.map(|t| t.to_rfc3339())),
+              edge.updated_at.to_rfc3339(),
+              edge.created_at.to_rfc3339(),
+            ]
+          })
+          .collect::<Vec<_>>(),
+      );
+      Ok(())
+    }
+  }
+}
+
+pub fn print_node_edges(
+  node_edges: &[NodeEdge<Value, Value, Value>],
+  format: Format,
+) -> Result<(), Box<dyn Error>> {
+  match format {
+    Format::Json => print_json(node_edges),
+    Format::Table => {
+      print_table(
+        &[
+          "from_node.id",
+          "from_node.uri",
+          "edge.id",
+          "edge.uri",
+          "to_node.id",
+          "to_node.uri",
+        ],
+        &node_edges
+          .iter()
+          .map(|node_edge| {
+            vec![
+              node_edge.from_node.id.to_string(),
+              node_edge.from_node.uri.clone(),
+              node_edge.edge.id.to_string(),
+              node_edge.edge.uri.clone(),
+              node_edge.to_node.id.to_string(),
+              node_edge.to_node.uri.clone(),
+            ]
+          })
+          .collect::<Vec<_>>(),
+      );
+      Ok(())
+    }
+  }
+}
+
+pub fn print_bindings(
+  rows: &[Bindings],
+  variables: &[String],
+  format: Format,
+) -> Result<(), Box<dyn Error>> {
+  match format {
+    Format::Json => print_json(rows),
+    Format::Table => {
+      let headers = variables.iter().map(String::as_str).collect::<Vec<_>>();
+      print_table(
+        &headers,
+        &rows
+          .iter()
+          .map(|row| {
+            variables
+              .iter()
+              .map(|variable| match row.get(variable) {
+                Some(Binding::Node(node)) => format!("{}#{} {}", node.uri, node.id, node.data),
+                Some(Binding::Edge(edge)) => format!(
+                  "{}#{} {}->{}",
+                  edge.uri, edge.id, edge.from_node_id, edge.to_node_id
+                ),
+                None => String::new(),
+              })
+              .collect()
+          })
+          .collect::<Vec<_>>(),
+      );
+      Ok(())
+    }
+  }
+}
+
+pub fn print_uri_counts(counts: &UriCounts) {
+  let mut rows = Vec::new();
+  for (kind, counts) in [("node", &counts.nodes), ("edge", &counts.edges)] {
+    for count in counts {
+      rows.push(vec![
+        kind.to_owned(),
+        count.uri.clone(),
+        count.count.to_string(),
+      ]);
+    }
+  }
+  print_table(&["type", "uri", "count"], &rows);
+}
+
+pub fn print_json<T>(value: &T) -> Result<(), Box<dyn Error>>
+where
+  T: Serialize + ?Sized,
+{
+  let mut stdout = io::stdout().lock();
+  serde_json::to_writer_pretty(&mut stdout, value)?;
+  writeln!(stdout)?;
+  Ok(())
+}
+
+pub fn print_found<T>(value: Option<T>, kind: &str, id: i64) -> Result<(), Box<dyn Error>>
+where
+  T: Serialize,
+{
+  match value {
+    Some(value) => print_json(&value),
+    None => Err(format!("no {kind} with id {id}").into()),
+  }
+}
+
+pub fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+  let mut widths = headers
+    .iter()
+    .map(|header| header.len())
+    .collect::<Vec<_>>();
+  for row in rows {
+    for (width, cell) in widths.iter_mut().zip(row) {
+      *width = (*width).max(cell.chars().count());
+    }
+  }
+  let line = |cells: &mut dyn Iterator<Item = &str>| {
+    cells
+      .zip(&widths)
+      .map(|(cell, width)| format!("{cell:<width$}"))
+      .collect::<Vec<_>>()
+      .join("  ")
+      .trim_end()
+      .to_owned()
+  };
+  let rules = widths
+    .iter()
+    .map(|width| "-".repeat(*width))
+    .collect::<Vec<_>>();
+  println!("{}", line(&mut headers.iter().copied()));
+  println!("{}", line(&mut rules.iter().map(String::as_str)));
+  for row in rows {
+    println!("{}", line(&mut row.iter().map(String::as_str)));
+  }
+}
+
+fn optional<T>(value: Option<T>) -> String
+where
+  T: ToString,
+{
+  value.map(|value| value.to_string()).unwrap_or_default()
+}
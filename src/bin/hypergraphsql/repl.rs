@@ -0,0 +1,181 @@
+use std::{error::Error, path::PathBuf};
+
+use hypergraphsql::*;
+use rustyline::{error::ReadlineError, DefaultEditor};
+use serde_json::Value;
+
+use crate::{
+  execute_query,
+  output::{print_bindings, print_found, print_uri_counts, Format, QueryKind},
+};
+
+static HELP: &str = r#"Enter Query JSON, e.g. {"from_node.uri": {"eq": "user"}}, or a pattern,
+e.g. MATCH (a:user)-[:follows]->(b) RETURN b. JSON may span several lines.
+
+  :mode nodes|edges|node-edges  what Query JSON selects (default node-edges)
+  :format json|table            how results are printed (default json)
+  :sql on|off                   print the generated SQL (default on)
+  :deleted on|off               include soft deleted rows (default off)
+  :node <id>, :edge <id>        print a single node or edge
+  :uris                         list uris with their counts
+  :help                         show this message
+  :quit                         exit"#;
+
+struct State {
+  kind: QueryKind,
+  format: Format,
+  show_sql: bool,
+  with_deleted: bool,
+}
+
+pub async fn run(pool: &sqlx::SqlitePool) -> Result<(), Box<dyn Error>> {
+  let mut editor = DefaultEditor::new()?;
+  let history =
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".hypergraphsql_history"));
+  if let Some(history) = &history {
+    // a missing history file just means this is the first session
+    let _ = editor.load_history(history);
+  }
+  let mut state = State {
+    kind: QueryKind::NodeEdges,
+    format: Format::Json,
+    show_sql: true,
+    with_deleted: false,
+  };
+  println!("type :help for help");
+
+  let mut buffer = String::new();
+  loop {
+    let prompt = if buffer.is_empty() {
+      "hypergraphsql> "
+    } else {
+      "...> "
+    };
+    match editor.readline(prompt) {
+      Ok(line) => {
+        buffer.push_str(&line);
+        buffer.push('\n');
+        if !is_complete(&buffer) {
+          continue;
+        }
+        let input = std::mem::take(&mut buffer);
+        let input = input.trim();
+        if input.is_empty() {
+          continue;
+        }
+        editor.add_history_entry(input)?;
+        match execute(pool, &mut state, input).await {
+          Ok(true) => {}
+          Ok(false) => break,
+          Err(e) => eprintln!("error: {e}"),
+        }
+      }
+      Err(ReadlineError::Interrupted) => buffer.clear(),
+      Err(ReadlineError::Eof) => break,
+      Err(e) => return Err(e.into()),
+    }
+  }
+
+  if let Some(history) = &history {
+    editor.save_history(history)?;
+  }
+  Ok(())
+}
+
+// returns false once the user asks to quit
+async fn execute(
+  pool: &sqlx::SqlitePool,
+  state: &mut State,
+  input: &str,
+) -> Result<bool, Box<dyn Error>> {
+  if let Some(command) = input.strip_prefix(':') {
+    let mut args = command.split_whitespace();
+    let name = args.next().unwrap_or_default();
+    let arg = args.next();
+    match (name, arg) {
+      ("q", _) | ("quit", _) | ("exit", _) => return Ok(false),
+      ("help", _) => println!("{HELP}"),
+      ("mode", Some("nodes")) => state.kind = QueryKind::Nodes,
+      ("mode", Some("edges")) => state.kind = QueryKind::Edges,
+      ("mode", Some("node-edges")) => state.kind = QueryKind::NodeEdges,
+      ("format", Some("json")) => state.format = Format::Json,
+      ("format", Some("table")) => state.format = Format::Table,
+      ("sql", Some(flag)) => state.show_sql = parse_flag(flag)?,
+      ("deleted", Some(flag)) => state.with_deleted = parse_flag(flag)?,
+      ("node", Some(id)) => {
+        let id = id.parse()?;
+        print_found(get_node::<Value>(pool, id).await?, "node", id)?
+      }
+      ("edge", Some(id)) => {
+        let id = id.parse()?;
+        print_found(get_edge::<Value>(pool, id).await?, "edge", id)?
+      }
+      ("uris", _) => print_uri_counts(&uri_counts(pool).await?),
+      _ => return Err(format!("unknown command `:{command}`, see :help").into()),
+    }
+    return Ok(true);
+  }
+
+  if input.starts_with('{') {
+    let mut query = serde_json::from_str::<ScopedQuery>(input)?;
+    if state.with_deleted {
+      query = query.with_deleted(true);
+    }
+    if state.show_sql {
+      let sql = match state.kind {
+        QueryKind::Nodes => query.nodes_sql(),
+        QueryKind::Edges => query.edges_sql(),
+        QueryKind::NodeEdges => query.node_edges_sql(),
+      };
+      println!("{sql}");
+    }
+    execute_query(pool, &query, state.kind, state.format).await?;
+  } else {
+    let pattern = input.trim_end_matches(';').parse::<Pattern>()?;
+    let pattern = pattern.with_deleted(state.with_deleted);
+    if state.show_sql {
+      println!("{}", pattern.sql()?);
+    }
+    let rows = pattern.rows(pool).await?;
+    print_bindings(&rows, &pattern.returned_variables(), state.format)?;
+    println!("({} rows)", rows.len());
+  }
+  Ok(true)
+}
+
+fn parse_flag(flag: &str) -> Result<bool, Box<dyn Error>> {
+  match flag {
+    "on" | "true" => Ok(true),
+    "off" | "false" => Ok(false),
+    _ => Err(format!("expected on or off, got `{flag}`").into()),
+  }
+}
+
+// Query JSON may be typed over several lines, so keep reading until every
+// brace outside of a string has been closed
+fn is_complete(input: &str) -> bool {
+  if !input.trim_start().starts_with('{') {
+    return true;
+  }
+  let mut depth = 0i64;
+  let mut in_string = false;
+  let mut escaped = false;
+  for c in input.chars() {
+    if in_string {
+      match c {
+        _ if escaped => escaped = false,
+        '\\' => escaped = true,
+        '"' => in_string = false,
+        _ => {}
+      }
+    } else {
+      match c {
+        '"' => in_string = true,
+        '{' | '[' => depth += 1,
+        '}' | ']' => depth -= 1,
+        _ => {}
+      }
+    }
+  }
+  depth <= 0
+}
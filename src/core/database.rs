@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!();
 
 pub async fn create(filename: &str, create_if_missing: bool) -> sqlx::Result<sqlx::SqlitePool> {
@@ -7,6 +9,7 @@ pub async fn create(filename: &str, create_if_missing: bool) -> sqlx::Result<sql
         .filename(filename)
         .create_if_missing(create_if_missing)
         .foreign_keys(true)
+        .with_regexp()
         .synchronous(sqlx::sqlite::SqliteSynchronous::Normal)
         .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal),
     )
@@ -26,3 +29,63 @@ where
     .await?;
   Ok(())
 }
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Purged {
+  pub nodes: u64,
+  pub edges: u64,
+}
+
+// Hard deletes tombstones older than `older_than`, edges and nodes in one
+// transaction.
+pub async fn purge_deleted(
+  pool: &sqlx::SqlitePool,
+  older_than: chrono::DateTime<chrono::Utc>,
+) -> sqlx::Result<Purged> {
+  let mut tx = pool.begin().await?;
+  let edges =
+    crate::repo::edge::purge_deleted_edges(&mut *tx, older_than.timestamp_millis()).await?;
+  let nodes =
+    crate::repo::node::purge_deleted_nodes(&mut *tx, older_than.timestamp_millis()).await?;
+  tx.commit().await?;
+  Ok(Purged { nodes, edges })
+}
+
+// While enabled `delete_*` soft deletes, like the `soft_delete_*` functions,
+// and rows are only removed for good by `purge_deleted`.
+pub async fn enable_soft_delete(pool: &sqlx::SqlitePool) -> sqlx::Result<()> {
+  crate::repo::settings::set_soft_delete_enabled(pool, true).await
+}
+
+pub async fn disable_soft_delete(pool: &sqlx::SqlitePool) -> sqlx::Result<()> {
+  crate::repo::settings::set_soft_delete_enabled(pool, false).await
+}
+
+pub async fn soft_delete_enabled(pool: &sqlx::SqlitePool) -> sqlx::Result<bool> {
+  crate::repo::settings::soft_delete_enabled(pool).await
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UriCount {
+  pub uri: String,
+  pub count: i64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UriCounts {
+  pub nodes: Vec<UriCount>,
+  pub edges: Vec<UriCount>,
+}
+
+pub async fn uri_counts(pool: &sqlx::SqlitePool) -> sqlx::Result<UriCounts> {
+  let to_counts = |rows: Vec<(String, i64)>| {
+    rows
+      .into_iter()
+      .map(|(uri, count)| UriCount { uri, count })
+      .collect()
+  };
+  Ok(UriCounts {
+    nodes: to_counts(crate::repo::node::count_nodes_by_uri(pool).await?),
+    edges: to_counts(crate::repo::edge::count_edges_by_uri(pool).await?),
+  })
+}
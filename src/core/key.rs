@@ -0,0 +1,25 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// UUIDv7: a 48 bit millisecond timestamp followed by random bits, so keys
+// sort roughly by creation time without revealing row counts. Fails when the
+// operating system has no randomness to give.
+pub fn generate_key() -> Result<String, getrandom::Error> {
+  let mut bytes = [0u8; 16];
+  getrandom::getrandom(&mut bytes[6..])?;
+  let millis = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |d| d.as_millis() as u64);
+  bytes[..6].copy_from_slice(&millis.to_be_bytes()[2..]);
+  bytes[6] = (bytes[6] & 0x0f) | 0x70;
+  bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+  let hex = bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+  Ok(format!(
+    "{}-{}-{}-{}-{}",
+    &hex[0..8],
+    &hex[8..12],
+    &hex[12..16],
+    &hex[16..20],
+    &hex[20..32]
+  ))
+}
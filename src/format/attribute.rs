@@ -0,0 +1,92 @@
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeType {
+  Boolean,
+  Int,
+  Long,
+  Float,
+  Double,
+  String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Attribute {
+  pub name: String,
+  pub path: String,
+  pub kind: AttributeType,
+}
+
+impl Attribute {
+  pub fn new(path: impl Into<String>, kind: AttributeType) -> Self {
+    let path = path.into();
+    Self {
+      name: path.clone(),
+      path,
+      kind,
+    }
+  }
+
+  pub fn named(mut self, name: impl Into<String>) -> Self {
+    self.name = name.into();
+    self
+  }
+
+  pub(crate) fn value(&self, data: Option<&Value>) -> Option<String> {
+    let value = lookup(data?, &self.path)?;
+    match self.kind {
+      AttributeType::Boolean => value.as_bool().map(|b| b.to_string()),
+      AttributeType::Int | AttributeType::Long => value.as_i64().map(|n| n.to_string()),
+      AttributeType::Float | AttributeType::Double => value.as_f64().map(|n| n.to_string()),
+      AttributeType::String => match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+      },
+    }
+  }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct GraphExportOptions {
+  pub node_attributes: Vec<Attribute>,
+  pub edge_attributes: Vec<Attribute>,
+}
+
+impl GraphExportOptions {
+  pub fn node_attribute(mut self, attribute: Attribute) -> Self {
+    self.node_attributes.push(attribute);
+    self
+  }
+
+  pub fn edge_attribute(mut self, attribute: Attribute) -> Self {
+    self.edge_attributes.push(attribute);
+    self
+  }
+}
+
+pub fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+  if path.is_empty() {
+    return Some(value);
+  }
+  path.split('.').try_fold(value, |value, key| match value {
+    Value::Object(map) => map.get(key),
+    Value::Array(array) => array.get(key.parse::<usize>().ok()?),
+    _ => None,
+  })
+}
+
+pub(crate) fn escape_xml(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  for c in value.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&apos;"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
@@ -0,0 +1,153 @@
+use std::{collections::HashSet, fmt::Write};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::model::{node_edge::NodeEdge, subgraph::Subgraph};
+
+use super::attribute::lookup;
+
+#[derive(Debug, Clone)]
+pub struct DotOptions {
+  pub name: String,
+  pub node_label_paths: Vec<String>,
+  pub edge_labels: bool,
+}
+
+impl Default for DotOptions {
+  fn default() -> Self {
+    Self {
+      name: "G".to_owned(),
+      node_label_paths: Vec::new(),
+      edge_labels: true,
+    }
+  }
+}
+
+impl DotOptions {
+  pub fn name(mut self, name: impl Into<String>) -> Self {
+    self.name = name.into();
+    self
+  }
+
+  pub fn node_label(mut self, path: impl Into<String>) -> Self {
+    self.node_label_paths.push(path.into());
+    self
+  }
+
+  pub fn edge_labels(mut self, edge_labels: bool) -> Self {
+    self.edge_labels = edge_labels;
+    self
+  }
+}
+
+pub trait ToDot {
+  fn to_dot_with(&self, options: &DotOptions) -> String;
+
+  fn to_dot(&self) -> String {
+    self.to_dot_with(&DotOptions::default())
+  }
+}
+
+impl ToDot for Subgraph {
+  fn to_dot_with(&self, options: &DotOptions) -> String {
+    let mut dot = DotWriter::new(options);
+    for node in &self.nodes {
+      dot.node(node.id, &node.uri, Some(&node.data));
+    }
+    for edge in &self.edges {
+      dot.edge(edge.from_node_id, edge.to_node_id, &edge.uri);
+    }
+    dot.finish()
+  }
+}
+
+impl<FN, TN, E> ToDot for [NodeEdge<FN, TN, E>]
+where
+  FN: Serialize,
+  TN: Serialize,
+{
+  fn to_dot_with(&self, options: &DotOptions) -> String {
+    let mut dot = DotWriter::new(options);
+    let mut node_ids = HashSet::new();
+    for node_edge in self {
+      let (from_node, to_node) = (&node_edge.from_node, &node_edge.to_node);
+      if node_ids.insert(from_node.id) {
+        let data = serde_json::to_value(&from_node.data).ok();
+        dot.node(from_node.id, &from_node.uri, data.as_ref());
+      }
+      if node_ids.insert(to_node.id) {
+        let data = serde_json::to_value(&to_node.data).ok();
+        dot.node(to_node.id, &to_node.uri, data.as_ref());
+      }
+    }
+    for node_edge in self {
+      let edge = &node_edge.edge;
+      dot.edge(edge.from_node_id, edge.to_node_id, &edge.uri);
+    }
+    dot.finish()
+  }
+}
+
+struct DotWriter<'a> {
+  options: &'a DotOptions,
+  out: String,
+}
+
+impl<'a> DotWriter<'a> {
+  fn new(options: &'a DotOptions) -> Self {
+    let mut out = String::new();
+    let _ = writeln!(out, "digraph \"{}\" {{", escape_dot(&options.name));
+    Self { options, out }
+  }
+
+  fn node(&mut self, id: i64, uri: &str, data: Option<&Value>) {
+    let mut lines = vec![uri.to_owned()];
+    if self.options.node_label_paths.is_empty() {
+      lines.push(format!("#{id}"));
+    }
+    for path in &self.options.node_label_paths {
+      match data.and_then(|data| lookup(data, path)) {
+        Some(Value::String(s)) => lines.push(s.clone()),
+        Some(Value::Null) | None => {}
+        Some(value) => lines.push(value.to_string()),
+      }
+    }
+    let label = lines
+      .iter()
+      .map(|line| escape_dot(line))
+      .collect::<Vec<_>>()
+      .join("\\n");
+    let _ = writeln!(self.out, "  n{id} [label=\"{label}\"];");
+  }
+
+  fn edge(&mut self, from_node_id: i64, to_node_id: i64, uri: &str) {
+    if self.options.edge_labels {
+      let _ = writeln!(
+        self.out,
+        "  n{from_node_id} -> n{to_node_id} [label=\"{}\"];",
+        escape_dot(uri)
+      );
+    } else {
+      let _ = writeln!(self.out, "  n{from_node_id} -> n{to_node_id};");
+    }
+  }
+
+  fn finish(mut self) -> String {
+    self.out.push_str("}\n");
+    self.out
+  }
+}
+
+fn escape_dot(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  for c in value.chars() {
+    match c {
+      '"' => escaped.push_str("\\\""),
+      '\\' => escaped.push_str("\\\\"),
+      '\n' => escaped.push_str("\\n"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
@@ -0,0 +1,114 @@
+use std::io::Write;
+
+use crate::model::subgraph::Subgraph;
+
+use super::attribute::{escape_xml, Attribute, AttributeType, GraphExportOptions};
+
+fn gexf_type(kind: AttributeType) -> &'static str {
+  match kind {
+    AttributeType::Boolean => "boolean",
+    AttributeType::Int => "integer",
+    AttributeType::Long => "long",
+    AttributeType::Float => "float",
+    AttributeType::Double => "double",
+    AttributeType::String => "string",
+  }
+}
+
+fn write_attributes<W>(writer: &mut W, class: &str, attributes: &[Attribute]) -> std::io::Result<()>
+where
+  W: Write,
+{
+  if attributes.is_empty() {
+    return Ok(());
+  }
+  writeln!(writer, r#"    <attributes class="{class}">"#)?;
+  for (i, attribute) in attributes.iter().enumerate() {
+    writeln!(
+      writer,
+      r#"      <attribute id="{i}" title="{}" type="{}"/>"#,
+      escape_xml(&attribute.name),
+      gexf_type(attribute.kind)
+    )?;
+  }
+  writeln!(writer, "    </attributes>")
+}
+
+fn write_attribute_values<W>(
+  writer: &mut W,
+  attributes: &[Attribute],
+  data: Option<&serde_json::Value>,
+) -> std::io::Result<()>
+where
+  W: Write,
+{
+  let values = attributes
+    .iter()
+    .enumerate()
+    .filter_map(|(i, attribute)| attribute.value(data).map(|value| (i, value)))
+    .collect::<Vec<_>>();
+  if values.is_empty() {
+    return Ok(());
+  }
+  writeln!(writer, "        <attvalues>")?;
+  for (i, value) in values {
+    writeln!(
+      writer,
+      r#"          <attvalue for="{i}" value="{}"/>"#,
+      escape_xml(&value)
+    )?;
+  }
+  writeln!(writer, "        </attvalues>")
+}
+
+pub fn write_gexf<W>(
+  mut writer: W,
+  subgraph: &Subgraph,
+  options: &GraphExportOptions,
+) -> std::io::Result<()>
+where
+  W: Write,
+{
+  writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+  writeln!(
+    writer,
+    r#"<gexf xmlns="http://gexf.net/1.3" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://gexf.net/1.3 http://gexf.net/1.3/gexf.xsd" version="1.3">"#
+  )?;
+  writeln!(
+    writer,
+    r#"  <graph defaultedgetype="directed" mode="static">"#
+  )?;
+  write_attributes(&mut writer, "node", &options.node_attributes)?;
+  write_attributes(&mut writer, "edge", &options.edge_attributes)?;
+
+  writeln!(writer, "    <nodes>")?;
+  for node in &subgraph.nodes {
+    writeln!(
+      writer,
+      r#"      <node id="{}" label="{}">"#,
+      node.id,
+      escape_xml(&node.uri)
+    )?;
+    write_attribute_values(&mut writer, &options.node_attributes, Some(&node.data))?;
+    writeln!(writer, "      </node>")?;
+  }
+  writeln!(writer, "    </nodes>")?;
+
+  writeln!(writer, "    <edges>")?;
+  for edge in &subgraph.edges {
+    writeln!(
+      writer,
+      r#"      <edge id="{}" source="{}" target="{}" label="{}">"#,
+      edge.id,
+      edge.from_node_id,
+      edge.to_node_id,
+      escape_xml(&edge.uri)
+    )?;
+    write_attribute_values(&mut writer, &options.edge_attributes, edge.data.as_ref())?;
+    writeln!(writer, "      </edge>")?;
+  }
+  writeln!(writer, "    </edges>")?;
+  writeln!(writer, "  </graph>")?;
+  writeln!(writer, "</gexf>")?;
+  writer.flush()
+}
@@ -0,0 +1,100 @@
+use std::io::Write;
+
+use crate::model::subgraph::Subgraph;
+
+use super::attribute::{escape_xml, AttributeType, GraphExportOptions};
+
+fn graphml_type(kind: AttributeType) -> &'static str {
+  match kind {
+    AttributeType::Boolean => "boolean",
+    AttributeType::Int => "int",
+    AttributeType::Long => "long",
+    AttributeType::Float => "float",
+    AttributeType::Double => "double",
+    AttributeType::String => "string",
+  }
+}
+
+pub fn write_graphml<W>(
+  mut writer: W,
+  subgraph: &Subgraph,
+  options: &GraphExportOptions,
+) -> std::io::Result<()>
+where
+  W: Write,
+{
+  writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+  writeln!(
+    writer,
+    r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">"#
+  )?;
+  writeln!(
+    writer,
+    r#"  <key id="node_label" for="node" attr.name="label" attr.type="string"/>"#
+  )?;
+  for (i, attribute) in options.node_attributes.iter().enumerate() {
+    writeln!(
+      writer,
+      r#"  <key id="n{i}" for="node" attr.name="{}" attr.type="{}"/>"#,
+      escape_xml(&attribute.name),
+      graphml_type(attribute.kind)
+    )?;
+  }
+  writeln!(
+    writer,
+    r#"  <key id="edge_label" for="edge" attr.name="label" attr.type="string"/>"#
+  )?;
+  for (i, attribute) in options.edge_attributes.iter().enumerate() {
+    writeln!(
+      writer,
+      r#"  <key id="e{i}" for="edge" attr.name="{}" attr.type="{}"/>"#,
+      escape_xml(&attribute.name),
+      graphml_type(attribute.kind)
+    )?;
+  }
+
+  writeln!(writer, r#"  <graph id="G" edgedefault="directed">"#)?;
+  for node in &subgraph.nodes {
+    writeln!(writer, r#"    <node id="n{}">"#, node.id)?;
+    writeln!(
+      writer,
+      r#"      <data key="node_label">{}</data>"#,
+      escape_xml(&node.uri)
+    )?;
+    for (i, attribute) in options.node_attributes.iter().enumerate() {
+      if let Some(value) = attribute.value(Some(&node.data)) {
+        writeln!(
+          writer,
+          r#"      <data key="n{i}">{}</data>"#,
+          escape_xml(&value)
+        )?;
+      }
+    }
+    writeln!(writer, "    </node>")?;
+  }
+  for edge in &subgraph.edges {
+    writeln!(
+      writer,
+      r#"    <edge id="e{}" source="n{}" target="n{}">"#,
+      edge.id, edge.from_node_id, edge.to_node_id
+    )?;
+    writeln!(
+      writer,
+      r#"      <data key="edge_label">{}</data>"#,
+      escape_xml(&edge.uri)
+    )?;
+    for (i, attribute) in options.edge_attributes.iter().enumerate() {
+      if let Some(value) = attribute.value(edge.data.as_ref()) {
+        writeln!(
+          writer,
+          r#"      <data key="e{i}">{}</data>"#,
+          escape_xml(&value)
+        )?;
+      }
+    }
+    writeln!(writer, "    </edge>")?;
+  }
+  writeln!(writer, "  </graph>")?;
+  writeln!(writer, "</graphml>")?;
+  writer.flush()
+}
@@ -0,0 +1,217 @@
+use std::{
+  collections::{HashMap, HashSet},
+  io::{BufRead, Write},
+};
+
+use futures_util::{pin_mut, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+  model::{edge::Edge, neighbor::Direction, node::Node, subgraph::incident_edges},
+  query::ScopedQuery,
+  repo,
+};
+
+const IMPORT_BATCH_SIZE: usize = 500;
+const EXPORT_BATCH_SIZE: usize = 500;
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Record {
+  Node(Node<Value>),
+  Edge(EdgeRecord),
+  Label { node_id: i64, label: String },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EdgeRecord {
+  #[serde(flatten)]
+  pub edge: Edge<Value>,
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub cross_namespace: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExportReport {
+  pub nodes: usize,
+  pub edges: usize,
+  pub labels: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportFailure {
+  pub line: usize,
+  pub error: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ImportReport {
+  pub nodes: usize,
+  pub edges: usize,
+  pub labels: usize,
+  pub failures: Vec<ImportFailure>,
+}
+
+pub async fn export_jsonl<W>(
+  pool: &sqlx::SqlitePool,
+  mut writer: W,
+  filter: Option<&ScopedQuery>,
+) -> sqlx::Result<ExportReport>
+where
+  W: Write,
+{
+  let mut report = ExportReport::default();
+  let all = ScopedQuery::default();
+  let nodes_query = filter.unwrap_or(&all);
+
+  let mut node_ids = HashSet::new();
+  let nodes = nodes_query.nodes_stream::<Value>(pool);
+  pin_mut!(nodes);
+  while let Some(node) = nodes.try_next().await? {
+    node_ids.insert(node.id);
+    write_record(&mut writer, &Record::Node(node))?;
+    report.nodes += 1;
+  }
+
+  // labels and edges follow every node so imports can map their node ids
+  let mut ids: Vec<i64> = node_ids.iter().copied().collect();
+  ids.sort_unstable();
+  for chunk in ids.chunks(EXPORT_BATCH_SIZE) {
+    for (node_id, label) in repo::label::labels_of_nodes(pool, chunk).await? {
+      write_record(&mut writer, &Record::Label { node_id, label })?;
+      report.labels += 1;
+    }
+  }
+  // only edges between exported nodes, in the namespace of the filter
+  for chunk in ids.chunks(EXPORT_BATCH_SIZE) {
+    let edges: Vec<Edge<Value>> = incident_edges(pool, nodes_query, chunk, Direction::Outgoing)
+      .await?
+      .into_iter()
+      .filter(|edge| node_ids.contains(&edge.to_node_id))
+      .collect();
+    let edge_ids: Vec<i64> = edges.iter().map(|edge| edge.id).collect();
+    let cross: HashSet<i64> = repo::edge::cross_namespace_edges(pool, &edge_ids)
+      .await?
+      .into_iter()
+      .collect();
+    for edge in edges {
+      let cross_namespace = cross.contains(&edge.id);
+      write_record(
+        &mut writer,
+        &Record::Edge(EdgeRecord {
+          edge,
+          cross_namespace,
+        }),
+      )?;
+      report.edges += 1;
+    }
+  }
+  writer.flush()?;
+  Ok(report)
+}
+
+fn write_record<W>(writer: &mut W, record: &Record) -> sqlx::Result<()>
+where
+  W: Write,
+{
+  if let Err(e) = serde_json::to_writer(&mut *writer, record) {
+    return Err(sqlx::Error::Encode(Box::new(e)));
+  }
+  writer.write_all(b"\n")?;
+  Ok(())
+}
+
+pub async fn import_jsonl<R>(pool: &sqlx::SqlitePool, reader: R) -> sqlx::Result<ImportReport>
+where
+  R: BufRead,
+{
+  let mut report = ImportReport::default();
+  let mut node_ids = HashMap::<i64, i64>::new();
+  let mut lines = reader.lines().enumerate().peekable();
+
+  while lines.peek().is_some() {
+    let mut tx = pool.begin().await?;
+    for (index, line) in lines.by_ref().take(IMPORT_BATCH_SIZE) {
+      let line = line?;
+      if line.trim().is_empty() {
+        continue;
+      }
+      if let Err(error) = import_record(&mut tx, &mut node_ids, &mut report, &line).await {
+        report.failures.push(ImportFailure {
+          line: index + 1,
+          error,
+        });
+      }
+    }
+    tx.commit().await?;
+  }
+  Ok(report)
+}
+
+async fn import_record(
+  tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+  node_ids: &mut HashMap<i64, i64>,
+  report: &mut ImportReport,
+  line: &str,
+) -> Result<(), String> {
+  match serde_json::from_str::<Record>(line).map_err(|e| e.to_string())? {
+    Record::Node(node) => {
+      let data = serde_json::to_string(&node.data).map_err(|e| e.to_string())?;
+      let row = repo::node::create_node_at(
+        &mut **tx,
+        &node.namespace,
+        node.key.as_deref(),
+        &node.uri,
+        &data,
+        node.created_at.timestamp_millis(),
+        node.updated_at.timestamp_millis(),
+        node.deleted_at.map(|time| time.timestamp_millis()),
+      )
+      .await
+      .map_err(|e| e.to_string())?;
+      node_ids.insert(node.id, row.id);
+      report.nodes += 1;
+    }
+    Record::Edge(EdgeRecord {
+      edge,
+      cross_namespace,
+    }) => {
+      let from_node_id = *node_ids
+        .get(&edge.from_node_id)
+        .ok_or_else(|| format!("unknown from_node_id {}", edge.from_node_id))?;
+      let to_node_id = *node_ids
+        .get(&edge.to_node_id)
+        .ok_or_else(|| format!("unknown to_node_id {}", edge.to_node_id))?;
+      let data = match &edge.data {
+        Some(data) => Some(serde_json::to_string(data).map_err(|e| e.to_string())?),
+        None => None,
+      };
+      repo::edge::create_edge_at(
+        &mut **tx,
+        &edge.namespace,
+        from_node_id,
+        to_node_id,
+        &edge.uri,
+        data.as_deref(),
+        edge.created_at.timestamp_millis(),
+        edge.updated_at.timestamp_millis(),
+        edge.deleted_at.map(|time| time.timestamp_millis()),
+        cross_namespace,
+      )
+      .await
+      .map_err(|e| e.to_string())?;
+      report.edges += 1;
+    }
+    Record::Label { node_id, label } => {
+      let node_id = *node_ids
+        .get(&node_id)
+        .ok_or_else(|| format!("unknown node_id {node_id}"))?;
+      repo::label::add_label(&mut **tx, node_id, &label)
+        .await
+        .map_err(|e| e.to_string())?;
+      report.labels += 1;
+    }
+  }
+  Ok(())
+}
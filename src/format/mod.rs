@@ -0,0 +1,13 @@
+pub mod attribute;
+pub mod dot;
+pub mod gexf;
+pub mod graphml;
+pub mod jsonl;
+
+pub use attribute::{Attribute, AttributeType, GraphExportOptions};
+pub use dot::{DotOptions, ToDot};
+pub use gexf::write_gexf;
+pub use graphml::write_graphml;
+pub use jsonl::{
+  export_jsonl, import_jsonl, EdgeRecord, ExportReport, ImportFailure, ImportReport, Record,
+};
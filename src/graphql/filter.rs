@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::query::{
+  query::{QueryExpr, QueryField, QueryOp, QueryValue},
+  time::QueryDuration,
+};
+
+#[derive(Default, Deserialize)]
+pub struct OpFilter {
+  pub eq: Option<QueryValue>,
+  pub neq: Option<QueryValue>,
+  pub gt: Option<QueryValue>,
+  pub lt: Option<QueryValue>,
+  pub gte: Option<QueryValue>,
+  pub lte: Option<QueryValue>,
+  pub like: Option<QueryValue>,
+  pub ilike: Option<QueryValue>,
+  #[serde(rename = "startsWith")]
+  pub starts_with: Option<QueryValue>,
+  #[serde(rename = "endsWith")]
+  pub ends_with: Option<QueryValue>,
+  pub regex: Option<QueryValue>,
+  pub contains: Option<QueryValue>,
+  #[serde(rename = "isNull")]
+  pub is_null: Option<bool>,
+  pub exists: Option<bool>,
+  #[serde(rename = "in")]
+  pub in_: Option<Vec<QueryValue>>,
+  pub within: Option<QueryDuration>,
+}
+
+impl OpFilter {
+  pub fn into_expr(self) -> Option<QueryExpr> {
+    let value = |value: QueryValue| Box::new(QueryExpr::Value(value));
+    let mut ops = Vec::new();
+    if let Some(v) = self.eq {
+      ops.push(QueryOp::Eq(value(v)));
+    }
+    if let Some(v) = self.neq {
+      ops.push(QueryOp::Neq(value(v)));
+    }
+    if let Some(v) = self.gt {
+      ops.push(QueryOp::Gt(value(v)));
+    }
+    if let Some(v) = self.lt {
+      ops.push(QueryOp::Lt(value(v)));
+    }
+    if let Some(v) = self.gte {
+      ops.push(QueryOp::Gte(value(v)));
+    }
+    if let Some(v) = self.lte {
+      ops.push(QueryOp::Lte(value(v)));
+    }
+    if let Some(v) = self.like {
+      ops.push(QueryOp::Like(value(v)));
+    }
+    if let Some(v) = self.ilike {
+      ops.push(QueryOp::ILike(value(v)));
+    }
+    if let Some(v) = self.starts_with {
+      ops.push(QueryOp::StartsWith(value(v)));
+    }
+    if let Some(v) = self.ends_with {
+      ops.push(QueryOp::EndsWith(value(v)));
+    }
+    if let Some(v) = self.regex {
+      ops.push(QueryOp::Regex(value(v)));
+    }
+    if let Some(v) = self.contains {
+      ops.push(QueryOp::Contains(value(v)));
+    }
+    if let Some(is_null) = self.is_null {
+      ops.push(QueryOp::IsNull(is_null));
+    }
+    if let Some(exists) = self.exists {
+      ops.push(QueryOp::Exists(exists));
+    }
+    if let Some(values) = self.in_ {
+      ops.push(QueryOp::In(
+        values.into_iter().map(QueryExpr::Value).collect(),
+      ));
+    }
+    if let Some(duration) = self.within {
+      ops.push(QueryOp::Within(duration));
+    }
+    match ops.len() {
+      0 => None,
+      1 => ops.pop().map(QueryExpr::Op),
+      _ => Some(QueryExpr::Op(QueryOp::And(
+        ops.into_iter().map(QueryExpr::Op).collect(),
+      ))),
+    }
+  }
+}
+
+#[derive(Deserialize)]
+pub struct DataFilter {
+  pub path: String,
+  #[serde(flatten)]
+  pub op: OpFilter,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeFilter {
+  pub id: Option<OpFilter>,
+  pub created_at: Option<OpFilter>,
+  pub updated_at: Option<OpFilter>,
+  pub data: Option<Vec<DataFilter>>,
+}
+
+impl NodeFilter {
+  pub fn into_conditions(self, conditions: &mut HashMap<QueryField, QueryExpr>) {
+    push(conditions, QueryField::NodeId, self.id);
+    push(conditions, QueryField::NodeCreatedAt, self.created_at);
+    push(conditions, QueryField::NodeUpdatedAt, self.updated_at);
+    if let Some(data) = self.data.and_then(data_expr) {
+      conditions.insert(QueryField::NodeData, data);
+    }
+  }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeFilter {
+  pub id: Option<OpFilter>,
+  pub from_node_id: Option<OpFilter>,
+  pub to_node_id: Option<OpFilter>,
+  pub created_at: Option<OpFilter>,
+  pub updated_at: Option<OpFilter>,
+  pub data: Option<Vec<DataFilter>>,
+}
+
+impl EdgeFilter {
+  pub fn into_conditions(self, conditions: &mut HashMap<QueryField, QueryExpr>) {
+    push(conditions, QueryField::EdgeId, self.id);
+    push(conditions, QueryField::EdgeFromNodeId, self.from_node_id);
+    push(conditions, QueryField::EdgeToNodeId, self.to_node_id);
+    push(conditions, QueryField::EdgeCreatedAt, self.created_at);
+    push(conditions, QueryField::EdgeUpdatedAt, self.updated_at);
+    if let Some(data) = self.data.and_then(data_expr) {
+      conditions.insert(QueryField::EdgeData, data);
+    }
+  }
+}
+
+fn push(
+  conditions: &mut HashMap<QueryField, QueryExpr>,
+  field: QueryField,
+  filter: Option<OpFilter>,
+) {
+  if let Some(expr) = filter.and_then(OpFilter::into_expr) {
+    // a field fixed by the schema, e.g. the edge source, can also be filtered
+    let expr = match conditions.remove(&field) {
+      Some(fixed) => QueryExpr::Op(QueryOp::And(vec![fixed, expr])),
+      None => expr,
+    };
+    conditions.insert(field, expr);
+  }
+}
+
+fn data_expr(filters: Vec<DataFilter>) -> Option<QueryExpr> {
+  let mut paths: HashMap<String, QueryExpr> = HashMap::new();
+  for filter in filters {
+    if let Some(expr) = filter.op.into_expr() {
+      let expr = match paths.remove(&filter.path) {
+        Some(other) => QueryExpr::Op(QueryOp::And(vec![other, expr])),
+        None => expr,
+      };
+      paths.insert(filter.path, expr);
+    }
+  }
+  if paths.is_empty() {
+    None
+  } else {
+    Some(QueryExpr::Data(paths))
+  }
+}
@@ -0,0 +1,490 @@
+pub mod filter;
+
+use std::collections::HashMap;
+
+use async_graphql::{
+  dynamic::{
+    Field, FieldFuture, FieldValue, InputObject, InputValue, Object, ResolverContext, Scalar,
+    Schema, SchemaError, TypeRef,
+  },
+  Value,
+};
+
+use crate::{
+  format::attribute::lookup,
+  model::{
+    edge::{get_edge, Edge},
+    node::{get_node, Node},
+  },
+  query::query::{Query, QueryExpr, QueryField, QueryOp, QueryValue, ScopedQuery},
+};
+
+use filter::{EdgeFilter, NodeFilter};
+
+static JSON: &str = "JSON";
+static INT_FILTER: &str = "IntFilter";
+static TIME_FILTER: &str = "TimeFilter";
+static NODE_FILTER: &str = "NodeFilter";
+static DATA_FILTER: &str = "DataFilter";
+static EDGE_FILTER: &str = "EdgeFilter";
+
+// `*List` fields without a `limit` return at most `DEFAULT_LIMIT` items
+const DEFAULT_LIMIT: u64 = 100;
+const MAX_LIMIT: u64 = 1000;
+
+#[derive(Debug, Clone)]
+pub struct GraphQLNodeType {
+  pub uri: String,
+  pub type_name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphQLEdgeType {
+  pub uri: String,
+  pub type_name: String,
+  pub from_uri: String,
+  pub to_uri: String,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct GraphQLSchemaBuilder {
+  pub nodes: Vec<GraphQLNodeType>,
+  pub edges: Vec<GraphQLEdgeType>,
+}
+
+impl GraphQLSchemaBuilder {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn node(mut self, uri: &str, type_name: &str) -> Self {
+    self.nodes.push(GraphQLNodeType {
+      uri: uri.to_owned(),
+      type_name: type_name.to_owned(),
+    });
+    self
+  }
+
+  pub fn edge(mut self, uri: &str, type_name: &str, from_uri: &str, to_uri: &str) -> Self {
+    self.edges.push(GraphQLEdgeType {
+      uri: uri.to_owned(),
+      type_name: type_name.to_owned(),
+      from_uri: from_uri.to_owned(),
+      to_uri: to_uri.to_owned(),
+    });
+    self
+  }
+
+  pub fn build(self, pool: sqlx::SqlitePool) -> Result<Schema, SchemaError> {
+    let mut query = Object::new("Query");
+    let mut objects = Vec::new();
+
+    for node_type in &self.nodes {
+      let mut object = node_object(&node_type.type_name);
+      for edge_type in &self.edges {
+        if edge_type.from_uri == node_type.uri {
+          object = object.field(edges_field(
+            field_name(&edge_type.uri),
+            edge_type,
+            QueryField::EdgeFromNodeId,
+          ));
+        }
+        if edge_type.to_uri == node_type.uri {
+          object = object.field(edges_field(
+            format!("{}Incoming", field_name(&edge_type.uri)),
+            edge_type,
+            QueryField::EdgeToNodeId,
+          ));
+        }
+      }
+      objects.push(object);
+
+      let name = field_name(&node_type.type_name);
+      let uri = node_type.uri.clone();
+      query = query.field(
+        Field::new(&name, TypeRef::named(&node_type.type_name), move |ctx| {
+          let uri = uri.clone();
+          FieldFuture::new(async move {
+            let pool = ctx.data::<sqlx::SqlitePool>()?;
+            let id = ctx.args.try_get("id")?.i64()?;
+            Ok(
+              get_node(pool, id)
+                .await?
+                .filter(|node: &Node<serde_json::Value>| {
+                  node.uri == uri && node.deleted_at.is_none()
+                })
+                .map(FieldValue::owned_any),
+            )
+          })
+        })
+        .argument(InputValue::new("id", TypeRef::named_nn(TypeRef::INT))),
+      );
+      let uri = node_type.uri.clone();
+      query = query.field(list_arguments(
+        Field::new(
+          format!("{name}List"),
+          TypeRef::named_nn_list_nn(&node_type.type_name),
+          move |ctx| {
+            let uri = uri.clone();
+            FieldFuture::new(async move {
+              let pool = ctx.data::<sqlx::SqlitePool>()?;
+              let mut conditions = HashMap::new();
+              conditions.insert(QueryField::NodeURI, eq(QueryValue::String(uri)));
+              if let Some(filter) = ctx.args.get("where") {
+                filter
+                  .deserialize::<NodeFilter>()?
+                  .into_conditions(&mut conditions);
+              }
+              let nodes = paginate(&ctx, Query::from(conditions))?
+                .nodes::<serde_json::Value>(pool)
+                .await?;
+              Ok(Some(FieldValue::list(
+                nodes.into_iter().map(FieldValue::owned_any),
+              )))
+            })
+          },
+        ),
+        NODE_FILTER,
+      ));
+    }
+
+    for edge_type in &self.edges {
+      let from_type = node_type_name(&self.nodes, &edge_type.from_uri);
+      let to_type = node_type_name(&self.nodes, &edge_type.to_uri);
+      objects.push(edge_object(&edge_type.type_name, from_type, to_type));
+
+      let name = field_name(&edge_type.type_name);
+      let uri = edge_type.uri.clone();
+      query = query.field(
+        Field::new(&name, TypeRef::named(&edge_type.type_name), move |ctx| {
+          let uri = uri.clone();
+          FieldFuture::new(async move {
+            let pool = ctx.data::<sqlx::SqlitePool>()?;
+            let id = ctx.args.try_get("id")?.i64()?;
+            Ok(
+              get_edge(pool, id)
+                .await?
+                .filter(|edge: &Edge<serde_json::Value>| {
+                  edge.uri == uri && edge.deleted_at.is_none()
+                })
+                .map(FieldValue::owned_any),
+            )
+          })
+        })
+        .argument(InputValue::new("id", TypeRef::named_nn(TypeRef::INT))),
+      );
+      query = query.field(edges_list_field(format!("{name}List"), edge_type, None));
+    }
+
+    let mut schema = Schema::build(query.type_name(), None, None)
+      .register(Scalar::new(JSON))
+      .register(int_filter())
+      .register(time_filter())
+      .register(data_filter())
+      .register(node_filter())
+      .register(edge_filter())
+      .register(query);
+    for object in objects {
+      schema = schema.register(object);
+    }
+    schema.data(pool).finish()
+  }
+}
+
+fn node_object(type_name: &str) -> Object {
+  Object::new(type_name)
+    .field(Field::new("id", TypeRef::named_nn(TypeRef::INT), |ctx| {
+      node_value(&ctx, |node| Value::from(node.id))
+    }))
+    .field(Field::new(
+      "uri",
+      TypeRef::named_nn(TypeRef::STRING),
+      |ctx| node_value(&ctx, |node| Value::from(node.uri.clone())),
+    ))
+    .field(data_field(|ctx| {
+      Ok(Some(&parent::<Node<serde_json::Value>>(ctx)?.data))
+    }))
+    .field(Field::new(
+      "createdAt",
+      TypeRef::named_nn(TypeRef::STRING),
+      |ctx| node_value(&ctx, |node| Value::from(node.created_at.to_rfc3339())),
+    ))
+    .field(Field::new(
+      "updatedAt",
+      TypeRef::named_nn(TypeRef::STRING),
+      |ctx| node_value(&ctx, |node| Value::from(node.updated_at.to_rfc3339())),
+    ))
+}
+
+fn edge_object(type_name: &str, from_type: Option<&str>, to_type: Option<&str>) -> Object {
+  let mut object = Object::new(type_name)
+    .field(Field::new("id", TypeRef::named_nn(TypeRef::INT), |ctx| {
+      edge_value(&ctx, |edge| Value::from(edge.id))
+    }))
+    .field(Field::new(
+      "uri",
+      TypeRef::named_nn(TypeRef::STRING),
+      |ctx| edge_value(&ctx, |edge| Value::from(edge.uri.clone())),
+    ))
+    .field(Field::new(
+      "fromNodeId",
+      TypeRef::named_nn(TypeRef::INT),
+      |ctx| edge_value(&ctx, |edge| Value::from(edge.from_node_id)),
+    ))
+    .field(Field::new(
+      "toNodeId",
+      TypeRef::named_nn(TypeRef::INT),
+      |ctx| edge_value(&ctx, |edge| Value::from(edge.to_node_id)),
+    ))
+    .field(data_field(|ctx| {
+      Ok(parent::<Edge<serde_json::Value>>(ctx)?.data.as_ref())
+    }))
+    .field(Field::new(
+      "createdAt",
+      TypeRef::named_nn(TypeRef::STRING),
+      |ctx| edge_value(&ctx, |edge| Value::from(edge.created_at.to_rfc3339())),
+    ))
+    .field(Field::new(
+      "updatedAt",
+      TypeRef::named_nn(TypeRef::STRING),
+      |ctx| edge_value(&ctx, |edge| Value::from(edge.updated_at.to_rfc3339())),
+    ));
+  // endpoints are only typed when their uri was registered as a node type
+  if let Some(from_type) = from_type {
+    object = object.field(endpoint_field("from", from_type, |edge| edge.from_node_id));
+  }
+  if let Some(to_type) = to_type {
+    object = object.field(endpoint_field("to", to_type, |edge| edge.to_node_id));
+  }
+  object
+}
+
+fn endpoint_field(
+  name: &str,
+  type_name: &str,
+  node_id: fn(&Edge<serde_json::Value>) -> i64,
+) -> Field {
+  Field::new(name, TypeRef::named(type_name), move |ctx| {
+    FieldFuture::new(async move {
+      let pool = ctx.data::<sqlx::SqlitePool>()?;
+      let id = node_id(parent::<Edge<serde_json::Value>>(&ctx)?);
+      // soft deleted endpoints resolve to null, `get_node` skips them
+      Ok(
+        get_node::<serde_json::Value>(pool, id)
+          .await?
+          .map(FieldValue::owned_any),
+      )
+    })
+  })
+}
+
+fn edges_field(name: String, edge_type: &GraphQLEdgeType, parent_field: QueryField) -> Field {
+  edges_list_field(name, edge_type, Some(parent_field))
+}
+
+fn edges_list_field(
+  name: String,
+  edge_type: &GraphQLEdgeType,
+  parent_field: Option<QueryField>,
+) -> Field {
+  let uri = edge_type.uri.clone();
+  list_arguments(
+    Field::new(
+      name,
+      TypeRef::named_nn_list_nn(&edge_type.type_name),
+      move |ctx| {
+        let uri = uri.clone();
+        let parent_field = parent_field.clone();
+        FieldFuture::new(async move {
+          let pool = ctx.data::<sqlx::SqlitePool>()?;
+          let mut conditions = HashMap::new();
+          conditions.insert(QueryField::EdgeURI, eq(QueryValue::String(uri)));
+          if let Some(parent_field) = parent_field {
+            let node_id = parent::<Node<serde_json::Value>>(&ctx)?.id;
+            conditions.insert(parent_field, eq(QueryValue::Number(node_id.into())));
+          }
+          if let Some(filter) = ctx.args.get("where") {
+            filter
+              .deserialize::<EdgeFilter>()?
+              .into_conditions(&mut conditions);
+          }
+          let edges = paginate(&ctx, Query::from(conditions))?
+            .edges::<serde_json::Value>(pool)
+            .await?;
+          Ok(Some(FieldValue::list(
+            edges.into_iter().map(FieldValue::owned_any),
+          )))
+        })
+      },
+    ),
+    EDGE_FILTER,
+  )
+}
+
+fn list_arguments(field: Field, filter_type: &str) -> Field {
+  field
+    .argument(InputValue::new("where", TypeRef::named(filter_type)))
+    .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)))
+    .argument(InputValue::new("offset", TypeRef::named(TypeRef::INT)))
+}
+
+fn paginate(ctx: &ResolverContext<'_>, query: Query) -> async_graphql::Result<ScopedQuery> {
+  let limit = match ctx.args.get("limit") {
+    Some(limit) => limit.u64()?,
+    None => DEFAULT_LIMIT,
+  };
+  let mut query = query.scoped().limit(limit.min(MAX_LIMIT));
+  if let Some(offset) = ctx.args.get("offset") {
+    query = query.offset(offset.u64()?);
+  }
+  Ok(query)
+}
+
+fn data_field(
+  data: for<'a> fn(&ResolverContext<'a>) -> async_graphql::Result<Option<&'a serde_json::Value>>,
+) -> Field {
+  Field::new("data", TypeRef::named(JSON), move |ctx| {
+    let value = data(&ctx).and_then(|data| {
+      let path = match ctx.args.get("path") {
+        Some(path) => path.string()?,
+        None => "",
+      };
+      match data.and_then(|data| lookup(data, path)) {
+        Some(value) => Ok(Some(FieldValue::value(Value::from_json(value.clone())?))),
+        None => Ok(None),
+      }
+    });
+    FieldFuture::Value(value.unwrap_or_default())
+  })
+  .argument(InputValue::new("path", TypeRef::named(TypeRef::STRING)))
+}
+
+fn node_value<'a>(
+  ctx: &ResolverContext<'a>,
+  value: fn(&Node<serde_json::Value>) -> Value,
+) -> FieldFuture<'a> {
+  FieldFuture::Value(
+    parent::<Node<serde_json::Value>>(ctx)
+      .ok()
+      .map(|node| FieldValue::value(value(node))),
+  )
+}
+
+fn edge_value<'a>(
+  ctx: &ResolverContext<'a>,
+  value: fn(&Edge<serde_json::Value>) -> Value,
+) -> FieldFuture<'a> {
+  FieldFuture::Value(
+    parent::<Edge<serde_json::Value>>(ctx)
+      .ok()
+      .map(|edge| FieldValue::value(value(edge))),
+  )
+}
+
+fn parent<'a, T>(ctx: &ResolverContext<'a>) -> async_graphql::Result<&'a T>
+where
+  T: std::any::Any,
+{
+  ctx.parent_value.try_downcast_ref::<T>()
+}
+
+fn int_filter() -> InputObject {
+  ["eq", "neq", "gt", "lt", "gte", "lte"]
+    .iter()
+    .fold(InputObject::new(INT_FILTER), |object, op| {
+      object.field(InputValue::new(*op, TypeRef::named(TypeRef::INT)))
+    })
+    .field(InputValue::new("in", TypeRef::named_nn_list(TypeRef::INT)))
+}
+
+// times are RFC3339 timestamps, dates or relative times such as "-7d", see
+// `parse_time`, and `within` takes a duration such as "1h30m"
+fn time_filter() -> InputObject {
+  ["eq", "neq", "gt", "lt", "gte", "lte", "within"]
+    .iter()
+    .fold(InputObject::new(TIME_FILTER), |object, op| {
+      object.field(InputValue::new(*op, TypeRef::named(TypeRef::STRING)))
+    })
+}
+
+fn data_filter() -> InputObject {
+  let object = [
+    "eq",
+    "neq",
+    "gt",
+    "lt",
+    "gte",
+    "lte",
+    "like",
+    "ilike",
+    "startsWith",
+    "endsWith",
+    "regex",
+    "contains",
+  ]
+  .iter()
+  .fold(
+    InputObject::new(DATA_FILTER)
+      .field(InputValue::new("path", TypeRef::named_nn(TypeRef::STRING))),
+    |object, op| object.field(InputValue::new(*op, TypeRef::named(JSON))),
+  );
+  ["isNull", "exists"]
+    .iter()
+    .fold(object, |object, op| {
+      object.field(InputValue::new(*op, TypeRef::named(TypeRef::BOOLEAN)))
+    })
+    .field(InputValue::new("in", TypeRef::named_nn_list(JSON)))
+}
+
+fn node_filter() -> InputObject {
+  InputObject::new(NODE_FILTER)
+    .field(InputValue::new("id", TypeRef::named(INT_FILTER)))
+    .field(InputValue::new("createdAt", TypeRef::named(TIME_FILTER)))
+    .field(InputValue::new("updatedAt", TypeRef::named(TIME_FILTER)))
+    .field(InputValue::new("data", TypeRef::named_nn_list(DATA_FILTER)))
+}
+
+fn edge_filter() -> InputObject {
+  InputObject::new(EDGE_FILTER)
+    .field(InputValue::new("id", TypeRef::named(INT_FILTER)))
+    .field(InputValue::new("fromNodeId", TypeRef::named(INT_FILTER)))
+    .field(InputValue::new("toNodeId", TypeRef::named(INT_FILTER)))
+    .field(InputValue::new("createdAt", TypeRef::named(TIME_FILTER)))
+    .field(InputValue::new("updatedAt", TypeRef::named(TIME_FILTER)))
+    .field(InputValue::new("data", TypeRef::named_nn_list(DATA_FILTER)))
+}
+
+fn node_type_name<'a>(nodes: &'a [GraphQLNodeType], uri: &str) -> Option<&'a str> {
+  nodes
+    .iter()
+    .find(|node_type| node_type.uri == uri)
+    .map(|node_type| node_type.type_name.as_str())
+}
+
+fn eq(value: QueryValue) -> QueryExpr {
+  QueryExpr::Op(QueryOp::Eq(Box::new(QueryExpr::Value(value))))
+}
+
+// `follows` => `follows`, `User` => `user`, `authored-by` => `authoredBy`
+fn field_name(name: &str) -> String {
+  let mut field = String::with_capacity(name.len());
+  let mut upper = false;
+  for c in name.chars() {
+    if !c.is_ascii_alphanumeric() {
+      upper = !field.is_empty();
+    } else if field.is_empty() {
+      if c.is_ascii_alphabetic() || c == '_' {
+        field.push(c.to_ascii_lowercase());
+      } else {
+        field.push('_');
+        field.push(c);
+      }
+    } else if upper {
+      field.push(c.to_ascii_uppercase());
+      upper = false;
+    } else {
+      field.push(c);
+    }
+  }
+  field
+}
@@ -1,8 +1,16 @@
+pub mod algo;
 pub mod core;
+pub mod format;
+#[cfg(feature = "graphql")]
+pub mod graphql;
 pub mod model;
 pub mod query;
 pub mod repo;
+#[cfg(feature = "server")]
+pub mod server;
 
 pub use core::database::*;
+pub use core::key::*;
+pub use format::*;
 pub use model::*;
 pub use query::*;
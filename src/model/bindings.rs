@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{edge::Edge, node::Node};
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Binding {
+  Node(Node<Value>),
+  Edge(Edge<Value>),
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Bindings {
+  #[serde(flatten)]
+  pub values: HashMap<String, Binding>,
+}
+
+impl Bindings {
+  pub fn get(&self, name: &str) -> Option<&Binding> {
+    self.values.get(name)
+  }
+
+  pub fn node(&self, name: &str) -> Option<&Node<Value>> {
+    match self.values.get(name) {
+      Some(Binding::Node(node)) => Some(node),
+      _ => None,
+    }
+  }
+
+  pub fn edge(&self, name: &str) -> Option<&Edge<Value>> {
+    match self.values.get(name) {
+      Some(Binding::Edge(edge)) => Some(edge),
+      _ => None,
+    }
+  }
+
+  // round trips through JSON so rows can be decoded into a struct whose
+  // fields are named after the pattern variables, e.g. `Node<User>`
+  pub fn typed<R>(&self) -> serde_json::Result<R>
+  where
+    R: DeserializeOwned,
+  {
+    serde_json::from_value(serde_json::to_value(self)?)
+  }
+}
@@ -1,17 +1,23 @@
 use chrono::{DateTime, Utc};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::repo::{self, edge::EdgeRow};
+use crate::{
+  core::database::soft_delete_enabled,
+  repo::{self, edge::EdgeRow},
+};
 
 use super::node::Node;
 
 #[derive(Serialize, Deserialize)]
 pub struct Edge<T> {
   pub id: i64,
+  #[serde(default)]
+  pub namespace: String,
   pub from_node_id: i64,
   pub to_node_id: i64,
   pub uri: String,
   pub data: Option<T>,
+  pub deleted_at: Option<DateTime<Utc>>,
   pub updated_at: DateTime<Utc>,
   pub created_at: DateTime<Utc>,
 }
@@ -25,6 +31,7 @@ where
   fn try_from(row: EdgeRow) -> Result<Self, Self::Error> {
     Ok(Self {
       id: row.id,
+      namespace: row.namespace,
       from_node_id: row.from_node_id,
       to_node_id: row.to_node_id,
       uri: row.uri,
@@ -33,8 +40,11 @@ where
       } else {
         None
       },
-      updated_at: DateTime::<Utc>::from_timestamp(row.updated_at, 0).unwrap_or_default(),
-      created_at: DateTime::<Utc>::from_timestamp(row.created_at, 0).unwrap_or_default(),
+      deleted_at: row
+        .deleted_at
+        .and_then(DateTime::<Utc>::from_timestamp_millis),
+      updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+      created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
     })
   }
 }
@@ -80,15 +90,144 @@ where
   .await?;
   Ok(Edge {
     id: row.id,
+    namespace: row.namespace,
     from_node_id: row.from_node_id,
     to_node_id: row.to_node_id,
     uri: row.uri,
     data,
-    updated_at: DateTime::<Utc>::from_timestamp(row.updated_at, 0).unwrap_or_default(),
-    created_at: DateTime::<Utc>::from_timestamp(row.created_at, 0).unwrap_or_default(),
+    deleted_at: row
+      .deleted_at
+      .and_then(DateTime::<Utc>::from_timestamp_millis),
+    updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+    created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
   })
 }
 
+pub async fn create_edge_at<E>(
+  pool: &sqlx::SqlitePool,
+  from_node_id: i64,
+  to_node_id: i64,
+  uri: &str,
+  data: Option<E>,
+  created_at: DateTime<Utc>,
+  updated_at: DateTime<Utc>,
+) -> sqlx::Result<Edge<E>>
+where
+  E: Serialize,
+{
+  let data_json = if let Some(d) = &data {
+    match serde_json::to_string(d) {
+      Ok(json) => Some(json),
+      Err(e) => return Err(sqlx::Error::Encode(Box::new(e))),
+    }
+  } else {
+    None
+  };
+  let row = repo::edge::create_edge_at(
+    pool,
+    "",
+    from_node_id,
+    to_node_id,
+    uri,
+    data_json.as_deref(),
+    created_at.timestamp_millis(),
+    updated_at.timestamp_millis(),
+    None,
+    false,
+  )
+  .await?;
+  Ok(Edge {
+    id: row.id,
+    namespace: row.namespace,
+    from_node_id: row.from_node_id,
+    to_node_id: row.to_node_id,
+    uri: row.uri,
+    data,
+    deleted_at: row
+      .deleted_at
+      .and_then(DateTime::<Utc>::from_timestamp_millis),
+    updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+    created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
+  })
+}
+
+// links nodes of the default namespace, see `Namespace::create_edge_by_keys`
+pub async fn create_edge_by_keys<E>(
+  pool: &sqlx::SqlitePool,
+  from_node_key: &str,
+  to_node_key: &str,
+  uri: &str,
+  data: Option<E>,
+) -> sqlx::Result<Edge<E>>
+where
+  E: Serialize,
+{
+  let data_json = if let Some(d) = &data {
+    match serde_json::to_string(d) {
+      Ok(json) => Some(json),
+      Err(e) => return Err(sqlx::Error::Encode(Box::new(e))),
+    }
+  } else {
+    None
+  };
+  let row = repo::edge::create_edge_by_keys(
+    pool,
+    "",
+    from_node_key,
+    to_node_key,
+    uri,
+    data_json.as_deref(),
+  )
+  .await?;
+  Ok(Edge {
+    id: row.id,
+    namespace: row.namespace,
+    from_node_id: row.from_node_id,
+    to_node_id: row.to_node_id,
+    uri: row.uri,
+    data,
+    deleted_at: row
+      .deleted_at
+      .and_then(DateTime::<Utc>::from_timestamp_millis),
+    updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+    created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
+  })
+}
+
+// soft deleted edges are found through `get_edge_with_deleted`
+pub async fn get_edge<E>(pool: &sqlx::SqlitePool, edge_id: i64) -> sqlx::Result<Option<Edge<E>>>
+where
+  E: DeserializeOwned,
+{
+  let edge = repo::edge::get_edge(pool, edge_id, false).await?;
+  if let Some(row) = edge {
+    match Edge::try_from(row) {
+      Ok(edge) => Ok(Some(edge)),
+      Err(e) => Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  } else {
+    Ok(None)
+  }
+}
+
+pub async fn get_edge_with_deleted<E>(
+  pool: &sqlx::SqlitePool,
+  edge_id: i64,
+) -> sqlx::Result<Option<Edge<E>>>
+where
+  E: DeserializeOwned,
+{
+  let edge = repo::edge::get_edge(pool, edge_id, true).await?;
+  if let Some(row) = edge {
+    match Edge::try_from(row) {
+      Ok(edge) => Ok(Some(edge)),
+      Err(e) => Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  } else {
+    Ok(None)
+  }
+}
+
 pub async fn update_edge<E>(
   pool: &sqlx::SqlitePool,
   edge_id: i64,
@@ -108,19 +247,27 @@ where
   let row = repo::edge::update_edge(pool, edge_id, data_json.as_ref().map(String::as_ref)).await?;
   Ok(Edge {
     id: row.id,
+    namespace: row.namespace,
     from_node_id: row.from_node_id,
     to_node_id: row.to_node_id,
     uri: row.uri,
     data,
-    updated_at: DateTime::<Utc>::from_timestamp(row.updated_at, 0).unwrap_or_default(),
-    created_at: DateTime::<Utc>::from_timestamp(row.created_at, 0).unwrap_or_default(),
+    deleted_at: row
+      .deleted_at
+      .and_then(DateTime::<Utc>::from_timestamp_millis),
+    updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+    created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
   })
 }
 
+// soft deletes instead while `enable_soft_delete` is on
 pub async fn delete_edge<E>(pool: &sqlx::SqlitePool, edge_id: i64) -> sqlx::Result<Option<Edge<E>>>
 where
   E: Serialize + DeserializeOwned,
 {
+  if soft_delete_enabled(pool).await? {
+    return soft_delete_edge(pool, edge_id).await;
+  }
   let edge = repo::edge::delete_edge(pool, edge_id).await?;
   if let Some(row) = edge {
     match Edge::try_from(row) {
@@ -139,6 +286,9 @@ pub async fn delete_edges<E>(
 where
   E: Serialize + DeserializeOwned,
 {
+  if soft_delete_enabled(pool).await? {
+    return soft_delete_edges(pool, edge_ids).await;
+  }
   let rows: Vec<EdgeRow> = repo::edge::delete_edges(pool, edge_ids).await?;
   let mut edges = Vec::with_capacity(rows.len());
   for row in rows {
@@ -157,6 +307,9 @@ pub async fn delete_edges_by_uri<E>(
 where
   E: Serialize + DeserializeOwned,
 {
+  if soft_delete_enabled(pool).await? {
+    return soft_delete_edges_by_uri(pool, uri).await;
+  }
   let rows: Vec<EdgeRow> = repo::edge::delete_edges_by_uri(pool, uri).await?;
   let mut edges = Vec::with_capacity(rows.len());
   for row in rows {
@@ -167,3 +320,72 @@ where
   }
   Ok(edges)
 }
+
+pub async fn soft_delete_edge<E>(
+  pool: &sqlx::SqlitePool,
+  edge_id: i64,
+) -> sqlx::Result<Option<Edge<E>>>
+where
+  E: Serialize + DeserializeOwned,
+{
+  let edge = repo::edge::soft_delete_edge(pool, edge_id).await?;
+  if let Some(row) = edge {
+    match Edge::try_from(row) {
+      Ok(edge) => Ok(Some(edge)),
+      Err(e) => Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  } else {
+    Ok(None)
+  }
+}
+
+pub async fn soft_delete_edges<E>(
+  pool: &sqlx::SqlitePool,
+  edge_ids: &[i64],
+) -> sqlx::Result<Vec<Edge<E>>>
+where
+  E: Serialize + DeserializeOwned,
+{
+  let rows: Vec<EdgeRow> = repo::edge::soft_delete_edges(pool, edge_ids).await?;
+  let mut edges = Vec::with_capacity(rows.len());
+  for row in rows {
+    match Edge::try_from(row) {
+      Ok(edge) => edges.push(edge),
+      Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  }
+  Ok(edges)
+}
+
+pub async fn soft_delete_edges_by_uri<E>(
+  pool: &sqlx::SqlitePool,
+  uri: &str,
+) -> sqlx::Result<Vec<Edge<E>>>
+where
+  E: Serialize + DeserializeOwned,
+{
+  let rows: Vec<EdgeRow> = repo::edge::soft_delete_edges_by_uri(pool, uri).await?;
+  let mut edges = Vec::with_capacity(rows.len());
+  for row in rows {
+    match Edge::try_from(row) {
+      Ok(edge) => edges.push(edge),
+      Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  }
+  Ok(edges)
+}
+
+pub async fn restore_edge<E>(pool: &sqlx::SqlitePool, edge_id: i64) -> sqlx::Result<Option<Edge<E>>>
+where
+  E: Serialize + DeserializeOwned,
+{
+  let edge = repo::edge::restore_edge(pool, edge_id).await?;
+  if let Some(row) = edge {
+    match Edge::try_from(row) {
+      Ok(edge) => Ok(Some(edge)),
+      Err(e) => Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  } else {
+    Ok(None)
+  }
+}
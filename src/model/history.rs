@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::repo::{
+  self,
+  history::{EdgeHistoryRow, NodeHistoryRow},
+};
+
+use super::{edge::Edge, node::Node};
+
+#[derive(Serialize, Deserialize)]
+pub struct NodeVersion<T> {
+  pub node: Node<T>,
+  pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EdgeVersion<E> {
+  pub edge: Edge<E>,
+  pub recorded_at: DateTime<Utc>,
+}
+
+impl<T> TryFrom<NodeHistoryRow> for NodeVersion<T>
+where
+  T: DeserializeOwned,
+{
+  type Error = serde_json::Error;
+
+  fn try_from(row: NodeHistoryRow) -> Result<Self, Self::Error> {
+    Ok(Self {
+      node: Node {
+        id: row.node_id,
+        namespace: row.namespace,
+        key: row.key,
+        uri: row.uri,
+        data: serde_json::from_str::<T>(&row.data)?,
+        deleted_at: row
+          .deleted_at
+          .and_then(DateTime::<Utc>::from_timestamp_millis),
+        updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+        created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
+      },
+      recorded_at: DateTime::<Utc>::from_timestamp_millis(row.recorded_at).unwrap_or_default(),
+    })
+  }
+}
+
+impl<E> TryFrom<EdgeHistoryRow> for EdgeVersion<E>
+where
+  E: DeserializeOwned,
+{
+  type Error = serde_json::Error;
+
+  fn try_from(row: EdgeHistoryRow) -> Result<Self, Self::Error> {
+    Ok(Self {
+      edge: Edge {
+        id: row.edge_id,
+        namespace: row.namespace,
+        from_node_id: row.from_node_id,
+        to_node_id: row.to_node_id,
+        uri: row.uri,
+        data: if let Some(data) = row.data {
+          Some(serde_json::from_str::<E>(&data)?)
+        } else {
+          None
+        },
+        deleted_at: row
+          .deleted_at
+          .and_then(DateTime::<Utc>::from_timestamp_millis),
+        updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+        created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
+      },
+      recorded_at: DateTime::<Utc>::from_timestamp_millis(row.recorded_at).unwrap_or_default(),
+    })
+  }
+}
+
+pub async fn enable_history(pool: &sqlx::SqlitePool) -> sqlx::Result<()> {
+  repo::history::set_history_enabled(pool, true).await
+}
+
+pub async fn disable_history(pool: &sqlx::SqlitePool) -> sqlx::Result<()> {
+  repo::history::set_history_enabled(pool, false).await
+}
+
+pub async fn history_enabled(pool: &sqlx::SqlitePool) -> sqlx::Result<bool> {
+  repo::history::history_enabled(pool).await
+}
+
+pub async fn node_history<T>(
+  pool: &sqlx::SqlitePool,
+  node_id: i64,
+) -> sqlx::Result<Vec<NodeVersion<T>>>
+where
+  T: DeserializeOwned,
+{
+  let rows = repo::history::node_history(pool, node_id).await?;
+  let mut versions = Vec::with_capacity(rows.len());
+  for row in rows {
+    match NodeVersion::try_from(row) {
+      Ok(version) => versions.push(version),
+      Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  }
+  Ok(versions)
+}
+
+pub async fn edge_history<E>(
+  pool: &sqlx::SqlitePool,
+  edge_id: i64,
+) -> sqlx::Result<Vec<EdgeVersion<E>>>
+where
+  E: DeserializeOwned,
+{
+  let rows = repo::history::edge_history(pool, edge_id).await?;
+  let mut versions = Vec::with_capacity(rows.len());
+  for row in rows {
+    match EdgeVersion::try_from(row) {
+      Ok(version) => versions.push(version),
+      Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  }
+  Ok(versions)
+}
@@ -0,0 +1,38 @@
+use serde::de::DeserializeOwned;
+
+use crate::repo;
+
+use super::node::Node;
+
+// returns false if the node already had the label
+pub async fn add_label(pool: &sqlx::SqlitePool, node_id: i64, label: &str) -> sqlx::Result<bool> {
+  repo::label::add_label(pool, node_id, label).await
+}
+
+// returns false if the node did not have the label
+pub async fn remove_label(
+  pool: &sqlx::SqlitePool,
+  node_id: i64,
+  label: &str,
+) -> sqlx::Result<bool> {
+  repo::label::remove_label(pool, node_id, label).await
+}
+
+pub async fn node_labels(pool: &sqlx::SqlitePool, node_id: i64) -> sqlx::Result<Vec<String>> {
+  repo::label::node_labels(pool, node_id).await
+}
+
+pub async fn nodes_with_label<T>(pool: &sqlx::SqlitePool, label: &str) -> sqlx::Result<Vec<Node<T>>>
+where
+  T: DeserializeOwned,
+{
+  let rows = repo::label::nodes_with_label(pool, label).await?;
+  let mut nodes = Vec::with_capacity(rows.len());
+  for row in rows {
+    match Node::try_from(row) {
+      Ok(node) => nodes.push(node),
+      Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  }
+  Ok(nodes)
+}
@@ -1,10 +1,30 @@
+pub mod bindings;
 pub mod edge;
+pub mod history;
+pub mod label;
+pub mod namespace;
+pub mod neighbor;
 pub mod node;
 pub mod node_edge;
+pub mod subgraph;
 
+pub use bindings::{Binding, Bindings};
 pub use edge::{
-  create_edge, create_edge_with_ids, delete_edge, delete_edges, delete_edges_by_uri, update_edge,
-  Edge,
+  create_edge, create_edge_at, create_edge_by_keys, create_edge_with_ids, delete_edge,
+  delete_edges, delete_edges_by_uri, get_edge, get_edge_with_deleted, restore_edge,
+  soft_delete_edge, soft_delete_edges, soft_delete_edges_by_uri, update_edge, Edge,
+};
+pub use history::{
+  disable_history, edge_history, enable_history, history_enabled, node_history, EdgeVersion,
+  NodeVersion,
+};
+pub use label::{add_label, node_labels, nodes_with_label, remove_label};
+pub use namespace::Namespace;
+pub use neighbor::{degree, degrees, in_neighbors, out_neighbors, Degree, Direction};
+pub use node::{
+  create_node, create_node_at, create_node_with_key, delete_node, delete_nodes,
+  delete_nodes_by_uri, get_node, get_node_by_key, get_node_with_deleted, restore_node,
+  soft_delete_node, soft_delete_nodes, soft_delete_nodes_by_uri, update_node, Node,
 };
-pub use node::{create_node, delete_node, delete_nodes, delete_nodes_by_uri, update_node, Node};
 pub use node_edge::NodeEdge;
+pub use subgraph::{IdMapping, Subgraph};
@@ -0,0 +1,395 @@
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+  core::database::soft_delete_enabled,
+  query::{pattern::Pattern, ScopedQuery},
+  repo::{self, edge::EdgeRow, node::NodeRow},
+};
+
+use super::{edge::Edge, node, node::Node};
+
+// A handle whose queries and mutations only see one namespace. Rows outside
+// of it behave as if they did not exist: lookups return `None` and updates
+// fail with `RowNotFound`. Nodes and edges created through the global
+// functions live in the default namespace `""`.
+#[derive(Debug, Clone)]
+pub struct Namespace {
+  pool: sqlx::SqlitePool,
+  name: String,
+  allow_cross_namespace: bool,
+}
+
+impl Namespace {
+  pub fn new(pool: &sqlx::SqlitePool, name: impl Into<String>) -> Self {
+    Self {
+      pool: pool.clone(),
+      name: name.into(),
+      allow_cross_namespace: false,
+    }
+  }
+
+  // Lets `create_edge` link a node of this namespace to a node of another one.
+  // Without it the database rejects edges whose nodes are in other namespaces.
+  pub fn allow_cross_namespace(mut self, allow: bool) -> Self {
+    self.allow_cross_namespace = allow;
+    self
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn pool(&self) -> &sqlx::SqlitePool {
+    &self.pool
+  }
+
+  pub fn query(&self, query: impl Into<ScopedQuery>) -> ScopedQuery {
+    query.into().namespace(self.name.clone())
+  }
+
+  pub fn pattern(&self, pattern: Pattern) -> Pattern {
+    pattern.namespace(self.name.clone())
+  }
+
+  pub async fn create_node<T>(&self, uri: &str, data: T) -> sqlx::Result<Node<T>>
+  where
+    T: Serialize,
+  {
+    let data_json = match serde_json::to_string(&data) {
+      Ok(json) => json,
+      Err(e) => return Err(sqlx::Error::Encode(Box::new(e))),
+    };
+    let row = repo::node::create_node_in(&self.pool, &self.name, uri, &data_json).await?;
+    Ok(Node {
+      id: row.id,
+      namespace: row.namespace,
+      key: row.key,
+      uri: row.uri,
+      data,
+      deleted_at: row
+        .deleted_at
+        .and_then(DateTime::<Utc>::from_timestamp_millis),
+      updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+      created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
+    })
+  }
+
+  // without a key a UUIDv7 is generated
+  pub async fn create_node_with_key<T>(
+    &self,
+    key: Option<&str>,
+    uri: &str,
+    data: T,
+  ) -> sqlx::Result<Node<T>>
+  where
+    T: Serialize,
+  {
+    let data_json = match serde_json::to_string(&data) {
+      Ok(json) => json,
+      Err(e) => return Err(sqlx::Error::Encode(Box::new(e))),
+    };
+    let key = node::resolve_key(key)?;
+    let row =
+      repo::node::create_node_with_key(&self.pool, &self.name, &key, uri, &data_json).await?;
+    Ok(Node {
+      id: row.id,
+      namespace: row.namespace,
+      key: row.key,
+      uri: row.uri,
+      data,
+      deleted_at: row
+        .deleted_at
+        .and_then(DateTime::<Utc>::from_timestamp_millis),
+      updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+      created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
+    })
+  }
+
+  pub async fn get_node<T>(&self, node_id: i64) -> sqlx::Result<Option<Node<T>>>
+  where
+    T: DeserializeOwned,
+  {
+    decode_node(repo::node::get_node_in(&self.pool, &self.name, node_id, false).await?)
+  }
+
+  pub async fn get_node_with_deleted<T>(&self, node_id: i64) -> sqlx::Result<Option<Node<T>>>
+  where
+    T: DeserializeOwned,
+  {
+    decode_node(repo::node::get_node_in(&self.pool, &self.name, node_id, true).await?)
+  }
+
+  pub async fn get_node_by_key<T>(&self, key: &str) -> sqlx::Result<Option<Node<T>>>
+  where
+    T: DeserializeOwned,
+  {
+    decode_node(repo::node::get_node_by_key(&self.pool, &self.name, key).await?)
+  }
+
+  pub async fn update_node<T>(&self, node_id: i64, data: T) -> sqlx::Result<Node<T>>
+  where
+    T: Serialize,
+  {
+    let data_json = match serde_json::to_string(&data) {
+      Ok(json) => json,
+      Err(e) => return Err(sqlx::Error::Encode(Box::new(e))),
+    };
+    let row = repo::node::update_node_in(&self.pool, &self.name, node_id, &data_json).await?;
+    Ok(Node {
+      id: row.id,
+      namespace: row.namespace,
+      key: row.key,
+      uri: row.uri,
+      data,
+      deleted_at: row
+        .deleted_at
+        .and_then(DateTime::<Utc>::from_timestamp_millis),
+      updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+      created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
+    })
+  }
+
+  // soft deletes instead while `enable_soft_delete` is on
+  pub async fn delete_node<T>(&self, node_id: i64) -> sqlx::Result<Option<Node<T>>>
+  where
+    T: Serialize + DeserializeOwned,
+  {
+    if soft_delete_enabled(&self.pool).await? {
+      return self.soft_delete_node(node_id).await;
+    }
+    decode_node(repo::node::delete_node_in(&self.pool, &self.name, node_id).await?)
+  }
+
+  pub async fn delete_nodes<T>(&self, node_ids: &[i64]) -> sqlx::Result<Vec<Node<T>>>
+  where
+    T: Serialize + DeserializeOwned,
+  {
+    if soft_delete_enabled(&self.pool).await? {
+      return self.soft_delete_nodes(node_ids).await;
+    }
+    decode_nodes(repo::node::delete_nodes_in(&self.pool, &self.name, node_ids).await?)
+  }
+
+  pub async fn delete_nodes_by_uri<T>(&self, uri: &str) -> sqlx::Result<Vec<Node<T>>>
+  where
+    T: Serialize + DeserializeOwned,
+  {
+    if soft_delete_enabled(&self.pool).await? {
+      return self.soft_delete_nodes_by_uri(uri).await;
+    }
+    decode_nodes(repo::node::delete_nodes_by_uri_in(&self.pool, &self.name, uri).await?)
+  }
+
+  pub async fn soft_delete_node<T>(&self, node_id: i64) -> sqlx::Result<Option<Node<T>>>
+  where
+    T: Serialize + DeserializeOwned,
+  {
+    decode_node(repo::node::soft_delete_node_in(&self.pool, &self.name, node_id).await?)
+  }
+
+  pub async fn soft_delete_nodes<T>(&self, node_ids: &[i64]) -> sqlx::Result<Vec<Node<T>>>
+  where
+    T: Serialize + DeserializeOwned,
+  {
+    decode_nodes(repo::node::soft_delete_nodes_in(&self.pool, &self.name, node_ids).await?)
+  }
+
+  pub async fn soft_delete_nodes_by_uri<T>(&self, uri: &str) -> sqlx::Result<Vec<Node<T>>>
+  where
+    T: Serialize + DeserializeOwned,
+  {
+    decode_nodes(repo::node::soft_delete_nodes_by_uri_in(&self.pool, &self.name, uri).await?)
+  }
+
+  pub async fn restore_node<T>(&self, node_id: i64) -> sqlx::Result<Option<Node<T>>>
+  where
+    T: Serialize + DeserializeOwned,
+  {
+    decode_node(repo::node::restore_node_in(&self.pool, &self.name, node_id).await?)
+  }
+
+  pub async fn create_edge<E>(
+    &self,
+    from_node_id: i64,
+    to_node_id: i64,
+    uri: &str,
+    data: Option<E>,
+  ) -> sqlx::Result<Edge<E>>
+  where
+    E: Serialize,
+  {
+    let data_json = if let Some(d) = &data {
+      match serde_json::to_string(d) {
+        Ok(json) => Some(json),
+        Err(e) => return Err(sqlx::Error::Encode(Box::new(e))),
+      }
+    } else {
+      None
+    };
+    let row = repo::edge::create_edge_in(
+      &self.pool,
+      &self.name,
+      from_node_id,
+      to_node_id,
+      uri,
+      data_json.as_deref(),
+      self.allow_cross_namespace,
+    )
+    .await?;
+    Ok(Edge {
+      id: row.id,
+      namespace: row.namespace,
+      from_node_id: row.from_node_id,
+      to_node_id: row.to_node_id,
+      uri: row.uri,
+      data,
+      deleted_at: row
+        .deleted_at
+        .and_then(DateTime::<Utc>::from_timestamp_millis),
+      updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+      created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
+    })
+  }
+
+  // both nodes are looked up by key in this namespace
+  pub async fn create_edge_by_keys<E>(
+    &self,
+    from_node_key: &str,
+    to_node_key: &str,
+    uri: &str,
+    data: Option<E>,
+  ) -> sqlx::Result<Edge<E>>
+  where
+    E: Serialize,
+  {
+    let data_json = if let Some(d) = &data {
+      match serde_json::to_string(d) {
+        Ok(json) => Some(json),
+        Err(e) => return Err(sqlx::Error::Encode(Box::new(e))),
+      }
+    } else {
+      None
+    };
+    let row = repo::edge::create_edge_by_keys(
+      &self.pool,
+      &self.name,
+      from_node_key,
+      to_node_key,
+      uri,
+      data_json.as_deref(),
+    )
+    .await?;
+    Ok(Edge {
+      id: row.id,
+      namespace: row.namespace,
+      from_node_id: row.from_node_id,
+      to_node_id: row.to_node_id,
+      uri: row.uri,
+      data,
+      deleted_at: row
+        .deleted_at
+        .and_then(DateTime::<Utc>::from_timestamp_millis),
+      updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+      created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
+    })
+  }
+
+  pub async fn get_edge<E>(&self, edge_id: i64) -> sqlx::Result<Option<Edge<E>>>
+  where
+    E: DeserializeOwned,
+  {
+    decode_edge(repo::edge::get_edge_in(&self.pool, &self.name, edge_id, false).await?)
+  }
+
+  pub async fn get_edge_with_deleted<E>(&self, edge_id: i64) -> sqlx::Result<Option<Edge<E>>>
+  where
+    E: DeserializeOwned,
+  {
+    decode_edge(repo::edge::get_edge_in(&self.pool, &self.name, edge_id, true).await?)
+  }
+
+  pub async fn update_edge<E>(&self, edge_id: i64, data: Option<E>) -> sqlx::Result<Edge<E>>
+  where
+    E: Serialize + DeserializeOwned,
+  {
+    let data_json = if let Some(d) = &data {
+      match serde_json::to_string(d) {
+        Ok(json) => Some(json),
+        Err(e) => return Err(sqlx::Error::Encode(Box::new(e))),
+      }
+    } else {
+      None
+    };
+    let row =
+      repo::edge::update_edge_in(&self.pool, &self.name, edge_id, data_json.as_deref()).await?;
+    Ok(Edge {
+      id: row.id,
+      namespace: row.namespace,
+      from_node_id: row.from_node_id,
+      to_node_id: row.to_node_id,
+      uri: row.uri,
+      data,
+      deleted_at: row
+        .deleted_at
+        .and_then(DateTime::<Utc>::from_timestamp_millis),
+      updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+      created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
+    })
+  }
+
+  // soft deletes instead while `enable_soft_delete` is on
+  pub async fn delete_edge<E>(&self, edge_id: i64) -> sqlx::Result<Option<Edge<E>>>
+  where
+    E: Serialize + DeserializeOwned,
+  {
+    if soft_delete_enabled(&self.pool).await? {
+      return self.soft_delete_edge(edge_id).await;
+    }
+    decode_edge(repo::edge::delete_edge_in(&self.pool, &self.name, edge_id).await?)
+  }
+
+  pub async fn soft_delete_edge<E>(&self, edge_id: i64) -> sqlx::Result<Option<Edge<E>>>
+  where
+    E: Serialize + DeserializeOwned,
+  {
+    decode_edge(repo::edge::soft_delete_edge_in(&self.pool, &self.name, edge_id).await?)
+  }
+
+  pub async fn restore_edge<E>(&self, edge_id: i64) -> sqlx::Result<Option<Edge<E>>>
+  where
+    E: Serialize + DeserializeOwned,
+  {
+    decode_edge(repo::edge::restore_edge_in(&self.pool, &self.name, edge_id).await?)
+  }
+}
+
+fn decode_node<T>(row: Option<NodeRow>) -> sqlx::Result<Option<Node<T>>>
+where
+  T: DeserializeOwned,
+{
+  match row.map(Node::try_from).transpose() {
+    Ok(node) => Ok(node),
+    Err(e) => Err(sqlx::Error::Decode(Box::new(e))),
+  }
+}
+
+fn decode_nodes<T>(rows: Vec<NodeRow>) -> sqlx::Result<Vec<Node<T>>>
+where
+  T: DeserializeOwned,
+{
+  match rows.into_iter().map(Node::try_from).collect() {
+    Ok(nodes) => Ok(nodes),
+    Err(e) => Err(sqlx::Error::Decode(Box::new(e))),
+  }
+}
+
+fn decode_edge<E>(row: Option<EdgeRow>) -> sqlx::Result<Option<Edge<E>>>
+where
+  E: DeserializeOwned,
+{
+  match row.map(Edge::try_from).transpose() {
+    Ok(edge) => Ok(edge),
+    Err(e) => Err(sqlx::Error::Decode(Box::new(e))),
+  }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::repo::{self, node::NodeRow};
+
+use super::node::Node;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+  Outgoing,
+  Incoming,
+  Both,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Degree {
+  pub outgoing: i64,
+  pub incoming: i64,
+}
+
+pub async fn out_neighbors<T>(
+  pool: &sqlx::SqlitePool,
+  node_id: i64,
+  edge_uri: Option<&str>,
+) -> sqlx::Result<Vec<Node<T>>>
+where
+  T: DeserializeOwned,
+{
+  let rows = repo::neighbor::out_neighbors(pool, node_id, edge_uri).await?;
+  into_nodes(rows)
+}
+
+pub async fn in_neighbors<T>(
+  pool: &sqlx::SqlitePool,
+  node_id: i64,
+  edge_uri: Option<&str>,
+) -> sqlx::Result<Vec<Node<T>>>
+where
+  T: DeserializeOwned,
+{
+  let rows = repo::neighbor::in_neighbors(pool, node_id, edge_uri).await?;
+  into_nodes(rows)
+}
+
+pub async fn degree(
+  pool: &sqlx::SqlitePool,
+  node_id: i64,
+  direction: Direction,
+  edge_uri: Option<&str>,
+) -> sqlx::Result<i64> {
+  repo::neighbor::degree(pool, node_id, direction, edge_uri).await
+}
+
+pub async fn degrees(
+  pool: &sqlx::SqlitePool,
+  node_ids: &[i64],
+  edge_uri: Option<&str>,
+) -> sqlx::Result<HashMap<i64, Degree>> {
+  let rows = repo::neighbor::degrees(pool, node_ids, edge_uri).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|(id, outgoing, incoming)| (id, Degree { outgoing, incoming }))
+      .collect(),
+  )
+}
+
+fn into_nodes<T>(rows: Vec<NodeRow>) -> sqlx::Result<Vec<Node<T>>>
+where
+  T: DeserializeOwned,
+{
+  let mut nodes = Vec::with_capacity(rows.len());
+  for row in rows {
+    match Node::try_from(row) {
+      Ok(node) => nodes.push(node),
+      Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  }
+  Ok(nodes)
+}
@@ -1,13 +1,21 @@
 use chrono::{DateTime, Utc};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::repo::{self, node::NodeRow};
+use crate::{
+  core::{database::soft_delete_enabled, key::generate_key},
+  repo::{self, node::NodeRow},
+};
 
 #[derive(Serialize, Deserialize)]
 pub struct Node<T> {
   pub id: i64,
+  #[serde(default)]
+  pub namespace: String,
+  #[serde(default)]
+  pub key: Option<String>,
   pub uri: String,
   pub data: T,
+  pub deleted_at: Option<DateTime<Utc>>,
   pub updated_at: DateTime<Utc>,
   pub created_at: DateTime<Utc>,
 }
@@ -21,10 +29,15 @@ where
   fn try_from(row: NodeRow) -> Result<Self, Self::Error> {
     Ok(Self {
       id: row.id,
+      namespace: row.namespace,
+      key: row.key,
       uri: row.uri,
       data: serde_json::from_str::<T>(&row.data)?,
-      updated_at: DateTime::<Utc>::from_timestamp(row.updated_at, 0).unwrap_or_default(),
-      created_at: DateTime::<Utc>::from_timestamp(row.created_at, 0).unwrap_or_default(),
+      deleted_at: row
+        .deleted_at
+        .and_then(DateTime::<Utc>::from_timestamp_millis),
+      updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+      created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
     })
   }
 }
@@ -40,13 +53,145 @@ where
   let row = repo::node::create_node(pool, uri, &data_json).await?;
   Ok(Node {
     id: row.id,
+    namespace: row.namespace,
+    key: row.key,
     uri: row.uri,
     data,
-    updated_at: DateTime::<Utc>::from_timestamp(row.updated_at, 0).unwrap_or_default(),
-    created_at: DateTime::<Utc>::from_timestamp(row.created_at, 0).unwrap_or_default(),
+    deleted_at: row
+      .deleted_at
+      .and_then(DateTime::<Utc>::from_timestamp_millis),
+    updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+    created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
   })
 }
 
+// Without a key a UUIDv7 is generated. The node lives in the default
+// namespace, keys only need to be unique within their namespace.
+pub async fn create_node_with_key<T>(
+  pool: &sqlx::SqlitePool,
+  key: Option<&str>,
+  uri: &str,
+  data: T,
+) -> sqlx::Result<Node<T>>
+where
+  T: Serialize,
+{
+  let data_json = match serde_json::to_string(&data) {
+    Ok(json) => json,
+    Err(e) => return Err(sqlx::Error::Encode(Box::new(e))),
+  };
+  let key = resolve_key(key)?;
+  let row = repo::node::create_node_with_key(pool, "", &key, uri, &data_json).await?;
+  Ok(Node {
+    id: row.id,
+    namespace: row.namespace,
+    key: row.key,
+    uri: row.uri,
+    data,
+    deleted_at: row
+      .deleted_at
+      .and_then(DateTime::<Utc>::from_timestamp_millis),
+    updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+    created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
+  })
+}
+
+pub(crate) fn resolve_key(key: Option<&str>) -> sqlx::Result<String> {
+  match key {
+    Some(key) => Ok(key.to_owned()),
+    None => generate_key().map_err(|e| sqlx::Error::Encode(Box::new(e))),
+  }
+}
+
+pub async fn create_node_at<T>(
+  pool: &sqlx::SqlitePool,
+  uri: &str,
+  data: T,
+  created_at: DateTime<Utc>,
+  updated_at: DateTime<Utc>,
+) -> sqlx::Result<Node<T>>
+where
+  T: Serialize,
+{
+  let data_json = match serde_json::to_string(&data) {
+    Ok(json) => json,
+    Err(e) => return Err(sqlx::Error::Encode(Box::new(e))),
+  };
+  let row = repo::node::create_node_at(
+    pool,
+    "",
+    None,
+    uri,
+    &data_json,
+    created_at.timestamp_millis(),
+    updated_at.timestamp_millis(),
+    None,
+  )
+  .await?;
+  Ok(Node {
+    id: row.id,
+    namespace: row.namespace,
+    key: row.key,
+    uri: row.uri,
+    data,
+    deleted_at: row
+      .deleted_at
+      .and_then(DateTime::<Utc>::from_timestamp_millis),
+    updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+    created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
+  })
+}
+
+// soft deleted nodes are found through `get_node_with_deleted`
+pub async fn get_node<T>(pool: &sqlx::SqlitePool, node_id: i64) -> sqlx::Result<Option<Node<T>>>
+where
+  T: DeserializeOwned,
+{
+  let node = repo::node::get_node(pool, node_id, false).await?;
+  if let Some(row) = node {
+    match Node::try_from(row) {
+      Ok(node) => Ok(Some(node)),
+      Err(e) => Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  } else {
+    Ok(None)
+  }
+}
+
+pub async fn get_node_with_deleted<T>(
+  pool: &sqlx::SqlitePool,
+  node_id: i64,
+) -> sqlx::Result<Option<Node<T>>>
+where
+  T: DeserializeOwned,
+{
+  let node = repo::node::get_node(pool, node_id, true).await?;
+  if let Some(row) = node {
+    match Node::try_from(row) {
+      Ok(node) => Ok(Some(node)),
+      Err(e) => Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  } else {
+    Ok(None)
+  }
+}
+
+// keys of other namespaces are found through `Namespace::get_node_by_key`
+pub async fn get_node_by_key<T>(pool: &sqlx::SqlitePool, key: &str) -> sqlx::Result<Option<Node<T>>>
+where
+  T: DeserializeOwned,
+{
+  let node = repo::node::get_node_by_key(pool, "", key).await?;
+  if let Some(row) = node {
+    match Node::try_from(row) {
+      Ok(node) => Ok(Some(node)),
+      Err(e) => Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  } else {
+    Ok(None)
+  }
+}
+
 pub async fn update_node<T>(pool: &sqlx::SqlitePool, node_id: i64, data: T) -> sqlx::Result<Node<T>>
 where
   T: Serialize,
@@ -58,17 +203,26 @@ where
   let row = repo::node::update_node(pool, node_id, &data_json).await?;
   Ok(Node {
     id: row.id,
+    namespace: row.namespace,
+    key: row.key,
     uri: row.uri,
     data,
-    updated_at: DateTime::<Utc>::from_timestamp(row.updated_at, 0).unwrap_or_default(),
-    created_at: DateTime::<Utc>::from_timestamp(row.created_at, 0).unwrap_or_default(),
+    deleted_at: row
+      .deleted_at
+      .and_then(DateTime::<Utc>::from_timestamp_millis),
+    updated_at: DateTime::<Utc>::from_timestamp_millis(row.updated_at).unwrap_or_default(),
+    created_at: DateTime::<Utc>::from_timestamp_millis(row.created_at).unwrap_or_default(),
   })
 }
 
+// soft deletes instead while `enable_soft_delete` is on
 pub async fn delete_node<T>(pool: &sqlx::SqlitePool, node_id: i64) -> sqlx::Result<Option<Node<T>>>
 where
   T: Serialize + DeserializeOwned,
 {
+  if soft_delete_enabled(pool).await? {
+    return soft_delete_node(pool, node_id).await;
+  }
   let node = repo::node::delete_node(pool, node_id).await?;
   if let Some(row) = node {
     match Node::try_from(row) {
@@ -87,6 +241,9 @@ pub async fn delete_nodes<E>(
 where
   E: Serialize + DeserializeOwned,
 {
+  if soft_delete_enabled(pool).await? {
+    return soft_delete_nodes(pool, node_ids).await;
+  }
   let rows: Vec<NodeRow> = repo::node::delete_nodes(pool, node_ids).await?;
   let mut nodes = Vec::with_capacity(rows.len());
   for row in rows {
@@ -105,6 +262,9 @@ pub async fn delete_nodes_by_uri<T>(
 where
   T: Serialize + DeserializeOwned,
 {
+  if soft_delete_enabled(pool).await? {
+    return soft_delete_nodes_by_uri(pool, uri).await;
+  }
   let rows: Vec<NodeRow> = repo::node::delete_nodes_by_uri(pool, uri).await?;
   let mut nodes = Vec::with_capacity(rows.len());
   for row in rows {
@@ -115,3 +275,72 @@ where
   }
   Ok(nodes)
 }
+
+pub async fn soft_delete_node<T>(
+  pool: &sqlx::SqlitePool,
+  node_id: i64,
+) -> sqlx::Result<Option<Node<T>>>
+where
+  T: Serialize + DeserializeOwned,
+{
+  let node = repo::node::soft_delete_node(pool, node_id).await?;
+  if let Some(row) = node {
+    match Node::try_from(row) {
+      Ok(node) => Ok(Some(node)),
+      Err(e) => Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  } else {
+    Ok(None)
+  }
+}
+
+pub async fn soft_delete_nodes<T>(
+  pool: &sqlx::SqlitePool,
+  node_ids: &[i64],
+) -> sqlx::Result<Vec<Node<T>>>
+where
+  T: Serialize + DeserializeOwned,
+{
+  let rows: Vec<NodeRow> = repo::node::soft_delete_nodes(pool, node_ids).await?;
+  let mut nodes = Vec::with_capacity(rows.len());
+  for row in rows {
+    match Node::try_from(row) {
+      Ok(node) => nodes.push(node),
+      Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  }
+  Ok(nodes)
+}
+
+pub async fn soft_delete_nodes_by_uri<T>(
+  pool: &sqlx::SqlitePool,
+  uri: &str,
+) -> sqlx::Result<Vec<Node<T>>>
+where
+  T: Serialize + DeserializeOwned,
+{
+  let rows: Vec<NodeRow> = repo::node::soft_delete_nodes_by_uri(pool, uri).await?;
+  let mut nodes = Vec::with_capacity(rows.len());
+  for row in rows {
+    match Node::try_from(row) {
+      Ok(node) => nodes.push(node),
+      Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  }
+  Ok(nodes)
+}
+
+pub async fn restore_node<T>(pool: &sqlx::SqlitePool, node_id: i64) -> sqlx::Result<Option<Node<T>>>
+where
+  T: Serialize + DeserializeOwned,
+{
+  let node = repo::node::restore_node(pool, node_id).await?;
+  if let Some(row) = node {
+    match Node::try_from(row) {
+      Ok(node) => Ok(Some(node)),
+      Err(e) => Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  } else {
+    Ok(None)
+  }
+}
@@ -24,22 +24,35 @@ where
     Ok(Self {
       from_node: Node {
         id: row.from_node_id,
+        namespace: row.from_node_namespace,
+        key: row.from_node_key,
         uri: row.from_node_uri,
         data: serde_json::from_str::<FN>(&row.from_node_data)?,
-        updated_at: DateTime::<Utc>::from_timestamp(row.from_node_updated_at, 0)
+        deleted_at: row
+          .from_node_deleted_at
+          .and_then(DateTime::<Utc>::from_timestamp_millis),
+        updated_at: DateTime::<Utc>::from_timestamp_millis(row.from_node_updated_at)
           .unwrap_or_default(),
-        created_at: DateTime::<Utc>::from_timestamp(row.from_node_created_at, 0)
+        created_at: DateTime::<Utc>::from_timestamp_millis(row.from_node_created_at)
           .unwrap_or_default(),
       },
       to_node: Node {
         id: row.to_node_id,
+        namespace: row.to_node_namespace,
+        key: row.to_node_key,
         uri: row.to_node_uri,
         data: serde_json::from_str::<TN>(&row.to_node_data)?,
-        updated_at: DateTime::<Utc>::from_timestamp(row.to_node_updated_at, 0).unwrap_or_default(),
-        created_at: DateTime::<Utc>::from_timestamp(row.to_node_created_at, 0).unwrap_or_default(),
+        deleted_at: row
+          .to_node_deleted_at
+          .and_then(DateTime::<Utc>::from_timestamp_millis),
+        updated_at: DateTime::<Utc>::from_timestamp_millis(row.to_node_updated_at)
+          .unwrap_or_default(),
+        created_at: DateTime::<Utc>::from_timestamp_millis(row.to_node_created_at)
+          .unwrap_or_default(),
       },
       edge: Edge {
         id: row.edge_id,
+        namespace: row.edge_namespace,
         from_node_id: row.from_node_id,
         to_node_id: row.to_node_id,
         uri: row.edge_uri,
@@ -48,8 +61,11 @@ where
         } else {
           None
         },
-        updated_at: DateTime::<Utc>::from_timestamp(row.edge_updated_at, 0).unwrap_or_default(),
-        created_at: DateTime::<Utc>::from_timestamp(row.edge_created_at, 0).unwrap_or_default(),
+        deleted_at: row
+          .edge_deleted_at
+          .and_then(DateTime::<Utc>::from_timestamp_millis),
+        updated_at: DateTime::<Utc>::from_timestamp_millis(row.edge_updated_at).unwrap_or_default(),
+        created_at: DateTime::<Utc>::from_timestamp_millis(row.edge_created_at).unwrap_or_default(),
       },
     })
   }
@@ -0,0 +1,299 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+  query::{Query, QueryExpr, QueryField, QueryOp, QueryValue, ScopedQuery},
+  repo,
+};
+
+use super::{edge::Edge, neighbor::Direction, node::Node, node_edge::NodeEdge};
+
+// node ids per `IN (...)` list when walking the neighbourhood
+const ID_BATCH_SIZE: usize = 500;
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Subgraph {
+  pub nodes: Vec<Node<Value>>,
+  pub edges: Vec<Edge<Value>>,
+  // labels by node id, nodes without labels are left out
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub labels: HashMap<i64, Vec<String>>,
+}
+
+impl Subgraph {
+  // Node conditions (`node.*`) select nodes and every edge between them, the
+  // others select edges with both of their nodes. A query without conditions
+  // selects every node, including those without edges, and every edge.
+  pub async fn from_query(pool: &sqlx::SqlitePool, query: &ScopedQuery) -> sqlx::Result<Self> {
+    let conditions = &query.query.0;
+    let mut subgraph = Self::default();
+    let mut node_ids = HashSet::new();
+    if conditions.is_empty() || conditions.keys().any(is_node_field) {
+      for node in query.nodes::<Value>(pool).await? {
+        node_ids.insert(node.id);
+        subgraph.nodes.push(node);
+      }
+      if !conditions.is_empty() {
+        subgraph.edges = edges_between(pool, query, &node_ids).await?;
+        subgraph.load_labels(pool).await?;
+        return Ok(subgraph);
+      }
+    }
+    for node_edge in query.node_edges::<Value, Value, Value>(pool).await? {
+      if node_ids.insert(node_edge.from_node.id) {
+        subgraph.nodes.push(node_edge.from_node);
+      }
+      if node_ids.insert(node_edge.to_node.id) {
+        subgraph.nodes.push(node_edge.to_node);
+      }
+      subgraph.edges.push(node_edge.edge);
+    }
+    subgraph.load_labels(pool).await?;
+    Ok(subgraph)
+  }
+
+  // Nodes matched by `query` plus everything reachable within `hops` edges in
+  // `direction`, with every edge between the collected nodes. The walk sees
+  // the same rows as `query`: its namespace, `with_deleted` and `as_of` apply
+  // to every node and edge on the way.
+  pub async fn neighborhood(
+    pool: &sqlx::SqlitePool,
+    query: &ScopedQuery,
+    hops: usize,
+    direction: Direction,
+  ) -> sqlx::Result<Self> {
+    let seeds = query.nodes::<Value>(pool).await?;
+    let mut node_ids: HashSet<i64> = seeds.iter().map(|node| node.id).collect();
+    let mut frontier: Vec<i64> = node_ids.iter().copied().collect();
+    for _ in 0..hops {
+      if frontier.is_empty() {
+        break;
+      }
+      let mut next = Vec::new();
+      for edge in incident_edges(pool, query, &frontier, direction).await? {
+        for node_id in [edge.from_node_id, edge.to_node_id] {
+          if node_ids.insert(node_id) {
+            next.push(node_id);
+          }
+        }
+      }
+      frontier = next;
+    }
+
+    let mut ids: Vec<i64> = node_ids.iter().copied().collect();
+    ids.sort_unstable();
+    let mut subgraph = Self::default();
+    for chunk in ids.chunks(ID_BATCH_SIZE) {
+      subgraph.nodes.extend(
+        scoped(query, id_condition(QueryField::NodeId, chunk))
+          .nodes::<Value>(pool)
+          .await?,
+      );
+    }
+    subgraph.edges = edges_between(pool, query, &node_ids).await?;
+    subgraph.load_labels(pool).await?;
+    Ok(subgraph)
+  }
+
+  // Inserts the nodes and edges into another database in one transaction.
+  // Rows get new ids there, the returned mapping is keyed by the old ids.
+  pub async fn copy_to(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<IdMapping> {
+    let mut mapping = IdMapping::default();
+    let mut tx = pool.begin().await?;
+    for node in &self.nodes {
+      // keys, namespaces and timestamps travel with the nodes, copying a node
+      // twice fails on the unique key
+      let row = repo::node::create_node_at(
+        &mut *tx,
+        &node.namespace,
+        node.key.as_deref(),
+        &node.uri,
+        &node.data.to_string(),
+        node.created_at.timestamp_millis(),
+        node.updated_at.timestamp_millis(),
+        node.deleted_at.map(|time| time.timestamp_millis()),
+      )
+      .await?;
+      for label in self.labels.get(&node.id).into_iter().flatten() {
+        repo::label::add_label(&mut *tx, row.id, label).await?;
+      }
+      mapping.nodes.insert(node.id, row.id);
+    }
+    for edge in &self.edges {
+      let (Some(&from_node_id), Some(&to_node_id)) = (
+        mapping.nodes.get(&edge.from_node_id),
+        mapping.nodes.get(&edge.to_node_id),
+      ) else {
+        continue;
+      };
+      let data = edge.data.as_ref().map(Value::to_string);
+      let row = repo::edge::create_edge_at(
+        &mut *tx,
+        &edge.namespace,
+        from_node_id,
+        to_node_id,
+        &edge.uri,
+        data.as_deref(),
+        edge.created_at.timestamp_millis(),
+        edge.updated_at.timestamp_millis(),
+        edge.deleted_at.map(|time| time.timestamp_millis()),
+        true,
+      )
+      .await?;
+      mapping.edges.insert(edge.id, row.id);
+    }
+    tx.commit().await?;
+    Ok(mapping)
+  }
+
+  pub fn from_node_edges<FN, TN, E>(node_edges: &[NodeEdge<FN, TN, E>]) -> serde_json::Result<Self>
+  where
+    FN: Serialize,
+    TN: Serialize,
+    E: Serialize,
+  {
+    let mut subgraph = Self::default();
+    let mut node_ids = HashSet::new();
+    for node_edge in node_edges {
+      if node_ids.insert(node_edge.from_node.id) {
+        subgraph.nodes.push(to_value_node(&node_edge.from_node)?);
+      }
+      if node_ids.insert(node_edge.to_node.id) {
+        subgraph.nodes.push(to_value_node(&node_edge.to_node)?);
+      }
+      subgraph.edges.push(to_value_edge(&node_edge.edge)?);
+    }
+    Ok(subgraph)
+  }
+
+  async fn load_labels(&mut self, pool: &sqlx::SqlitePool) -> sqlx::Result<()> {
+    let ids: Vec<i64> = self.nodes.iter().map(|node| node.id).collect();
+    for chunk in ids.chunks(ID_BATCH_SIZE) {
+      for (node_id, label) in repo::label::labels_of_nodes(pool, chunk).await? {
+        self.labels.entry(node_id).or_default().push(label);
+      }
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct IdMapping {
+  pub nodes: HashMap<i64, i64>,
+  pub edges: HashMap<i64, i64>,
+}
+
+pub(crate) async fn incident_edges(
+  pool: &sqlx::SqlitePool,
+  scope: &ScopedQuery,
+  node_ids: &[i64],
+  direction: Direction,
+) -> sqlx::Result<Vec<Edge<Value>>> {
+  let fields: &[QueryField] = match direction {
+    Direction::Outgoing => &[QueryField::EdgeFromNodeId],
+    Direction::Incoming => &[QueryField::EdgeToNodeId],
+    Direction::Both => &[QueryField::EdgeFromNodeId, QueryField::EdgeToNodeId],
+  };
+  let mut edge_ids = HashSet::new();
+  let mut edges = Vec::new();
+  for chunk in node_ids.chunks(ID_BATCH_SIZE) {
+    for field in fields {
+      let query = scoped(scope, id_condition(field.clone(), chunk));
+      for edge in query.edges::<Value>(pool).await? {
+        if edge_ids.insert(edge.id) {
+          edges.push(edge);
+        }
+      }
+    }
+  }
+  Ok(edges)
+}
+
+// edges in the scope of `scope` with both of their nodes in `node_ids`
+async fn edges_between(
+  pool: &sqlx::SqlitePool,
+  scope: &ScopedQuery,
+  node_ids: &HashSet<i64>,
+) -> sqlx::Result<Vec<Edge<Value>>> {
+  let mut ids: Vec<i64> = node_ids.iter().copied().collect();
+  ids.sort_unstable();
+  Ok(
+    incident_edges(pool, scope, &ids, Direction::Outgoing)
+      .await?
+      .into_iter()
+      .filter(|edge| node_ids.contains(&edge.to_node_id))
+      .collect(),
+  )
+}
+
+fn is_node_field(field: &QueryField) -> bool {
+  matches!(
+    field,
+    QueryField::NodeId
+      | QueryField::NodeKey
+      | QueryField::NodeURI
+      | QueryField::NodeData
+      | QueryField::NodeLabels
+      | QueryField::NodeDeletedAt
+      | QueryField::NodeUpdatedAt
+      | QueryField::NodeCreatedAt
+  )
+}
+
+// `conditions` with the namespace, `with_deleted` and `as_of` of `scope`
+fn scoped(scope: &ScopedQuery, conditions: HashMap<QueryField, QueryExpr>) -> ScopedQuery {
+  ScopedQuery {
+    query: Query::from(conditions),
+    with_deleted: scope.with_deleted,
+    as_of: scope.as_of,
+    limit: None,
+    offset: None,
+    namespace: scope.namespace.clone(),
+  }
+}
+
+fn id_condition(field: QueryField, ids: &[i64]) -> HashMap<QueryField, QueryExpr> {
+  let values = ids
+    .iter()
+    .map(|&id| QueryExpr::Value(QueryValue::Number(id.into())))
+    .collect();
+  HashMap::from([(field, QueryExpr::Op(QueryOp::In(values)))])
+}
+
+fn to_value_node<T>(node: &Node<T>) -> serde_json::Result<Node<Value>>
+where
+  T: Serialize,
+{
+  Ok(Node {
+    id: node.id,
+    namespace: node.namespace.clone(),
+    key: node.key.clone(),
+    uri: node.uri.clone(),
+    data: serde_json::to_value(&node.data)?,
+    deleted_at: node.deleted_at,
+    updated_at: node.updated_at,
+    created_at: node.created_at,
+  })
+}
+
+fn to_value_edge<E>(edge: &Edge<E>) -> serde_json::Result<Edge<Value>>
+where
+  E: Serialize,
+{
+  Ok(Edge {
+    id: edge.id,
+    namespace: edge.namespace.clone(),
+    from_node_id: edge.from_node_id,
+    to_node_id: edge.to_node_id,
+    uri: edge.uri.clone(),
+    data: match &edge.data {
+      Some(data) => Some(serde_json::to_value(data)?),
+      None => None,
+    },
+    deleted_at: edge.deleted_at,
+    updated_at: edge.updated_at,
+    created_at: edge.created_at,
+  })
+}
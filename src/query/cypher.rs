@@ -0,0 +1,477 @@
+use std::{fmt, str::FromStr};
+
+use super::{
+  pattern::{
+    CompareOp, Condition, Direction, EdgePattern, NodePattern, Operand, PathPattern, Pattern,
+    Property,
+  },
+  query::QueryValue,
+};
+
+#[derive(Debug)]
+pub struct ParseError {
+  pub message: String,
+  pub offset: usize,
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} at offset {}", self.message, self.offset)
+  }
+}
+
+impl FromStr for Pattern {
+  type Err = ParseError;
+
+  fn from_str(input: &str) -> Result<Self, Self::Err> {
+    parse(input)
+  }
+}
+
+pub fn parse(input: &str) -> Result<Pattern, ParseError> {
+  let tokens = tokenize(input)?;
+  let mut parser = Parser {
+    tokens,
+    position: 0,
+    end: input.len(),
+  };
+  let pattern = parser.query()?;
+  if let Some((_, offset)) = parser.tokens.get(parser.position) {
+    return Err(ParseError {
+      message: "Unexpected trailing input".to_owned(),
+      offset: *offset,
+    });
+  }
+  Ok(pattern)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  Ident(String),
+  String(String),
+  Number(serde_json::Number),
+  Symbol(&'static str),
+}
+
+static SYMBOLS: &[&str] = &[
+  "<>", "!=", "<=", ">=", "(", ")", "[", "]", "{", "}", ":", ",", ".", "-", "<", ">", "=", "*",
+];
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+  let mut tokens = Vec::new();
+  let mut chars = input.char_indices().peekable();
+  while let Some(&(offset, c)) = chars.peek() {
+    if c.is_whitespace() {
+      chars.next();
+    } else if c.is_ascii_alphabetic() || c == '_' {
+      let mut ident = String::new();
+      while let Some(&(_, c)) = chars.peek() {
+        if !(c.is_ascii_alphanumeric() || c == '_') {
+          break;
+        }
+        ident.push(c);
+        chars.next();
+      }
+      tokens.push((Token::Ident(ident), offset));
+    } else if c.is_ascii_digit() {
+      let mut number = String::new();
+      while let Some(&(_, c)) = chars.peek() {
+        if !(c.is_ascii_digit() || c == '.') {
+          break;
+        }
+        number.push(c);
+        chars.next();
+      }
+      match serde_json::from_str::<serde_json::Number>(&number) {
+        Ok(number) => tokens.push((Token::Number(number), offset)),
+        Err(_) => {
+          return Err(ParseError {
+            message: format!("Invalid number `{number}`"),
+            offset,
+          })
+        }
+      }
+    } else if c == '\'' || c == '"' || c == '`' {
+      chars.next();
+      let mut value = String::new();
+      let mut closed = false;
+      while let Some((_, next)) = chars.next() {
+        if next == '\\' {
+          if let Some((_, escaped)) = chars.next() {
+            value.push(escaped);
+          }
+        } else if next == c {
+          closed = true;
+          break;
+        } else {
+          value.push(next);
+        }
+      }
+      if !closed {
+        return Err(ParseError {
+          message: "Unterminated string".to_owned(),
+          offset,
+        });
+      }
+      // backticks quote identifiers, e.g. uris that are not plain words
+      if c == '`' {
+        tokens.push((Token::Ident(value), offset));
+      } else {
+        tokens.push((Token::String(value), offset));
+      }
+    } else {
+      let rest = &input[offset..];
+      match SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+        Some(symbol) => {
+          for _ in 0..symbol.len() {
+            chars.next();
+          }
+          tokens.push((Token::Symbol(symbol), offset));
+        }
+        None => {
+          return Err(ParseError {
+            message: format!("Unexpected character `{c}`"),
+            offset,
+          })
+        }
+      }
+    }
+  }
+  Ok(tokens)
+}
+
+struct Parser {
+  tokens: Vec<(Token, usize)>,
+  position: usize,
+  end: usize,
+}
+
+impl Parser {
+  fn query(&mut self) -> Result<Pattern, ParseError> {
+    self.expect_keyword("MATCH")?;
+    let mut pattern = Pattern::default();
+    pattern.paths.push(self.path()?);
+    while self.eat_symbol(",") {
+      pattern.paths.push(self.path()?);
+    }
+    if self.eat_keyword("WHERE") {
+      pattern.condition = Some(self.or()?);
+    }
+    self.expect_keyword("RETURN")?;
+    if !self.eat_symbol("*") {
+      pattern.returns.push(self.ident()?);
+      while self.eat_symbol(",") {
+        pattern.returns.push(self.ident()?);
+      }
+    }
+    if self.eat_keyword("LIMIT") {
+      let offset = self.offset();
+      match self.next() {
+        Some(Token::Number(n)) if n.as_u64().is_some() => pattern.limit = n.as_u64(),
+        _ => {
+          return Err(ParseError {
+            message: "Expected a non-negative integer limit".to_owned(),
+            offset,
+          })
+        }
+      }
+    }
+    Ok(pattern)
+  }
+
+  fn path(&mut self) -> Result<PathPattern, ParseError> {
+    let start = self.node()?;
+    let mut steps = Vec::new();
+    while self.peek_symbol("-") || self.peek_symbol("<") {
+      let edge = self.edge()?;
+      let node = self.node()?;
+      steps.push((edge, node));
+    }
+    Ok(PathPattern { start, steps })
+  }
+
+  fn node(&mut self) -> Result<NodePattern, ParseError> {
+    self.expect_symbol("(")?;
+    let (variable, uri, properties) = self.element(")")?;
+    self.expect_symbol(")")?;
+    Ok(NodePattern {
+      variable,
+      uri,
+      properties,
+    })
+  }
+
+  fn edge(&mut self) -> Result<EdgePattern, ParseError> {
+    let incoming = self.eat_symbol("<");
+    self.expect_symbol("-")?;
+    let (variable, uri, properties) = if self.eat_symbol("[") {
+      let element = self.element("]")?;
+      self.expect_symbol("]")?;
+      element
+    } else {
+      (None, None, Vec::new())
+    };
+    self.expect_symbol("-")?;
+    let outgoing = self.eat_symbol(">");
+    let direction = match (incoming, outgoing) {
+      (false, true) => Direction::Outgoing,
+      (true, false) => Direction::Incoming,
+      (false, false) => Direction::Both,
+      (true, true) => {
+        return Err(ParseError {
+          message: "Edge cannot point in both directions".to_owned(),
+          offset: self.offset(),
+        })
+      }
+    };
+    Ok(EdgePattern {
+      variable,
+      uri,
+      properties,
+      direction,
+    })
+  }
+
+  #[allow(clippy::type_complexity)]
+  fn element(
+    &mut self,
+    close: &str,
+  ) -> Result<(Option<String>, Option<String>, Vec<(String, QueryValue)>), ParseError> {
+    let variable = match self.peek() {
+      Some(Token::Ident(_)) => Some(self.ident()?),
+      _ => None,
+    };
+    let uri = if self.eat_symbol(":") {
+      Some(self.ident()?)
+    } else {
+      None
+    };
+    let mut properties = Vec::new();
+    if self.eat_symbol("{") {
+      if !self.peek_symbol("}") {
+        loop {
+          let mut path = self.ident()?;
+          while self.eat_symbol(".") {
+            path.push('.');
+            path.push_str(&self.ident()?);
+          }
+          self.expect_symbol(":")?;
+          properties.push((path, self.value()?));
+          if !self.eat_symbol(",") {
+            break;
+          }
+        }
+      }
+      self.expect_symbol("}")?;
+    }
+    if !self.peek_symbol(close) {
+      return Err(self.unexpected(&format!("`{close}`")));
+    }
+    Ok((variable, uri, properties))
+  }
+
+  fn or(&mut self) -> Result<Condition, ParseError> {
+    let mut condition = self.and()?;
+    while self.eat_keyword("OR") {
+      condition = Condition::Or(Box::new(condition), Box::new(self.and()?));
+    }
+    Ok(condition)
+  }
+
+  fn and(&mut self) -> Result<Condition, ParseError> {
+    let mut condition = self.not()?;
+    while self.eat_keyword("AND") {
+      condition = Condition::And(Box::new(condition), Box::new(self.not()?));
+    }
+    Ok(condition)
+  }
+
+  fn not(&mut self) -> Result<Condition, ParseError> {
+    if self.eat_keyword("NOT") {
+      return Ok(Condition::Not(Box::new(self.not()?)));
+    }
+    if self.eat_symbol("(") {
+      let condition = self.or()?;
+      self.expect_symbol(")")?;
+      return Ok(condition);
+    }
+    self.comparison()
+  }
+
+  fn comparison(&mut self) -> Result<Condition, ParseError> {
+    let left = self.operand()?;
+    if self.eat_keyword("IS") {
+      let negated = self.eat_keyword("NOT");
+      self.expect_keyword("NULL")?;
+      let condition = Condition::IsNull(left);
+      return Ok(if negated {
+        Condition::Not(Box::new(condition))
+      } else {
+        condition
+      });
+    }
+    if self.eat_keyword("IN") {
+      self.expect_symbol("[")?;
+      let mut list = Vec::new();
+      if !self.peek_symbol("]") {
+        list.push(self.operand()?);
+        while self.eat_symbol(",") {
+          list.push(self.operand()?);
+        }
+      }
+      self.expect_symbol("]")?;
+      return Ok(Condition::In(left, list));
+    }
+    let op = if self.eat_keyword("CONTAINS") {
+      CompareOp::Contains
+    } else if self.eat_keyword("STARTS") {
+      self.expect_keyword("WITH")?;
+      CompareOp::StartsWith
+    } else if self.eat_keyword("ENDS") {
+      self.expect_keyword("WITH")?;
+      CompareOp::EndsWith
+    } else {
+      let offset = self.offset();
+      match self.next() {
+        Some(Token::Symbol("=")) => CompareOp::Eq,
+        Some(Token::Symbol("<>")) | Some(Token::Symbol("!=")) => CompareOp::Neq,
+        Some(Token::Symbol("<")) => CompareOp::Lt,
+        Some(Token::Symbol("<=")) => CompareOp::Lte,
+        Some(Token::Symbol(">")) => CompareOp::Gt,
+        Some(Token::Symbol(">=")) => CompareOp::Gte,
+        _ => {
+          return Err(ParseError {
+            message: "Expected a comparison operator".to_owned(),
+            offset,
+          })
+        }
+      }
+    };
+    Ok(Condition::Compare(left, op, self.operand()?))
+  }
+
+  fn operand(&mut self) -> Result<Operand, ParseError> {
+    match self.peek() {
+      Some(Token::Ident(ident)) if !is_literal_keyword(ident) => {
+        let variable = self.ident()?;
+        let mut path = Vec::new();
+        while self.eat_symbol(".") {
+          path.push(self.ident()?);
+        }
+        Ok(Operand::Property(Property { variable, path }))
+      }
+      _ => Ok(Operand::Value(self.value()?)),
+    }
+  }
+
+  fn value(&mut self) -> Result<QueryValue, ParseError> {
+    let offset = self.offset();
+    let negative = self.eat_symbol("-");
+    match self.next() {
+      Some(Token::Number(n)) if negative => match format!("-{n}").parse() {
+        Ok(n) => Ok(QueryValue::Number(n)),
+        Err(_) => Err(ParseError {
+          message: "Invalid number".to_owned(),
+          offset,
+        }),
+      },
+      Some(Token::Number(n)) => Ok(QueryValue::Number(n)),
+      Some(Token::String(s)) if !negative => Ok(QueryValue::String(s)),
+      Some(Token::Ident(ident)) if !negative && ident.eq_ignore_ascii_case("TRUE") => {
+        Ok(QueryValue::Bool(true))
+      }
+      Some(Token::Ident(ident)) if !negative && ident.eq_ignore_ascii_case("FALSE") => {
+        Ok(QueryValue::Bool(false))
+      }
+      Some(Token::Ident(ident)) if !negative && ident.eq_ignore_ascii_case("NULL") => {
+        Ok(QueryValue::Null)
+      }
+      _ => Err(ParseError {
+        message: "Expected a value".to_owned(),
+        offset,
+      }),
+    }
+  }
+
+  fn ident(&mut self) -> Result<String, ParseError> {
+    match self.peek().cloned() {
+      Some(Token::Ident(ident)) => {
+        self.position += 1;
+        Ok(ident)
+      }
+      _ => Err(self.unexpected("an identifier")),
+    }
+  }
+
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.position).map(|(token, _)| token)
+  }
+
+  fn next(&mut self) -> Option<Token> {
+    let token = self.peek().cloned();
+    if token.is_some() {
+      self.position += 1;
+    }
+    token
+  }
+
+  fn offset(&self) -> usize {
+    self
+      .tokens
+      .get(self.position)
+      .map(|(_, offset)| *offset)
+      .unwrap_or(self.end)
+  }
+
+  fn peek_symbol(&self, symbol: &str) -> bool {
+    matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol)
+  }
+
+  fn eat_symbol(&mut self, symbol: &str) -> bool {
+    let matched = self.peek_symbol(symbol);
+    if matched {
+      self.position += 1;
+    }
+    matched
+  }
+
+  fn expect_symbol(&mut self, symbol: &str) -> Result<(), ParseError> {
+    if self.eat_symbol(symbol) {
+      Ok(())
+    } else {
+      Err(self.unexpected(&format!("`{symbol}`")))
+    }
+  }
+
+  fn eat_keyword(&mut self, keyword: &str) -> bool {
+    let matched =
+      matches!(self.peek(), Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case(keyword));
+    if matched {
+      self.position += 1;
+    }
+    matched
+  }
+
+  fn expect_keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+    if self.eat_keyword(keyword) {
+      Ok(())
+    } else {
+      Err(self.unexpected(&format!("`{keyword}`")))
+    }
+  }
+
+  fn unexpected(&self, expected: &str) -> ParseError {
+    ParseError {
+      message: format!("Expected {expected}"),
+      offset: self.offset(),
+    }
+  }
+}
+
+fn is_literal_keyword(ident: &str) -> bool {
+  ["TRUE", "FALSE", "NULL"]
+    .iter()
+    .any(|keyword| ident.eq_ignore_ascii_case(keyword))
+}
@@ -1,3 +1,16 @@
+pub mod cypher;
+pub mod pattern;
+#[allow(clippy::module_inception)]
 pub mod query;
+pub mod time;
 
-pub use query::Query;
+pub use cypher::ParseError;
+pub use pattern::{
+  CompareOp, Condition, Direction, EdgePattern, NodePattern, Operand, PathPattern, Pattern,
+  PatternError, Property, VariableFilter,
+};
+pub use query::{
+  DataUpdate, Query, QueryError, QueryExpr, QueryField, QueryOp, QueryTarget, QueryValue,
+  ScopedQuery,
+};
+pub use time::QueryDuration;
@@ -0,0 +1,645 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use sqlx::Row;
+
+use crate::{
+  model::{
+    bindings::{Binding, Bindings},
+    edge::Edge,
+    node::Node,
+  },
+  repo::{edge::EdgeRow, node::NodeRow},
+};
+
+pub use crate::model::neighbor::Direction;
+
+use super::query::{
+  contains_sql, edges_source, ends_with_sql, nodes_source, query_condition_builder_expr,
+  starts_with_sql, validate_expr, QueryError, QueryExpr, QueryValue,
+};
+
+static NODE_COLUMNS: &[&str] = &[
+  "id",
+  "namespace",
+  "key",
+  "uri",
+  "data",
+  "deleted_at",
+  "updated_at",
+  "created_at",
+];
+static EDGE_COLUMNS: &[&str] = &[
+  "id",
+  "namespace",
+  "from_node_id",
+  "to_node_id",
+  "uri",
+  "data",
+  "deleted_at",
+  "updated_at",
+  "created_at",
+];
+
+#[derive(Debug, Default, Clone)]
+pub struct Pattern {
+  pub paths: Vec<PathPattern>,
+  pub condition: Option<Condition>,
+  pub filters: Vec<VariableFilter>,
+  pub returns: Vec<String>,
+  pub limit: Option<u64>,
+  pub with_deleted: bool,
+  pub as_of: Option<DateTime<Utc>>,
+  pub namespace: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PathPattern {
+  pub start: NodePattern,
+  pub steps: Vec<(EdgePattern, NodePattern)>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct NodePattern {
+  pub variable: Option<String>,
+  pub uri: Option<String>,
+  pub properties: Vec<(String, QueryValue)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EdgePattern {
+  pub variable: Option<String>,
+  pub uri: Option<String>,
+  pub properties: Vec<(String, QueryValue)>,
+  pub direction: Direction,
+}
+
+#[derive(Debug, Clone)]
+pub struct VariableFilter {
+  pub variable: String,
+  pub column: String,
+  pub expr: QueryExpr,
+}
+
+#[derive(Debug, Clone)]
+pub enum Condition {
+  Compare(Operand, CompareOp, Operand),
+  In(Operand, Vec<Operand>),
+  IsNull(Operand),
+  And(Box<Condition>, Box<Condition>),
+  Or(Box<Condition>, Box<Condition>),
+  Not(Box<Condition>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+  Eq,
+  Neq,
+  Lt,
+  Lte,
+  Gt,
+  Gte,
+  Contains,
+  StartsWith,
+  EndsWith,
+}
+
+#[derive(Debug, Clone)]
+pub enum Operand {
+  Property(Property),
+  Value(QueryValue),
+}
+
+#[derive(Debug, Clone)]
+pub struct Property {
+  pub variable: String,
+  pub path: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum PatternError {
+  EmptyPattern,
+  UnknownVariable(String),
+  VariableKindMismatch(String),
+  DuplicateEdgeVariable(String),
+  UnknownColumn(String, String),
+  Query(QueryError),
+}
+
+impl std::error::Error for PatternError {}
+
+impl fmt::Display for PatternError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PatternError::EmptyPattern => write!(f, "Pattern has no paths"),
+      PatternError::UnknownVariable(name) => write!(f, "Unknown variable `{name}`"),
+      PatternError::VariableKindMismatch(name) => {
+        write!(f, "Variable `{name}` is bound to both a node and an edge")
+      }
+      PatternError::DuplicateEdgeVariable(name) => {
+        write!(f, "Edge variable `{name}` is bound more than once")
+      }
+      PatternError::UnknownColumn(name, column) => {
+        write!(f, "Variable `{name}` has no column `{column}`")
+      }
+      PatternError::Query(error) => error.fmt(f),
+    }
+  }
+}
+
+impl From<QueryError> for PatternError {
+  fn from(error: QueryError) -> Self {
+    PatternError::Query(error)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VariableKind {
+  Node,
+  Edge,
+}
+
+struct Variable {
+  name: String,
+  alias: String,
+  kind: VariableKind,
+  named: bool,
+}
+
+impl Pattern {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn node(mut self, variable: &str, uri: Option<&str>) -> Self {
+    self.paths.push(PathPattern {
+      start: NodePattern {
+        variable: Some(variable.to_owned()),
+        uri: uri.map(ToOwned::to_owned),
+        properties: Vec::new(),
+      },
+      steps: Vec::new(),
+    });
+    self
+  }
+
+  pub fn edge(mut self, variable: Option<&str>, from: &str, to: &str, uri: Option<&str>) -> Self {
+    let node = |variable: &str| NodePattern {
+      variable: Some(variable.to_owned()),
+      ..Default::default()
+    };
+    self.paths.push(PathPattern {
+      start: node(from),
+      steps: vec![(
+        EdgePattern {
+          variable: variable.map(ToOwned::to_owned),
+          uri: uri.map(ToOwned::to_owned),
+          properties: Vec::new(),
+          direction: Direction::Outgoing,
+        },
+        node(to),
+      )],
+    });
+    self
+  }
+
+  pub fn filter(mut self, variable: &str, column: &str, expr: QueryExpr) -> Self {
+    self.filters.push(VariableFilter {
+      variable: variable.to_owned(),
+      column: column.to_owned(),
+      expr,
+    });
+    self
+  }
+
+  pub fn returning(mut self, variables: &[&str]) -> Self {
+    self.returns = variables.iter().map(|name| (*name).to_owned()).collect();
+    self
+  }
+
+  pub fn limit(mut self, limit: u64) -> Self {
+    self.limit = Some(limit);
+    self
+  }
+
+  pub fn with_deleted(mut self, with_deleted: bool) -> Self {
+    self.with_deleted = with_deleted;
+    self
+  }
+
+  pub fn as_of(mut self, as_of: DateTime<Utc>) -> Self {
+    self.as_of = Some(as_of);
+    self
+  }
+
+  pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+    self.namespace = Some(namespace.into());
+    self
+  }
+
+  pub fn sql(&self) -> Result<String, PatternError> {
+    if self.paths.is_empty() {
+      return Err(PatternError::EmptyPattern);
+    }
+    let mut variables = Vec::<Variable>::new();
+    let mut conditions = Vec::<String>::new();
+
+    for path in &self.paths {
+      let mut prev = bind_node(&mut variables, &mut conditions, &path.start)?;
+      for (edge, node) in &path.steps {
+        let edge_alias = bind_edge(&mut variables, &mut conditions, edge)?;
+        let next = bind_node(&mut variables, &mut conditions, node)?;
+        let (from, to) = match edge.direction {
+          Direction::Outgoing | Direction::Both => (&prev, &next),
+          Direction::Incoming => (&next, &prev),
+        };
+        let mut join =
+          format!("{edge_alias}.from_node_id = {from}.id AND {edge_alias}.to_node_id = {to}.id");
+        if edge.direction == Direction::Both {
+          join = format!(
+            "(({join}) OR ({edge_alias}.from_node_id = {to}.id AND {edge_alias}.to_node_id = {from}.id))"
+          );
+        }
+        conditions.push(join);
+        prev = next;
+      }
+    }
+
+    // like Cypher, distinct edge patterns never match the same edge
+    let edge_aliases = variables
+      .iter()
+      .filter(|variable| variable.kind == VariableKind::Edge)
+      .map(|variable| variable.alias.as_str())
+      .collect::<Vec<_>>();
+    for (i, a) in edge_aliases.iter().enumerate() {
+      for b in &edge_aliases[(i + 1)..] {
+        conditions.push(format!("{a}.id != {b}.id"));
+      }
+    }
+
+    if !self.with_deleted {
+      for variable in &variables {
+        conditions.push(format!("{}.deleted_at IS NULL", variable.alias));
+      }
+    }
+    if let Some(namespace) = &self.namespace {
+      let namespace = QueryValue::String(namespace.clone()).into_sql();
+      for variable in &variables {
+        conditions.push(format!("{}.namespace = {namespace}", variable.alias));
+      }
+    }
+    for filter in &self.filters {
+      conditions.push(filter_sql(&variables, filter)?);
+    }
+    if let Some(condition) = &self.condition {
+      conditions.push(condition_sql(&variables, condition)?);
+    }
+
+    let returns = if self.returns.is_empty() {
+      variables
+        .iter()
+        .filter(|variable| variable.named)
+        .collect::<Vec<_>>()
+    } else {
+      let mut returns = Vec::with_capacity(self.returns.len());
+      for name in &self.returns {
+        returns.push(find_variable(&variables, name)?);
+      }
+      returns
+    };
+    let mut columns = Vec::new();
+    for variable in &returns {
+      let variable_columns = match variable.kind {
+        VariableKind::Node => NODE_COLUMNS,
+        VariableKind::Edge => EDGE_COLUMNS,
+      };
+      for column in variable_columns {
+        columns.push(format!(
+          "{}.{column} AS {}",
+          variable.alias,
+          quote_identifier(&format!("{}.{column}", variable.name))
+        ));
+      }
+    }
+    if columns.is_empty() {
+      columns.push("1".to_owned());
+    }
+
+    let nodes = nodes_source(self.as_of);
+    let edges = edges_source(self.as_of);
+    let sources = variables
+      .iter()
+      .map(|variable| match variable.kind {
+        VariableKind::Node => format!("{nodes} {}", variable.alias),
+        VariableKind::Edge => format!("{edges} {}", variable.alias),
+      })
+      .collect::<Vec<_>>();
+
+    let mut sql = format!("SELECT {} FROM {}", columns.join(", "), sources.join(", "));
+    if !conditions.is_empty() {
+      sql.push_str(" WHERE ");
+      sql.push_str(&conditions.join(" AND "));
+    }
+    if let Some(limit) = self.limit {
+      sql.push_str(&format!(" LIMIT {limit}"));
+    }
+    Ok(sql)
+  }
+
+  pub async fn rows(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<Bindings>> {
+    let sql = match self.sql() {
+      Ok(sql) => sql,
+      Err(e) => return Err(sqlx::Error::Encode(Box::new(e))),
+    };
+    let returns = self.return_kinds();
+    let rows = sqlx::query(&sql).fetch_all(pool).await?;
+    let mut results = Vec::with_capacity(rows.len());
+    for row in rows {
+      let mut bindings = Bindings::default();
+      for (name, kind) in &returns {
+        let binding = match kind {
+          VariableKind::Node => Binding::Node(decode_node(&row, name)?),
+          VariableKind::Edge => Binding::Edge(decode_edge(&row, name)?),
+        };
+        bindings.values.insert(name.clone(), binding);
+      }
+      results.push(bindings);
+    }
+    Ok(results)
+  }
+
+  pub async fn typed_rows<R>(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<R>>
+  where
+    R: DeserializeOwned,
+  {
+    let rows = self.rows(pool).await?;
+    let mut typed_rows = Vec::with_capacity(rows.len());
+    for row in rows {
+      match row.typed() {
+        Ok(typed_row) => typed_rows.push(typed_row),
+        Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
+      }
+    }
+    Ok(typed_rows)
+  }
+
+  pub fn returned_variables(&self) -> Vec<String> {
+    self
+      .return_kinds()
+      .into_iter()
+      .map(|(name, _)| name)
+      .collect()
+  }
+
+  fn return_kinds(&self) -> Vec<(String, VariableKind)> {
+    let mut kinds = Vec::<(String, VariableKind)>::new();
+    let mut push = |variable: &Option<String>, kind: VariableKind| {
+      if let Some(name) = variable {
+        if !kinds.iter().any(|(n, _)| n == name) {
+          kinds.push((name.clone(), kind));
+        }
+      }
+    };
+    for path in &self.paths {
+      push(&path.start.variable, VariableKind::Node);
+      for (edge, node) in &path.steps {
+        push(&edge.variable, VariableKind::Edge);
+        push(&node.variable, VariableKind::Node);
+      }
+    }
+    if self.returns.is_empty() {
+      kinds
+    } else {
+      self
+        .returns
+        .iter()
+        .filter_map(|name| kinds.iter().find(|(n, _)| n == name).cloned())
+        .collect()
+    }
+  }
+}
+
+fn bind_node(
+  variables: &mut Vec<Variable>,
+  conditions: &mut Vec<String>,
+  node: &NodePattern,
+) -> Result<String, PatternError> {
+  let alias = match &node.variable {
+    Some(name) => match variables.iter().find(|variable| &variable.name == name) {
+      Some(variable) if variable.kind == VariableKind::Node => variable.alias.clone(),
+      Some(_) => return Err(PatternError::VariableKindMismatch(name.clone())),
+      None => push_variable(variables, Some(name), VariableKind::Node),
+    },
+    None => push_variable(variables, None, VariableKind::Node),
+  };
+  push_pattern_conditions(conditions, &alias, &node.uri, &node.properties);
+  Ok(alias)
+}
+
+fn bind_edge(
+  variables: &mut Vec<Variable>,
+  conditions: &mut Vec<String>,
+  edge: &EdgePattern,
+) -> Result<String, PatternError> {
+  if let Some(name) = &edge.variable {
+    match variables.iter().find(|variable| &variable.name == name) {
+      Some(variable) if variable.kind == VariableKind::Edge => {
+        return Err(PatternError::DuplicateEdgeVariable(name.clone()))
+      }
+      Some(_) => return Err(PatternError::VariableKindMismatch(name.clone())),
+      None => {}
+    }
+  }
+  let alias = push_variable(variables, edge.variable.as_ref(), VariableKind::Edge);
+  push_pattern_conditions(conditions, &alias, &edge.uri, &edge.properties);
+  Ok(alias)
+}
+
+fn push_variable(
+  variables: &mut Vec<Variable>,
+  name: Option<&String>,
+  kind: VariableKind,
+) -> String {
+  let index = variables.len();
+  let (name, named) = match name {
+    Some(name) => (name.clone(), true),
+    None => (format!("#{index}"), false),
+  };
+  let alias = quote_identifier(&format!("v{index}"));
+  variables.push(Variable {
+    name,
+    alias: alias.clone(),
+    kind,
+    named,
+  });
+  alias
+}
+
+fn push_pattern_conditions(
+  conditions: &mut Vec<String>,
+  alias: &str,
+  uri: &Option<String>,
+  properties: &[(String, QueryValue)],
+) {
+  if let Some(uri) = uri {
+    conditions.push(format!("{alias}.uri = {}", quote_string(uri)));
+  }
+  for (path, value) in properties {
+    conditions.push(format!(
+      "json_extract({alias}.data, {}) = {}",
+      quote_string(&format!("$.{path}")),
+      value_sql(value)
+    ));
+  }
+}
+
+fn find_variable<'a>(variables: &'a [Variable], name: &str) -> Result<&'a Variable, PatternError> {
+  variables
+    .iter()
+    .find(|variable| variable.named && variable.name == name)
+    .ok_or_else(|| PatternError::UnknownVariable(name.to_owned()))
+}
+
+fn filter_sql(variables: &[Variable], filter: &VariableFilter) -> Result<String, PatternError> {
+  let variable = find_variable(variables, &filter.variable)?;
+  let columns = match variable.kind {
+    VariableKind::Node => NODE_COLUMNS,
+    VariableKind::Edge => EDGE_COLUMNS,
+  };
+  if !columns.contains(&filter.column.as_str()) {
+    return Err(PatternError::UnknownColumn(
+      filter.variable.clone(),
+      filter.column.clone(),
+    ));
+  }
+  let column = format!("{}.{}", variable.alias, filter.column);
+  validate_expr(&column, &filter.expr)?;
+  let mut qb = sqlx::QueryBuilder::<'_, sqlx::Sqlite>::new("(");
+  qb = query_condition_builder_expr(qb, &column, &filter.expr);
+  qb.push(")");
+  Ok(qb.into_sql())
+}
+
+fn condition_sql(variables: &[Variable], condition: &Condition) -> Result<String, PatternError> {
+  Ok(match condition {
+    Condition::Compare(left, op, right) => {
+      let left = operand_sql(variables, left)?;
+      let right = operand_sql(variables, right)?;
+      match op {
+        CompareOp::Eq => format!("{left} = {right}"),
+        CompareOp::Neq => format!("{left} != {right}"),
+        CompareOp::Lt => format!("{left} < {right}"),
+        CompareOp::Lte => format!("{left} <= {right}"),
+        CompareOp::Gt => format!("{left} > {right}"),
+        CompareOp::Gte => format!("{left} >= {right}"),
+        CompareOp::Contains => contains_sql(&left, &right),
+        CompareOp::StartsWith => starts_with_sql(&left, &right),
+        CompareOp::EndsWith => ends_with_sql(&left, &right),
+      }
+    }
+    Condition::In(operand, list) => {
+      let mut values = Vec::with_capacity(list.len());
+      for value in list {
+        values.push(operand_sql(variables, value)?);
+      }
+      format!(
+        "{} IN ({})",
+        operand_sql(variables, operand)?,
+        values.join(", ")
+      )
+    }
+    Condition::IsNull(operand) => format!("{} IS NULL", operand_sql(variables, operand)?),
+    Condition::And(left, right) => format!(
+      "({} AND {})",
+      condition_sql(variables, left)?,
+      condition_sql(variables, right)?
+    ),
+    Condition::Or(left, right) => format!(
+      "({} OR {})",
+      condition_sql(variables, left)?,
+      condition_sql(variables, right)?
+    ),
+    Condition::Not(condition) => format!("NOT ({})", condition_sql(variables, condition)?),
+  })
+}
+
+fn operand_sql(variables: &[Variable], operand: &Operand) -> Result<String, PatternError> {
+  match operand {
+    Operand::Value(value) => Ok(value_sql(value)),
+    Operand::Property(property) => {
+      let variable = find_variable(variables, &property.variable)?;
+      let columns = match variable.kind {
+        VariableKind::Node => NODE_COLUMNS,
+        VariableKind::Edge => EDGE_COLUMNS,
+      };
+      // top level columns win over data fields, so `a.uri` is the node uri
+      // rather than `a.data.uri`
+      Ok(match property.path.as_slice() {
+        [] => format!("{}.id", variable.alias),
+        [column] if columns.contains(&column.as_str()) => {
+          format!("{}.{column}", variable.alias)
+        }
+        [data, path @ ..] if data == "data" && !path.is_empty() => data_sql(&variable.alias, path),
+        path => data_sql(&variable.alias, path),
+      })
+    }
+  }
+}
+
+fn data_sql(alias: &str, path: &[String]) -> String {
+  format!(
+    "json_extract({alias}.data, {})",
+    quote_string(&format!("$.{}", path.join(".")))
+  )
+}
+
+fn value_sql(value: &QueryValue) -> String {
+  match value {
+    QueryValue::String(s) => quote_string(s),
+    value => value.into_sql(),
+  }
+}
+
+fn quote_string(value: &str) -> String {
+  format!("'{}'", value.replace('\'', "''"))
+}
+
+fn quote_identifier(value: &str) -> String {
+  format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+fn decode_node(row: &sqlx::sqlite::SqliteRow, name: &str) -> sqlx::Result<Node<serde_json::Value>> {
+  let column = |column: &str| format!("{name}.{column}");
+  let node_row = NodeRow {
+    id: row.try_get(column("id").as_str())?,
+    namespace: row.try_get(column("namespace").as_str())?,
+    key: row.try_get(column("key").as_str())?,
+    uri: row.try_get(column("uri").as_str())?,
+    data: row.try_get(column("data").as_str())?,
+    deleted_at: row.try_get(column("deleted_at").as_str())?,
+    updated_at: row.try_get(column("updated_at").as_str())?,
+    created_at: row.try_get(column("created_at").as_str())?,
+  };
+  node_row
+    .try_into()
+    .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
+
+fn decode_edge(row: &sqlx::sqlite::SqliteRow, name: &str) -> sqlx::Result<Edge<serde_json::Value>> {
+  let column = |column: &str| format!("{name}.{column}");
+  let edge_row = EdgeRow {
+    id: row.try_get(column("id").as_str())?,
+    namespace: row.try_get(column("namespace").as_str())?,
+    from_node_id: row.try_get(column("from_node_id").as_str())?,
+    to_node_id: row.try_get(column("to_node_id").as_str())?,
+    uri: row.try_get(column("uri").as_str())?,
+    data: row.try_get(column("data").as_str())?,
+    deleted_at: row.try_get(column("deleted_at").as_str())?,
+    updated_at: row.try_get(column("updated_at").as_str())?,
+    created_at: row.try_get(column("created_at").as_str())?,
+  };
+  edge_row
+    .try_into()
+    .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
@@ -1,68 +1,457 @@
 use std::{collections::HashMap, fmt};
 
+use chrono::{DateTime, Utc};
+use futures_util::{
+  future::{self, Either},
+  stream, Stream, StreamExt, TryStreamExt,
+};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
+  core::database::soft_delete_enabled,
   model::{edge::Edge, node::Node, node_edge::NodeEdge},
-  repo::{edge::EdgeRow, node::NodeRow, node_edge::NodeEdgeRow},
+  repo::{self, edge::EdgeRow, node::NodeRow, node_edge::NodeEdgeRow, NOW_MILLIS_SQL},
 };
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use super::time::{parse_time, QueryDuration};
+
+// rows fetched per round trip by the `*_stream` methods
+const STREAM_BATCH_SIZE: usize = 1000;
+
+const NODE_EDGE_COLUMNS: &str = r#"
+  from_node.id as from_node_id,
+  from_node.namespace as from_node_namespace,
+  from_node.key as from_node_key,
+  from_node.uri as from_node_uri,
+  from_node.data as from_node_data,
+  from_node.deleted_at as from_node_deleted_at,
+  from_node.created_at as from_node_created_at,
+  from_node.updated_at as from_node_updated_at,
+
+  to_node.id as to_node_id,
+  to_node.namespace as to_node_namespace,
+  to_node.key as to_node_key,
+  to_node.uri as to_node_uri,
+  to_node.data as to_node_data,
+  to_node.deleted_at as to_node_deleted_at,
+  to_node.created_at as to_node_created_at,
+  to_node.updated_at as to_node_updated_at,
+
+  edge.id as edge_id,
+  edge.namespace as edge_namespace,
+  edge.uri as edge_uri,
+  edge.data as edge_data,
+  edge.deleted_at as edge_deleted_at,
+  edge.created_at as edge_created_at,
+  edge.updated_at as edge_updated_at
+"#;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataUpdate<T> {
+  // merged into the existing data following RFC 7396 (SQLite `json_patch`)
+  Patch(T),
+  Replace(T),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryTarget {
+  Nodes,
+  Edges,
+  NodeEdges,
+}
+
+#[derive(Debug, Default, Serialize, Clone)]
 pub struct Query(pub HashMap<QueryField, QueryExpr>);
 
+// invalid time operands are rejected while deserializing, see `Query::validate`
+impl<'de> Deserialize<'de> for Query {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: serde::Deserializer<'de>,
+  {
+    let query = Self(HashMap::deserialize(deserializer)?);
+    query.validate().map_err(serde::de::Error::custom)?;
+    Ok(query)
+  }
+}
+
+impl From<HashMap<QueryField, QueryExpr>> for Query {
+  fn from(conditions: HashMap<QueryField, QueryExpr>) -> Self {
+    Self(conditions)
+  }
+}
+
 impl Query {
+  // Times written as strings must parse on timestamp columns, and only those
+  // and data take `within` and string `between`. Deserialized queries are
+  // checked already, queries built in code are checked before they run. The
+  // `sql` and `*_sql` methods render failing conditions as NULL, matching nothing.
+  pub fn validate(&self) -> Result<(), QueryError> {
+    for (field, expr) in &self.0 {
+      validate_expr(&field.to_string(), expr)?;
+    }
+    Ok(())
+  }
+
   pub fn sql(&self) -> String {
     query_condition_builder(sqlx::QueryBuilder::<'_, sqlx::Sqlite>::new(""), &self.0).into_sql()
   }
 
+  pub fn scoped(self) -> ScopedQuery {
+    ScopedQuery::from(self)
+  }
+
+  pub fn with_deleted(self, with_deleted: bool) -> ScopedQuery {
+    self.scoped().with_deleted(with_deleted)
+  }
+
+  pub fn as_of(self, as_of: DateTime<Utc>) -> ScopedQuery {
+    self.scoped().as_of(as_of)
+  }
+
+  pub fn limit(self, limit: u64) -> ScopedQuery {
+    self.scoped().limit(limit)
+  }
+
+  pub fn offset(self, offset: u64) -> ScopedQuery {
+    self.scoped().offset(offset)
+  }
+
+  pub fn namespace(self, namespace: impl Into<String>) -> ScopedQuery {
+    self.scoped().namespace(namespace)
+  }
+
   pub fn nodes_sql(&self) -> String {
-    query_condition_builder(
-      sqlx::QueryBuilder::<'_, sqlx::Sqlite>::new("SELECT node.* FROM nodes node"),
-      &self.0,
-    )
-    .into_sql()
+    self.clone().scoped().nodes_sql()
   }
 
   pub fn edges_sql(&self) -> String {
-    query_condition_builder(
-      sqlx::QueryBuilder::<'_, sqlx::Sqlite>::new("SELECT edge.* FROM nodes edge"),
-      &self.0,
-    )
-    .into_sql()
+    self.clone().scoped().edges_sql()
+  }
+
+  pub fn node_edges_sql(&self) -> String {
+    self.clone().scoped().node_edges_sql()
+  }
+
+  pub fn count_sql(&self, target: QueryTarget) -> String {
+    self.clone().scoped().count_sql(target)
+  }
+
+  pub fn exists_sql(&self, target: QueryTarget) -> String {
+    self.clone().scoped().exists_sql(target)
+  }
+
+  pub async fn node_rows(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<NodeRow>> {
+    self.clone().scoped().node_rows(pool).await
+  }
+
+  pub async fn nodes<N>(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<Node<N>>>
+  where
+    N: DeserializeOwned,
+  {
+    self.clone().scoped().nodes(pool).await
+  }
+
+  pub async fn edge_rows(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<EdgeRow>> {
+    self.clone().scoped().edge_rows(pool).await
+  }
+
+  pub async fn edges<E>(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<Edge<E>>>
+  where
+    E: DeserializeOwned,
+  {
+    self.clone().scoped().edges(pool).await
+  }
+
+  pub async fn node_edge_rows(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<NodeEdgeRow>> {
+    self.clone().scoped().node_edge_rows(pool).await
+  }
+
+  pub async fn node_edges<FN, TN, E>(
+    &self,
+    pool: &sqlx::SqlitePool,
+  ) -> sqlx::Result<Vec<NodeEdge<FN, TN, E>>>
+  where
+    FN: DeserializeOwned,
+    TN: DeserializeOwned,
+    E: DeserializeOwned,
+  {
+    self.clone().scoped().node_edges(pool).await
+  }
+
+  pub async fn delete_nodes<N>(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<Node<N>>>
+  where
+    N: DeserializeOwned,
+  {
+    self.clone().scoped().delete_nodes(pool).await
+  }
+
+  pub async fn delete_edges<E>(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<Edge<E>>>
+  where
+    E: DeserializeOwned,
+  {
+    self.clone().scoped().delete_edges(pool).await
+  }
+
+  pub async fn update_nodes<T, N>(
+    &self,
+    pool: &sqlx::SqlitePool,
+    update: DataUpdate<T>,
+  ) -> sqlx::Result<Vec<Node<N>>>
+  where
+    T: Serialize,
+    N: DeserializeOwned,
+  {
+    self.clone().scoped().update_nodes(pool, update).await
+  }
+
+  pub async fn update_edges<T, E>(
+    &self,
+    pool: &sqlx::SqlitePool,
+    update: DataUpdate<T>,
+  ) -> sqlx::Result<Vec<Edge<E>>>
+  where
+    T: Serialize,
+    E: DeserializeOwned,
+  {
+    self.clone().scoped().update_edges(pool, update).await
+  }
+
+  pub async fn count(&self, pool: &sqlx::SqlitePool, target: QueryTarget) -> sqlx::Result<i64> {
+    self.clone().scoped().count(pool, target).await
+  }
+
+  pub async fn exists(&self, pool: &sqlx::SqlitePool, target: QueryTarget) -> sqlx::Result<bool> {
+    self.clone().scoped().exists(pool, target).await
+  }
+
+  pub fn node_rows_stream<'a>(
+    &self,
+    pool: &'a sqlx::SqlitePool,
+  ) -> impl Stream<Item = sqlx::Result<NodeRow>> + Send + 'a {
+    self.clone().scoped().node_rows_stream(pool)
+  }
+
+  pub fn nodes_stream<'a, N>(
+    &self,
+    pool: &'a sqlx::SqlitePool,
+  ) -> impl Stream<Item = sqlx::Result<Node<N>>> + Send + 'a
+  where
+    N: DeserializeOwned + 'a,
+  {
+    self.clone().scoped().nodes_stream(pool)
+  }
+
+  pub fn edge_rows_stream<'a>(
+    &self,
+    pool: &'a sqlx::SqlitePool,
+  ) -> impl Stream<Item = sqlx::Result<EdgeRow>> + Send + 'a {
+    self.clone().scoped().edge_rows_stream(pool)
+  }
+
+  pub fn edges_stream<'a, E>(
+    &self,
+    pool: &'a sqlx::SqlitePool,
+  ) -> impl Stream<Item = sqlx::Result<Edge<E>>> + Send + 'a
+  where
+    E: DeserializeOwned + 'a,
+  {
+    self.clone().scoped().edges_stream(pool)
+  }
+
+  pub fn node_edge_rows_stream<'a>(
+    &self,
+    pool: &'a sqlx::SqlitePool,
+  ) -> impl Stream<Item = sqlx::Result<NodeEdgeRow>> + Send + 'a {
+    self.clone().scoped().node_edge_rows_stream(pool)
+  }
+
+  pub fn node_edges_stream<'a, FN, TN, E>(
+    &self,
+    pool: &'a sqlx::SqlitePool,
+  ) -> impl Stream<Item = sqlx::Result<NodeEdge<FN, TN, E>>> + Send + 'a
+  where
+    FN: DeserializeOwned + 'a,
+    TN: DeserializeOwned + 'a,
+    E: DeserializeOwned + 'a,
+  {
+    self.clone().scoped().node_edges_stream(pool)
+  }
+}
+
+// A query together with the options that scope it: soft deleted rows, a
+// point in time, a page and a namespace. The options sit next to the
+// conditions in JSON, e.g. {"node.uri": {"eq": "user"}, "limit": 10}.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ScopedQuery {
+  #[serde(flatten)]
+  pub query: Query,
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub with_deleted: bool,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub as_of: Option<DateTime<Utc>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub limit: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub offset: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub namespace: Option<String>,
+}
+
+impl From<Query> for ScopedQuery {
+  fn from(query: Query) -> Self {
+    Self {
+      query,
+      ..Default::default()
+    }
+  }
+}
+
+impl ScopedQuery {
+  pub fn with_deleted(mut self, with_deleted: bool) -> Self {
+    self.with_deleted = with_deleted;
+    self
+  }
+
+  pub fn as_of(mut self, as_of: DateTime<Utc>) -> Self {
+    self.as_of = Some(as_of);
+    self
+  }
+
+  pub fn limit(mut self, limit: u64) -> Self {
+    self.limit = Some(limit);
+    self
+  }
+
+  pub fn offset(mut self, offset: u64) -> Self {
+    self.offset = Some(offset);
+    self
+  }
+
+  pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+    self.namespace = Some(namespace.into());
+    self
+  }
+
+  pub fn validate(&self) -> Result<(), QueryError> {
+    self.query.validate()
+  }
+
+  // the query itself once it is valid, for the methods that run it
+  fn valid(&self) -> Result<&Self, QueryError> {
+    self.validate()?;
+    Ok(self)
+  }
+
+  pub fn nodes_sql(&self) -> String {
+    self.target_sql(QueryTarget::Nodes, "node.*")
+  }
+
+  pub fn edges_sql(&self) -> String {
+    self.target_sql(QueryTarget::Edges, "edge.*")
   }
 
   pub fn node_edges_sql(&self) -> String {
-    query_condition_builder(
-      sqlx::QueryBuilder::<'_, sqlx::Sqlite>::new(
-        r#"SELECT 
-        from_node.id as from_node_id, 
-        from_node.uri as from_node_uri,
-        from_node.data as from_node_data,
-        from_node.created_at as from_node_created_at,
-        from_node.updated_at as from_node_updated_at,
-
-        to_node.id as to_node_id, 
-        to_node.uri as to_node_uri,
-        to_node.data as to_node_data,
-        to_node.created_at as to_node_created_at,
-        to_node.updated_at as to_node_updated_at,
-
-        edge.id as edge_id, 
-        edge.uri as edge_uri,
-        edge.data as edge_data,
-        edge.created_at as edge_created_at,
-        edge.updated_at as edge_updated_at
-       FROM edges edge
-       JOIN nodes from_node ON from_node.id = edge.from_node_id
-       JOIN nodes to_node ON to_node.id = edge.to_node_id"#,
+    self.target_sql(QueryTarget::NodeEdges, NODE_EDGE_COLUMNS)
+  }
+
+  pub fn count_sql(&self, target: QueryTarget) -> String {
+    if self.limit.is_some() || self.offset.is_some() {
+      // COUNT(*) would be computed before the LIMIT applies
+      format!("SELECT COUNT(*) FROM ({})", self.target_sql(target, "1"))
+    } else {
+      self.target_sql(target, "COUNT(*)")
+    }
+  }
+
+  pub fn exists_sql(&self, target: QueryTarget) -> String {
+    format!("SELECT EXISTS ({})", self.target_sql(target, "1"))
+  }
+
+  fn target_sql(&self, target: QueryTarget, columns: &str) -> String {
+    match target {
+      QueryTarget::Nodes => self.select_sql(
+        &format!("SELECT {columns} FROM {} node", self.nodes_source()),
+        &["node"],
       ),
-      &self.0,
-    )
-    .into_sql()
+      QueryTarget::Edges => self.select_sql(
+        &format!("SELECT {columns} FROM {} edge", self.edges_source()),
+        &["edge"],
+      ),
+      QueryTarget::NodeEdges => self.select_sql(
+        &format!(
+          r#"SELECT {columns}
+          FROM {edges} edge
+          JOIN {nodes} from_node ON from_node.id = edge.from_node_id
+          JOIN {nodes} to_node ON to_node.id = edge.to_node_id"#,
+          edges = self.edges_source(),
+          nodes = self.nodes_source(),
+        ),
+        &["edge", "from_node", "to_node"],
+      ),
+    }
+  }
+
+  fn nodes_source(&self) -> String {
+    nodes_source(self.as_of)
+  }
+
+  fn edges_source(&self) -> String {
+    edges_source(self.as_of)
+  }
+
+  fn select_sql(&self, select: &str, aliases: &[&str]) -> String {
+    let mut qb = query_condition_builder(
+      sqlx::QueryBuilder::<'_, sqlx::Sqlite>::new(select),
+      &self.query.0,
+    );
+    let mut filters = Vec::new();
+    for alias in aliases {
+      if !self.with_deleted {
+        filters.push(format!("{alias}.deleted_at IS NULL"));
+      }
+      if let Some(namespace) = &self.namespace {
+        filters.push(format!(
+          "{alias}.namespace = {}",
+          QueryValue::String(namespace.clone()).into_sql()
+        ));
+      }
+    }
+    for (i, filter) in filters.iter().enumerate() {
+      if i == 0 && self.query.0.is_empty() {
+        qb.push(" WHERE ");
+      } else {
+        qb.push(" AND ");
+      }
+      qb.push(filter);
+    }
+    if self.limit.is_some() || self.offset.is_some() {
+      // pages are only stable if rows come back in a fixed order
+      qb.push(format!(" ORDER BY {}.id", aliases[0]));
+      // SQLite needs a LIMIT before an OFFSET, -1 means no limit. Both are
+      // 64-bit signed there, larger values cannot limit or skip any more rows.
+      qb.push(format!(
+        " LIMIT {}",
+        self
+          .limit
+          .map_or(-1, |limit| i64::try_from(limit).unwrap_or(i64::MAX))
+      ));
+      if let Some(offset) = self.offset {
+        qb.push(format!(
+          " OFFSET {}",
+          i64::try_from(offset).unwrap_or(i64::MAX)
+        ));
+      }
+    }
+    qb.into_sql()
   }
 
   pub async fn node_rows(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<NodeRow>> {
-    sqlx::query_as(&self.nodes_sql()).fetch_all(pool).await
+    sqlx::query_as(&self.valid()?.nodes_sql())
+      .fetch_all(pool)
+      .await
   }
 
   pub async fn nodes<N>(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<Node<N>>>
@@ -81,7 +470,9 @@ impl Query {
   }
 
   pub async fn edge_rows(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<EdgeRow>> {
-    sqlx::query_as(&self.edges_sql()).fetch_all(pool).await
+    sqlx::query_as(&self.valid()?.edges_sql())
+      .fetch_all(pool)
+      .await
   }
 
   pub async fn edges<E>(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<Edge<E>>>
@@ -100,7 +491,9 @@ impl Query {
   }
 
   pub async fn node_edge_rows(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<NodeEdgeRow>> {
-    sqlx::query_as(&self.node_edges_sql()).fetch_all(pool).await
+    sqlx::query_as(&self.valid()?.node_edges_sql())
+      .fetch_all(pool)
+      .await
   }
 
   pub async fn node_edges<FN, TN, E>(
@@ -122,19 +515,314 @@ impl Query {
     }
     Ok(node_edges)
   }
+
+  // soft deletes instead while `enable_soft_delete` is on
+  pub async fn delete_nodes<N>(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<Node<N>>>
+  where
+    N: DeserializeOwned,
+  {
+    if soft_delete_enabled(pool).await? {
+      let ids: Vec<i64> =
+        sqlx::query_scalar(&format!("SELECT id FROM ({});", self.valid()?.nodes_sql()))
+          .fetch_all(pool)
+          .await?;
+      return decode_rows(repo::node::soft_delete_nodes(pool, &ids).await?);
+    }
+    let rows: Vec<NodeRow> = sqlx::query_as(&format!(
+      "DELETE FROM nodes WHERE id IN (SELECT id FROM ({})) RETURNING *;",
+      self.valid()?.nodes_sql()
+    ))
+    .fetch_all(pool)
+    .await?;
+    decode_rows(rows)
+  }
+
+  // soft deletes instead while `enable_soft_delete` is on
+  pub async fn delete_edges<E>(&self, pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<Edge<E>>>
+  where
+    E: DeserializeOwned,
+  {
+    if soft_delete_enabled(pool).await? {
+      let ids: Vec<i64> =
+        sqlx::query_scalar(&format!("SELECT id FROM ({});", self.valid()?.edges_sql()))
+          .fetch_all(pool)
+          .await?;
+      return decode_rows(repo::edge::soft_delete_edges(pool, &ids).await?);
+    }
+    let rows: Vec<EdgeRow> = sqlx::query_as(&format!(
+      "DELETE FROM edges WHERE id IN (SELECT id FROM ({})) RETURNING *;",
+      self.valid()?.edges_sql()
+    ))
+    .fetch_all(pool)
+    .await?;
+    decode_rows(rows)
+  }
+
+  pub async fn update_nodes<T, N>(
+    &self,
+    pool: &sqlx::SqlitePool,
+    update: DataUpdate<T>,
+  ) -> sqlx::Result<Vec<Node<N>>>
+  where
+    T: Serialize,
+    N: DeserializeOwned,
+  {
+    let (data, data_json) = update_sql(&update, "data")?;
+    let rows: Vec<NodeRow> = sqlx::query_as(&format!(
+      "UPDATE nodes SET data = {data}, updated_at = {NOW_MILLIS_SQL} WHERE id IN (SELECT id FROM ({})) RETURNING *;",
+      self.valid()?.nodes_sql()
+    ))
+    .bind(data_json)
+    .fetch_all(pool)
+    .await?;
+    decode_rows(rows)
+  }
+
+  pub async fn update_edges<T, E>(
+    &self,
+    pool: &sqlx::SqlitePool,
+    update: DataUpdate<T>,
+  ) -> sqlx::Result<Vec<Edge<E>>>
+  where
+    T: Serialize,
+    E: DeserializeOwned,
+  {
+    // edge data is optional, a patch applies to an empty object
+    let (data, data_json) = update_sql(&update, "COALESCE(data, '{}')")?;
+    let rows: Vec<EdgeRow> = sqlx::query_as(&format!(
+      "UPDATE edges SET data = {data}, updated_at = {NOW_MILLIS_SQL} WHERE id IN (SELECT id FROM ({})) RETURNING *;",
+      self.valid()?.edges_sql()
+    ))
+    .bind(data_json)
+    .fetch_all(pool)
+    .await?;
+    decode_rows(rows)
+  }
+
+  pub async fn count(&self, pool: &sqlx::SqlitePool, target: QueryTarget) -> sqlx::Result<i64> {
+    sqlx::query_scalar(&self.valid()?.count_sql(target))
+      .fetch_one(pool)
+      .await
+  }
+
+  pub async fn exists(&self, pool: &sqlx::SqlitePool, target: QueryTarget) -> sqlx::Result<bool> {
+    sqlx::query_scalar(&self.valid()?.exists_sql(target))
+      .fetch_one(pool)
+      .await
+  }
+
+  pub fn node_rows_stream<'a>(
+    &self,
+    pool: &'a sqlx::SqlitePool,
+  ) -> impl Stream<Item = sqlx::Result<NodeRow>> + Send + 'a {
+    stream_rows(
+      pool,
+      self.valid().map(Self::nodes_sql),
+      "id",
+      |row: &NodeRow| row.id,
+    )
+  }
+
+  pub fn nodes_stream<'a, N>(
+    &self,
+    pool: &'a sqlx::SqlitePool,
+  ) -> impl Stream<Item = sqlx::Result<Node<N>>> + Send + 'a
+  where
+    N: DeserializeOwned + 'a,
+  {
+    self
+      .node_rows_stream(pool)
+      .map(|row| row.and_then(|row| row.try_into().map_err(|e| sqlx::Error::Decode(Box::new(e)))))
+  }
+
+  pub fn edge_rows_stream<'a>(
+    &self,
+    pool: &'a sqlx::SqlitePool,
+  ) -> impl Stream<Item = sqlx::Result<EdgeRow>> + Send + 'a {
+    stream_rows(
+      pool,
+      self.valid().map(Self::edges_sql),
+      "id",
+      |row: &EdgeRow| row.id,
+    )
+  }
+
+  pub fn edges_stream<'a, E>(
+    &self,
+    pool: &'a sqlx::SqlitePool,
+  ) -> impl Stream<Item = sqlx::Result<Edge<E>>> + Send + 'a
+  where
+    E: DeserializeOwned + 'a,
+  {
+    self
+      .edge_rows_stream(pool)
+      .map(|row| row.and_then(|row| row.try_into().map_err(|e| sqlx::Error::Decode(Box::new(e)))))
+  }
+
+  pub fn node_edge_rows_stream<'a>(
+    &self,
+    pool: &'a sqlx::SqlitePool,
+  ) -> impl Stream<Item = sqlx::Result<NodeEdgeRow>> + Send + 'a {
+    stream_rows(
+      pool,
+      self.valid().map(Self::node_edges_sql),
+      "edge_id",
+      |row: &NodeEdgeRow| row.edge_id,
+    )
+  }
+
+  pub fn node_edges_stream<'a, FN, TN, E>(
+    &self,
+    pool: &'a sqlx::SqlitePool,
+  ) -> impl Stream<Item = sqlx::Result<NodeEdge<FN, TN, E>>> + Send + 'a
+  where
+    FN: DeserializeOwned + 'a,
+    TN: DeserializeOwned + 'a,
+    E: DeserializeOwned + 'a,
+  {
+    self
+      .node_edge_rows_stream(pool)
+      .map(|row| row.and_then(|row| row.try_into().map_err(|e| sqlx::Error::Decode(Box::new(e)))))
+  }
+}
+
+fn update_sql<T>(update: &DataUpdate<T>, data: &str) -> sqlx::Result<(String, String)>
+where
+  T: Serialize,
+{
+  let (sql, value) = match update {
+    DataUpdate::Patch(value) => (format!("json_patch({data}, $1)"), value),
+    DataUpdate::Replace(value) => ("$1".to_owned(), value),
+  };
+  match serde_json::to_string(value) {
+    Ok(json) => Ok((sql, json)),
+    Err(e) => Err(sqlx::Error::Encode(Box::new(e))),
+  }
+}
+
+fn decode_rows<R, T>(rows: Vec<R>) -> sqlx::Result<Vec<T>>
+where
+  R: TryInto<T, Error = serde_json::Error>,
+{
+  let mut values = Vec::with_capacity(rows.len());
+  for row in rows {
+    match row.try_into() {
+      Ok(value) => values.push(value),
+      Err(e) => return Err(sqlx::Error::Decode(Box::new(e))),
+    }
+  }
+  Ok(values)
+}
+
+// Pages through the query by id (keyset pagination) so only one batch of rows
+// is held at a time and no connection stays checked out while the caller
+// processes rows. The query's own limit and offset still apply because it is
+// wrapped as a subquery. An invalid query yields its error.
+fn stream_rows<'a, R>(
+  pool: &'a sqlx::SqlitePool,
+  sql: Result<String, QueryError>,
+  id_column: &'static str,
+  id: fn(&R) -> i64,
+) -> impl Stream<Item = sqlx::Result<R>> + Send + 'a
+where
+  R: for<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin + 'a,
+{
+  let sql = match sql {
+    Ok(sql) => sql,
+    Err(e) => return Either::Right(stream::once(future::ready(Err(e.into())))),
+  };
+  let batch_sql = format!(
+    "SELECT * FROM ({sql}) WHERE {id_column} > ? ORDER BY {id_column} LIMIT {STREAM_BATCH_SIZE}"
+  );
+  let rows = stream::try_unfold(Some(i64::MIN), move |after| {
+    let batch_sql = batch_sql.clone();
+    async move {
+      let Some(after) = after else {
+        return Ok(None);
+      };
+      let rows: Vec<R> = sqlx::query_as(&batch_sql)
+        .bind(after)
+        .fetch_all(pool)
+        .await?;
+      let next = match rows.last() {
+        Some(last) if rows.len() == STREAM_BATCH_SIZE => Some(id(last)),
+        _ => None,
+      };
+      Ok::<_, sqlx::Error>(Some((stream::iter(rows.into_iter().map(Ok)), next)))
+    }
+  })
+  .try_flatten();
+  Either::Left(rows)
+}
+
+pub(crate) fn nodes_source(as_of: Option<DateTime<Utc>>) -> String {
+  let Some(as_of) = as_of else {
+    return "nodes".to_owned();
+  };
+  let t = as_of.timestamp_millis();
+  // history rows are the versions replaced by a write, so the version of a
+  // node at `t` is the first one recorded after `t`, or the current row if
+  // nothing was recorded since
+  format!(
+    r#"(SELECT id, namespace, key, uri, data, CASE WHEN deleted_at <= {t} THEN deleted_at END AS deleted_at, updated_at, created_at
+      FROM nodes WHERE updated_at <= {t}
+        AND NOT EXISTS (SELECT 1 FROM nodes_history later WHERE later.node_id = nodes.id AND later.recorded_at > {t})
+      UNION ALL
+      SELECT node_id AS id, namespace, key, uri, data, CASE WHEN deleted_at <= {t} THEN deleted_at END AS deleted_at, updated_at, created_at
+      FROM nodes_history version WHERE updated_at <= {t} AND recorded_at > {t}
+        AND NOT EXISTS (
+          SELECT 1 FROM nodes_history earlier
+          WHERE earlier.node_id = version.node_id AND earlier.recorded_at > {t}
+            AND (earlier.recorded_at < version.recorded_at OR (earlier.recorded_at = version.recorded_at AND earlier.id < version.id))
+        ))"#
+  )
+}
+
+pub(crate) fn edges_source(as_of: Option<DateTime<Utc>>) -> String {
+  let Some(as_of) = as_of else {
+    return "edges".to_owned();
+  };
+  let t = as_of.timestamp_millis();
+  format!(
+    r#"(SELECT id, namespace, from_node_id, to_node_id, uri, data, CASE WHEN deleted_at <= {t} THEN deleted_at END AS deleted_at, updated_at, created_at
+      FROM edges WHERE updated_at <= {t}
+        AND NOT EXISTS (SELECT 1 FROM edges_history later WHERE later.edge_id = edges.id AND later.recorded_at > {t})
+      UNION ALL
+      SELECT edge_id AS id, namespace, from_node_id, to_node_id, uri, data, CASE WHEN deleted_at <= {t} THEN deleted_at END AS deleted_at, updated_at, created_at
+      FROM edges_history version WHERE updated_at <= {t} AND recorded_at > {t}
+        AND NOT EXISTS (
+          SELECT 1 FROM edges_history earlier
+          WHERE earlier.edge_id = version.edge_id AND earlier.recorded_at > {t}
+            AND (earlier.recorded_at < version.recorded_at OR (earlier.recorded_at = version.recorded_at AND earlier.id < version.id))
+        ))"#
+  )
 }
 
 #[derive(Debug)]
 pub enum QueryError {
   InvalidQueryType,
+  InvalidDuration(String),
+  InvalidTime(String),
+  NotATimeField(String),
 }
 
 impl std::error::Error for QueryError {}
 
+// a query that cannot be turned into SQL fails like a value that cannot be
+// bound
+impl From<QueryError> for sqlx::Error {
+  fn from(error: QueryError) -> Self {
+    sqlx::Error::Encode(Box::new(error))
+  }
+}
+
 impl fmt::Display for QueryError {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
       QueryError::InvalidQueryType => write!(f, "Invalid query type"),
+      QueryError::InvalidDuration(value) => write!(f, "Invalid duration {value:?}"),
+      QueryError::InvalidTime(value) => write!(f, "Invalid time {value:?}"),
+      QueryError::NotATimeField(field) => write!(f, "{field} does not hold times"),
     }
   }
 }
@@ -144,10 +832,16 @@ pub enum QueryField {
   // Node
   #[serde(rename = "node.id")]
   NodeId,
+  #[serde(rename = "node.key")]
+  NodeKey,
   #[serde(rename = "node.uri")]
   NodeURI,
   #[serde(rename = "node.data")]
   NodeData,
+  #[serde(rename = "node.labels")]
+  NodeLabels,
+  #[serde(rename = "node.deleted_at")]
+  NodeDeletedAt,
   #[serde(rename = "node.updated_at")]
   NodeUpdatedAt,
   #[serde(rename = "node.created_at")]
@@ -163,6 +857,8 @@ pub enum QueryField {
   EdgeURI,
   #[serde(rename = "edge.data")]
   EdgeData,
+  #[serde(rename = "edge.deleted_at")]
+  EdgeDeletedAt,
   #[serde(rename = "edge.updated_at")]
   EdgeUpdatedAt,
   #[serde(rename = "edge.created_at")]
@@ -170,10 +866,16 @@ pub enum QueryField {
   // FromNode
   #[serde(rename = "from_node.id")]
   NodeEdgeFromNodeId,
+  #[serde(rename = "from_node.key")]
+  NodeEdgeFromNodeKey,
   #[serde(rename = "from_node.uri")]
   NodeEdgeFromNodeURI,
   #[serde(rename = "from_node.data")]
   NodeEdgeFromNodeData,
+  #[serde(rename = "from_node.labels")]
+  NodeEdgeFromNodeLabels,
+  #[serde(rename = "from_node.deleted_at")]
+  NodeEdgeFromNodeDeletedAt,
   #[serde(rename = "from_node.updated_at")]
   NodeEdgeFromNodeUpdatedAt,
   #[serde(rename = "from_node.created_at")]
@@ -181,10 +883,16 @@ pub enum QueryField {
   // ToNode
   #[serde(rename = "to_node.id")]
   NodeEdgeToNodeId,
+  #[serde(rename = "to_node.key")]
+  NodeEdgeToNodeKey,
   #[serde(rename = "to_node.uri")]
   NodeEdgeToNodeURI,
   #[serde(rename = "to_node.data")]
   NodeEdgeToNodeData,
+  #[serde(rename = "to_node.labels")]
+  NodeEdgeToNodeLabels,
+  #[serde(rename = "to_node.deleted_at")]
+  NodeEdgeToNodeDeletedAt,
   #[serde(rename = "to_node.updated_at")]
   NodeEdgeToNodeUpdatedAt,
   #[serde(rename = "to_node.created_at")]
@@ -224,7 +932,7 @@ impl QueryValue {
     match self {
       QueryValue::Null => "NULL".to_string(),
       QueryValue::Bool(b) => b.to_string(),
-      QueryValue::String(s) => format!("'{}'", s),
+      QueryValue::String(s) => format!("'{}'", s.replace('\'', "''")),
       QueryValue::Number(n) => n.to_string(),
     }
   }
@@ -240,7 +948,21 @@ pub enum QueryOp {
   Gte(Box<QueryExpr>),
   Lte(Box<QueryExpr>),
   Like(Box<QueryExpr>),
+  #[serde(rename = "ilike")]
+  ILike(Box<QueryExpr>),
+  StartsWith(Box<QueryExpr>),
+  EndsWith(Box<QueryExpr>),
+  // SQLite has no REGEXP function of its own, `core::database::create`
+  // registers one. Pools opened another way need `.with_regexp()` on their
+  // connect options.
+  Regex(Box<QueryExpr>),
+  Contains(Box<QueryExpr>),
+  // `false` matches values that are not NULL
+  IsNull(bool),
+  Exists(bool),
   In(Vec<QueryExpr>),
+  Between(Box<QueryExpr>, Box<QueryExpr>),
+  Within(QueryDuration),
   And(Vec<QueryExpr>),
   Or(Vec<QueryExpr>),
   Not(Box<QueryExpr>),
@@ -250,7 +972,7 @@ pub fn query_condition_builder<'args, DB: sqlx::Database>(
   mut qb: sqlx::QueryBuilder<'args, DB>,
   query: &HashMap<QueryField, QueryExpr>,
 ) -> sqlx::QueryBuilder<'args, DB> {
-  for (i, (field, expr)) in query.into_iter().enumerate() {
+  for (i, (field, expr)) in query.iter().enumerate() {
     if i == 0 {
       qb.push(" WHERE ");
     }
@@ -262,7 +984,7 @@ pub fn query_condition_builder<'args, DB: sqlx::Database>(
   qb
 }
 
-fn query_condition_builder_expr<'args, DB: sqlx::Database>(
+pub(crate) fn query_condition_builder_expr<'args, DB: sqlx::Database>(
   mut qb: sqlx::QueryBuilder<'args, DB>,
   field: &str,
   expr: &QueryExpr,
@@ -275,57 +997,122 @@ fn query_condition_builder_expr<'args, DB: sqlx::Database>(
       qb.push(sub_field.to_string());
     }
     QueryExpr::Data(data) => {
-      for (i, (data_field, sub_expr)) in data.into_iter().enumerate() {
+      for (i, (data_field, sub_expr)) in data.iter().enumerate() {
         if i > 0 {
           qb.push(" AND ");
         }
-        qb = query_condition_builder_expr(
-          qb,
-          &format!("json_extract({field},'$.{data_field}')"),
-          sub_expr,
-        );
+        qb = query_condition_builder_expr(qb, &data_path(field, data_field), sub_expr);
       }
     }
     QueryExpr::Op(op) => match op {
+      QueryOp::And(sub_exprs) => {
+        qb.push(" (");
+        for (i, sub_expr) in sub_exprs.iter().enumerate() {
+          if i > 0 {
+            qb.push(" AND ");
+          }
+          qb = query_condition_builder_expr(qb, field, sub_expr);
+        }
+        qb.push(")");
+      }
+      QueryOp::Or(sub_exprs) => {
+        qb.push(" (");
+        for (i, sub_expr) in sub_exprs.iter().enumerate() {
+          if i > 0 {
+            qb.push(" OR ");
+          }
+          qb = query_condition_builder_expr(qb, field, sub_expr);
+        }
+        qb.push(")");
+      }
+      QueryOp::Not(sub_expr) => {
+        qb.push(" NOT ");
+        qb = query_condition_builder_expr(qb, field, sub_expr);
+      }
+      // labels live in their own table, every label predicate is a lookup there
+      op if field.ends_with(".labels") => {
+        let alias = &field[..field.len() - ".labels".len()];
+        let op = match op {
+          QueryOp::Contains(sub_expr) => QueryOp::Eq(sub_expr.clone()),
+          op => op.clone(),
+        };
+        qb.push(format!(
+          "EXISTS (SELECT 1 FROM node_labels WHERE node_labels.node_id = {alias}.id AND "
+        ));
+        qb = query_condition_builder_expr(qb, "node_labels.label", &QueryExpr::Op(op));
+        qb.push(")");
+      }
+      // `= NULL` never matches in SQL
+      QueryOp::Eq(sub_expr) if matches!(**sub_expr, QueryExpr::Value(QueryValue::Null)) => {
+        qb.push(format!("{field} IS NULL"));
+      }
+      QueryOp::Neq(sub_expr) if matches!(**sub_expr, QueryExpr::Value(QueryValue::Null)) => {
+        qb.push(format!("{field} IS NOT NULL"));
+      }
       QueryOp::Eq(sub_expr) => {
-        qb.push(field);
-        qb.push(" = ");
-        qb = query_condition_builder_expr(qb, field, &*sub_expr);
+        qb = query_comparison_builder(qb, field, " = ", sub_expr, false);
       }
       QueryOp::Neq(sub_expr) => {
-        qb.push(field);
-        qb.push(" != ");
-        qb = query_condition_builder_expr(qb, field, &*sub_expr);
+        qb = query_comparison_builder(qb, field, " != ", sub_expr, false);
       }
       QueryOp::Gt(sub_expr) => {
-        qb.push(field);
-        qb.push(" > ");
-        qb = query_condition_builder_expr(qb, field, &*sub_expr);
+        qb = query_comparison_builder(qb, field, " > ", sub_expr, true);
       }
       QueryOp::Lt(sub_expr) => {
-        qb.push(field);
-        qb.push(" < ");
-        qb = query_condition_builder_expr(qb, field, &*sub_expr);
+        qb = query_comparison_builder(qb, field, " < ", sub_expr, true);
       }
       QueryOp::Gte(sub_expr) => {
-        qb.push(field);
-        qb.push(" >= ");
-        qb = query_condition_builder_expr(qb, field, &*sub_expr);
+        qb = query_comparison_builder(qb, field, " >= ", sub_expr, true);
       }
       QueryOp::Lte(sub_expr) => {
-        qb.push(field);
-        qb.push(" <= ");
-        qb = query_condition_builder_expr(qb, field, &*sub_expr);
+        qb = query_comparison_builder(qb, field, " <= ", sub_expr, true);
       }
       QueryOp::Like(sub_expr) => {
         qb.push(field);
         qb.push(" LIKE ");
-        qb = query_condition_builder_expr(qb, field, &*sub_expr);
+        qb = query_condition_builder_expr(qb, field, sub_expr);
+      }
+      QueryOp::ILike(sub_expr) => {
+        qb.push(format!("lower({field}) LIKE lower("));
+        qb = query_condition_builder_expr(qb, field, sub_expr);
+        qb.push(")");
+      }
+      QueryOp::StartsWith(sub_expr) => {
+        qb.push(starts_with_sql(field, &expr_sql::<DB>(field, sub_expr)));
+      }
+      QueryOp::EndsWith(sub_expr) => {
+        qb.push(ends_with_sql(field, &expr_sql::<DB>(field, sub_expr)));
+      }
+      QueryOp::Regex(sub_expr) => {
+        qb.push(field);
+        qb.push(" REGEXP ");
+        qb = query_condition_builder_expr(qb, field, sub_expr);
+      }
+      QueryOp::Contains(sub_expr) => {
+        qb.push(contains_sql(field, &expr_sql::<DB>(field, sub_expr)));
+      }
+      QueryOp::IsNull(is_null) => {
+        qb.push(format!(
+          "{field} IS {}NULL",
+          if *is_null { "" } else { "NOT " }
+        ));
+      }
+      // a data path holding JSON null exists, json_type only returns NULL for
+      // missing paths
+      QueryOp::Exists(exists) => {
+        let field = match json_arguments(field) {
+          Some(arguments) => format!("json_type({arguments})"),
+          None => field.to_owned(),
+        };
+        qb.push(format!(
+          "{field} IS {}NULL",
+          if *exists { "NOT " } else { "" }
+        ));
       }
       QueryOp::In(sub_exprs) => {
         qb.push(field);
         qb.push(" IN (");
-        for (i, sub_expr) in sub_exprs.into_iter().enumerate() {
+        for (i, sub_expr) in sub_exprs.iter().enumerate() {
           if i > 0 {
             qb.push(", ");
           }
@@ -333,31 +1120,190 @@ fn query_condition_builder_expr<'args, DB: sqlx::Database>(
         }
         qb.push(")");
       }
-      QueryOp::And(sub_exprs) => {
-        qb.push(" (");
-        for (i, sub_expr) in sub_exprs.into_iter().enumerate() {
-          if i > 0 {
-            qb.push(" AND ");
-          }
-          qb = query_condition_builder_expr(qb, field, sub_expr);
+      QueryOp::Between(low, high) => match time_range(field, low, high) {
+        Ok(Some((time_field, low, high))) => {
+          qb.push(format!("{time_field} BETWEEN {low} AND {high}"));
         }
-        qb.push(")");
-      }
-      QueryOp::Or(sub_exprs) => {
-        qb.push(" (");
-        for (i, sub_expr) in sub_exprs.into_iter().enumerate() {
-          if i > 0 {
-            qb.push(" OR ");
-          }
-          qb = query_condition_builder_expr(qb, field, sub_expr);
+        Ok(None) => {
+          qb.push(field);
+          qb.push(" BETWEEN ");
+          qb = query_condition_builder_expr(qb, field, low);
+          qb.push(" AND ");
+          qb = query_condition_builder_expr(qb, field, high);
         }
-        qb.push(")");
-      }
-      QueryOp::Not(sub_expr) => {
-        qb.push(" NOT ");
-        qb = query_condition_builder_expr(qb, field, &*sub_expr);
-      }
+        Err(_) => {
+          qb.push("NULL");
+        }
+      },
+      QueryOp::Within(duration) => match time_field(field) {
+        Some(time_field) => {
+          let now = Utc::now().timestamp_millis();
+          qb.push(format!(
+            "{time_field} BETWEEN {} AND {now}",
+            now.saturating_sub(duration.millis())
+          ));
+        }
+        None => {
+          qb.push("NULL");
+        }
+      },
     },
   }
   qb
 }
+
+fn query_comparison_builder<'args, DB: sqlx::Database>(
+  mut qb: sqlx::QueryBuilder<'args, DB>,
+  field: &str,
+  operator: &str,
+  expr: &QueryExpr,
+  range: bool,
+) -> sqlx::QueryBuilder<'args, DB> {
+  match time_operand(field, expr, range) {
+    Ok(Some((time_field, millis))) => {
+      qb.push(format!("{time_field}{operator}{millis}"));
+    }
+    Ok(None) => {
+      qb.push(field);
+      qb.push(operator);
+      qb = query_condition_builder_expr(qb, field, expr);
+    }
+    Err(_) => {
+      qb.push("NULL");
+    }
+  }
+  qb
+}
+
+fn expr_sql<DB: sqlx::Database>(field: &str, expr: &QueryExpr) -> String {
+  query_condition_builder_expr(sqlx::QueryBuilder::<DB>::new(""), field, expr).into_sql()
+}
+
+// The string operators are shared with Cypher `CONTAINS`, `STARTS WITH` and
+// `ENDS WITH` so both languages match the same rows, `value` is SQL. Arrays in
+// data are searched for an element, anything else for a substring.
+pub(crate) fn contains_sql(field: &str, value: &str) -> String {
+  match json_arguments(field) {
+    Some(arguments) => format!(
+      "(CASE json_type({arguments}) WHEN 'array' THEN EXISTS (SELECT 1 FROM json_each({arguments}) WHERE value = {value}) ELSE instr({field}, {value}) > 0 END)"
+    ),
+    None => format!("instr({field}, {value}) > 0"),
+  }
+}
+
+pub(crate) fn starts_with_sql(field: &str, value: &str) -> String {
+  format!("substr({field}, 1, length({value})) = {value}")
+}
+
+// a negative start counts from the end, an empty suffix always matches
+pub(crate) fn ends_with_sql(field: &str, value: &str) -> String {
+  format!("(length({value}) = 0 OR substr({field}, -length({value})) = {value})")
+}
+
+// the checks `query_condition_builder_expr` relies on, see `Query::validate`
+pub(crate) fn validate_expr(field: &str, expr: &QueryExpr) -> Result<(), QueryError> {
+  match expr {
+    QueryExpr::Value(_) | QueryExpr::Field(_) => Ok(()),
+    QueryExpr::Data(data) => data.iter().try_for_each(|(data_field, sub_expr)| {
+      validate_expr(&data_path(field, data_field), sub_expr)
+    }),
+    QueryExpr::Op(op) => match op {
+      QueryOp::And(sub_exprs) | QueryOp::Or(sub_exprs) => sub_exprs
+        .iter()
+        .try_for_each(|sub_expr| validate_expr(field, sub_expr)),
+      QueryOp::Not(sub_expr) => validate_expr(field, sub_expr),
+      _ if field.ends_with(".labels") => Ok(()),
+      QueryOp::Eq(sub_expr) | QueryOp::Neq(sub_expr) => {
+        time_operand(field, sub_expr, false).map(drop)
+      }
+      QueryOp::Gt(sub_expr)
+      | QueryOp::Lt(sub_expr)
+      | QueryOp::Gte(sub_expr)
+      | QueryOp::Lte(sub_expr) => time_operand(field, sub_expr, true).map(drop),
+      QueryOp::Between(low, high) => time_range(field, low, high).map(drop),
+      QueryOp::Within(_) => match time_field(field) {
+        Some(_) => Ok(()),
+        None => Err(QueryError::NotATimeField(field.to_owned())),
+      },
+      _ => Ok(()),
+    },
+  }
+}
+
+fn data_path(field: &str, data_field: &str) -> String {
+  format!(
+    "json_extract({field},'$.{}')",
+    data_field.replace('\'', "''")
+  )
+}
+
+// Both bounds of a `between` on a time field as milliseconds. Strings only
+// make sense as times, other fields compare their bounds as they are.
+fn time_range(
+  field: &str,
+  low: &QueryExpr,
+  high: &QueryExpr,
+) -> Result<Option<(String, i64, i64)>, QueryError> {
+  if time_field(field).is_none() && (is_string(low) || is_string(high)) {
+    return Err(QueryError::NotATimeField(field.to_owned()));
+  }
+  match (
+    time_operand(field, low, true)?,
+    time_operand(field, high, true)?,
+  ) {
+    (Some((time_field, low)), Some((_, high))) => Ok(Some((time_field, low, high))),
+    _ => Ok(None),
+  }
+}
+
+// Timestamp columns hold milliseconds, so times written as strings are
+// converted before comparing and a string that is no time is an error. Dates
+// inside data are converted on the column side instead, but only for range
+// comparisons and only if the string parses, so comparisons on plain strings
+// keep working.
+fn time_operand(
+  field: &str,
+  expr: &QueryExpr,
+  range: bool,
+) -> Result<Option<(String, i64)>, QueryError> {
+  let QueryExpr::Value(QueryValue::String(value)) = expr else {
+    return Ok(None);
+  };
+  if is_data_path(field) && !range {
+    return Ok(None);
+  }
+  let Some(time_field) = time_field(field) else {
+    return Ok(None);
+  };
+  match parse_time(value, Utc::now()) {
+    Some(millis) => Ok(Some((time_field, millis))),
+    None if is_data_path(field) => Ok(None),
+    None => Err(QueryError::InvalidTime(value.clone())),
+  }
+}
+
+fn time_field(field: &str) -> Option<String> {
+  if is_data_path(field) {
+    Some(format!("unixepoch({field}, 'subsec') * 1000"))
+  } else if [".created_at", ".updated_at", ".deleted_at"]
+    .iter()
+    .any(|column| field.ends_with(column))
+  {
+    Some(field.to_owned())
+  } else {
+    None
+  }
+}
+
+fn is_string(expr: &QueryExpr) -> bool {
+  matches!(expr, QueryExpr::Value(QueryValue::String(_)))
+}
+
+fn is_data_path(field: &str) -> bool {
+  field.starts_with("json_extract(")
+}
+
+// the document and path of a data path, e.g. `node.data,'$.tags'`
+fn json_arguments(field: &str) -> Option<&str> {
+  field.strip_prefix("json_extract(")?.strip_suffix(')')
+}
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::query::QueryError;
+
+// a span of time written as number and unit pairs, e.g. "7d" or "1h30m"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct QueryDuration(pub i64);
+
+impl QueryDuration {
+  pub fn millis(&self) -> i64 {
+    self.0
+  }
+}
+
+impl TryFrom<String> for QueryDuration {
+  type Error = QueryError;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    parse_duration(&value)
+      .map(QueryDuration)
+      .ok_or(QueryError::InvalidDuration(value))
+  }
+}
+
+impl From<QueryDuration> for String {
+  fn from(duration: QueryDuration) -> Self {
+    duration.to_string()
+  }
+}
+
+impl fmt::Display for QueryDuration {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}ms", self.0)
+  }
+}
+
+pub fn parse_duration(value: &str) -> Option<i64> {
+  let mut rest = value.trim();
+  if rest.is_empty() {
+    return None;
+  }
+  let mut millis = 0i64;
+  while !rest.is_empty() {
+    let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+    if digits == 0 {
+      return None;
+    }
+    let amount = rest[..digits].parse::<i64>().ok()?;
+    rest = &rest[digits..];
+    let unit = rest
+      .find(|c: char| c.is_ascii_digit())
+      .unwrap_or(rest.len());
+    let scale = match &rest[..unit] {
+      "ms" => 1,
+      "s" => 1_000,
+      "m" => 60_000,
+      "h" => 3_600_000,
+      "d" => 86_400_000,
+      "w" => 604_800_000,
+      _ => return None,
+    };
+    millis = millis.checked_add(amount.checked_mul(scale)?)?;
+    rest = &rest[unit..];
+  }
+  Some(millis)
+}
+
+// Milliseconds since the epoch for an RFC3339 timestamp, a date, "now" or a
+// time relative to now such as "-7d" or "now+1h". Relative times that do not
+// fit in 64 bits are `None`.
+pub fn parse_time(value: &str, now: DateTime<Utc>) -> Option<i64> {
+  let value = value.trim();
+  let relative = value.strip_prefix("now").unwrap_or(value);
+  if relative.is_empty() {
+    return Some(now.timestamp_millis());
+  }
+  if let Some(duration) = relative.strip_prefix('-').and_then(parse_duration) {
+    return now.timestamp_millis().checked_sub(duration);
+  }
+  if let Some(duration) = relative.strip_prefix('+').and_then(parse_duration) {
+    return now.timestamp_millis().checked_add(duration);
+  }
+  if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+    return Some(time.timestamp_millis());
+  }
+  for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+    if let Ok(time) = NaiveDateTime::parse_from_str(value, format) {
+      return Some(time.and_utc().timestamp_millis());
+    }
+  }
+  NaiveDate::parse_from_str(value, "%Y-%m-%d")
+    .ok()
+    .and_then(|date| date.and_hms_opt(0, 0, 0))
+    .map(|time| time.and_utc().timestamp_millis())
+}
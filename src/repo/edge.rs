@@ -1,29 +1,169 @@
+use super::NOW_MILLIS_SQL;
+
 #[derive(sqlx::FromRow)]
 pub struct EdgeRow {
   pub id: i64,
+  pub namespace: String,
   pub from_node_id: i64,
   pub to_node_id: i64,
   pub uri: String,
   pub data: Option<String>,
+  pub deleted_at: Option<i64>,
   pub updated_at: i64,
   pub created_at: i64,
 }
 
-pub async fn create_edge(
-  pool: &sqlx::SqlitePool,
+pub async fn create_edge<'e, E>(
+  executor: E,
   from_node_id: i64,
   to_node_id: i64,
   uri: &str,
   data: Option<&str>,
-) -> sqlx::Result<EdgeRow> {
+) -> sqlx::Result<EdgeRow>
+where
+  E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+  // soft deleted nodes select nothing, so no edge is inserted
   sqlx::query_as(
-    "INSERT INTO edges (from_node_id, to_node_id, uri, data) VALUES ($1, $2, $3, $4) RETURNING *;",
+    r#"INSERT INTO edges (from_node_id, to_node_id, uri, data)
+    SELECT $1, $2, $3, $4
+    WHERE EXISTS (SELECT 1 FROM nodes WHERE id = $1 AND deleted_at IS NULL)
+      AND EXISTS (SELECT 1 FROM nodes WHERE id = $2 AND deleted_at IS NULL)
+    RETURNING *;"#,
   )
   .bind(from_node_id)
   .bind(to_node_id)
   .bind(uri)
   .bind(data)
-  .fetch_one(pool)
+  .fetch_one(executor)
+  .await
+}
+
+// Timestamps are milliseconds since the epoch. With `allow_cross_namespace`
+// the nodes may be in other namespaces than `namespace`.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_edge_at<'e, E>(
+  executor: E,
+  namespace: &str,
+  from_node_id: i64,
+  to_node_id: i64,
+  uri: &str,
+  data: Option<&str>,
+  created_at: i64,
+  updated_at: i64,
+  deleted_at: Option<i64>,
+  allow_cross_namespace: bool,
+) -> sqlx::Result<EdgeRow>
+where
+  E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+  sqlx::query_as(
+    r#"INSERT INTO edges (namespace, from_node_id, to_node_id, uri, data, created_at, updated_at, deleted_at, cross_namespace)
+    SELECT $1, $2, $3, $4, $5, $6, $7, $8,
+      $9 AND EXISTS (SELECT 1 FROM nodes WHERE id IN ($2, $3) AND namespace != $1)
+    RETURNING *;"#,
+  )
+  .bind(namespace)
+  .bind(from_node_id)
+  .bind(to_node_id)
+  .bind(uri)
+  .bind(data)
+  .bind(created_at)
+  .bind(updated_at)
+  .bind(deleted_at)
+  .bind(allow_cross_namespace)
+  .fetch_one(executor)
+  .await
+}
+
+// both keys are looked up in `namespace`, which the edge is created in
+pub async fn create_edge_by_keys<'e, E>(
+  executor: E,
+  namespace: &str,
+  from_node_key: &str,
+  to_node_key: &str,
+  uri: &str,
+  data: Option<&str>,
+) -> sqlx::Result<EdgeRow>
+where
+  E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+  // an unknown key or a soft deleted node selects nothing, so nothing is inserted
+  sqlx::query_as(
+    r#"INSERT INTO edges (namespace, from_node_id, to_node_id, uri, data)
+    SELECT $1, from_node.id, to_node.id, $4, $5
+    FROM nodes from_node, nodes to_node
+    WHERE from_node.namespace = $1 AND from_node.key = $2
+      AND to_node.namespace = $1 AND to_node.key = $3
+      AND from_node.deleted_at IS NULL AND to_node.deleted_at IS NULL
+    RETURNING *;"#,
+  )
+  .bind(namespace)
+  .bind(from_node_key)
+  .bind(to_node_key)
+  .bind(uri)
+  .bind(data)
+  .fetch_one(executor)
+  .await
+}
+
+// a cross namespace edge still needs one of its nodes in `namespace`
+pub async fn create_edge_in<'e, E>(
+  executor: E,
+  namespace: &str,
+  from_node_id: i64,
+  to_node_id: i64,
+  uri: &str,
+  data: Option<&str>,
+  cross_namespace: bool,
+) -> sqlx::Result<EdgeRow>
+where
+  E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+  sqlx::query_as(
+    r#"INSERT INTO edges (namespace, from_node_id, to_node_id, uri, data, cross_namespace)
+    SELECT $1, $2, $3, $4, $5, $6
+    WHERE EXISTS (SELECT 1 FROM nodes WHERE id = $2 AND deleted_at IS NULL)
+      AND EXISTS (SELECT 1 FROM nodes WHERE id = $3 AND deleted_at IS NULL)
+      AND (NOT $6 OR EXISTS (SELECT 1 FROM nodes WHERE id IN ($2, $3) AND namespace = $1))
+    RETURNING *;"#,
+  )
+  .bind(namespace)
+  .bind(from_node_id)
+  .bind(to_node_id)
+  .bind(uri)
+  .bind(data)
+  .bind(cross_namespace)
+  .fetch_one(executor)
+  .await
+}
+
+// soft deleted edges are only returned `with_deleted`
+pub async fn get_edge(
+  pool: &sqlx::SqlitePool,
+  edge_id: i64,
+  with_deleted: bool,
+) -> sqlx::Result<Option<EdgeRow>> {
+  sqlx::query_as("SELECT * FROM edges WHERE id = $1 AND ($2 OR deleted_at IS NULL);")
+    .bind(edge_id)
+    .bind(with_deleted)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn get_edge_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  edge_id: i64,
+  with_deleted: bool,
+) -> sqlx::Result<Option<EdgeRow>> {
+  sqlx::query_as(
+    "SELECT * FROM edges WHERE id = $1 AND namespace = $2 AND ($3 OR deleted_at IS NULL);",
+  )
+  .bind(edge_id)
+  .bind(namespace)
+  .bind(with_deleted)
+  .fetch_optional(pool)
   .await
 }
 
@@ -32,11 +172,29 @@ pub async fn update_edge(
   edge_id: i64,
   data: Option<&str>,
 ) -> sqlx::Result<EdgeRow> {
-  sqlx::query_as("UPDATE edges SET data = $1 WHERE id = $2 RETURNING *;")
-    .bind(data)
-    .bind(edge_id)
-    .fetch_one(pool)
-    .await
+  sqlx::query_as(&format!(
+    "UPDATE edges SET data = $1, updated_at = {NOW_MILLIS_SQL} WHERE id = $2 RETURNING *;"
+  ))
+  .bind(data)
+  .bind(edge_id)
+  .fetch_one(pool)
+  .await
+}
+
+pub async fn update_edge_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  edge_id: i64,
+  data: Option<&str>,
+) -> sqlx::Result<EdgeRow> {
+  sqlx::query_as(&format!(
+    "UPDATE edges SET data = $1, updated_at = {NOW_MILLIS_SQL} WHERE id = $2 AND namespace = $3 RETURNING *;"
+  ))
+  .bind(data)
+  .bind(edge_id)
+  .bind(namespace)
+  .fetch_one(pool)
+  .await
 }
 
 pub async fn delete_edge(pool: &sqlx::SqlitePool, edge_id: i64) -> sqlx::Result<Option<EdgeRow>> {
@@ -46,9 +204,21 @@ pub async fn delete_edge(pool: &sqlx::SqlitePool, edge_id: i64) -> sqlx::Result<
     .await
 }
 
+pub async fn delete_edge_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  edge_id: i64,
+) -> sqlx::Result<Option<EdgeRow>> {
+  sqlx::query_as("DELETE FROM edges WHERE id = $1 AND namespace = $2 RETURNING *;")
+    .bind(edge_id)
+    .bind(namespace)
+    .fetch_optional(pool)
+    .await
+}
+
 pub async fn delete_edges(pool: &sqlx::SqlitePool, edge_ids: &[i64]) -> sqlx::Result<Vec<EdgeRow>> {
   let ids = edge_ids
-    .into_iter()
+    .iter()
     .map(ToString::to_string)
     .collect::<Vec<String>>()
     .join(",");
@@ -65,3 +235,134 @@ pub async fn delete_edges_by_uri(pool: &sqlx::SqlitePool, uri: &str) -> sqlx::Re
     .fetch_all(pool)
     .await
 }
+
+pub async fn soft_delete_edge(
+  pool: &sqlx::SqlitePool,
+  edge_id: i64,
+) -> sqlx::Result<Option<EdgeRow>> {
+  sqlx::query_as(&format!(
+    "UPDATE edges SET deleted_at = {NOW_MILLIS_SQL} WHERE id = $1 AND deleted_at IS NULL RETURNING *;"
+  ))
+  .bind(edge_id)
+  .fetch_optional(pool)
+  .await
+}
+
+pub async fn soft_delete_edges(
+  pool: &sqlx::SqlitePool,
+  edge_ids: &[i64],
+) -> sqlx::Result<Vec<EdgeRow>> {
+  let ids = edge_ids
+    .iter()
+    .map(ToString::to_string)
+    .collect::<Vec<String>>()
+    .join(",");
+  sqlx::query_as(&format!(
+    "UPDATE edges SET deleted_at = {NOW_MILLIS_SQL} WHERE id in ({ids}) AND deleted_at IS NULL RETURNING *;"
+  ))
+  .fetch_all(pool)
+  .await
+}
+
+pub async fn soft_delete_edges_by_uri(
+  pool: &sqlx::SqlitePool,
+  uri: &str,
+) -> sqlx::Result<Vec<EdgeRow>> {
+  sqlx::query_as(&format!(
+    "UPDATE edges SET deleted_at = {NOW_MILLIS_SQL} WHERE uri = $1 AND deleted_at IS NULL RETURNING *;"
+  ))
+  .bind(uri)
+  .fetch_all(pool)
+  .await
+}
+
+// `None` when either node is soft deleted or another live edge already links
+// the same nodes
+pub async fn restore_edge(pool: &sqlx::SqlitePool, edge_id: i64) -> sqlx::Result<Option<EdgeRow>> {
+  restore_edge_scoped(pool, None, edge_id).await
+}
+
+pub async fn restore_edge_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  edge_id: i64,
+) -> sqlx::Result<Option<EdgeRow>> {
+  restore_edge_scoped(pool, Some(namespace), edge_id).await
+}
+
+// without a namespace any edge matches
+async fn restore_edge_scoped(
+  pool: &sqlx::SqlitePool,
+  namespace: Option<&str>,
+  edge_id: i64,
+) -> sqlx::Result<Option<EdgeRow>> {
+  sqlx::query_as(
+    r#"UPDATE edges SET deleted_at = NULL
+    WHERE id = $1
+      AND ($2 IS NULL OR namespace = $2)
+      AND deleted_at IS NOT NULL
+      AND from_node_id IN (SELECT id FROM nodes WHERE deleted_at IS NULL)
+      AND to_node_id IN (SELECT id FROM nodes WHERE deleted_at IS NULL)
+      AND NOT EXISTS (
+        SELECT 1 FROM edges live
+        WHERE live.from_node_id = edges.from_node_id
+          AND live.to_node_id = edges.to_node_id
+          AND live.deleted_at IS NULL
+      )
+    RETURNING *;"#,
+  )
+  .bind(edge_id)
+  .bind(namespace)
+  .fetch_optional(pool)
+  .await
+}
+
+pub async fn soft_delete_edge_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  edge_id: i64,
+) -> sqlx::Result<Option<EdgeRow>> {
+  sqlx::query_as(&format!(
+    "UPDATE edges SET deleted_at = {NOW_MILLIS_SQL} WHERE id = $1 AND namespace = $2 AND deleted_at IS NULL RETURNING *;"
+  ))
+  .bind(edge_id)
+  .bind(namespace)
+  .fetch_optional(pool)
+  .await
+}
+
+// the ids among `edge_ids` of edges stored with `cross_namespace` set
+pub async fn cross_namespace_edges(
+  pool: &sqlx::SqlitePool,
+  edge_ids: &[i64],
+) -> sqlx::Result<Vec<i64>> {
+  let ids = edge_ids
+    .iter()
+    .map(ToString::to_string)
+    .collect::<Vec<String>>()
+    .join(",");
+  sqlx::query_scalar(&format!(
+    "SELECT id FROM edges WHERE id in ({ids}) AND cross_namespace;"
+  ))
+  .fetch_all(pool)
+  .await
+}
+
+pub async fn purge_deleted_edges<'e, E>(executor: E, older_than: i64) -> sqlx::Result<u64>
+where
+  E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+  let result = sqlx::query("DELETE FROM edges WHERE deleted_at IS NOT NULL AND deleted_at <= $1;")
+    .bind(older_than)
+    .execute(executor)
+    .await?;
+  Ok(result.rows_affected())
+}
+
+pub async fn count_edges_by_uri(pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<(String, i64)>> {
+  sqlx::query_as(
+    "SELECT uri, COUNT(*) FROM edges WHERE deleted_at IS NULL GROUP BY uri ORDER BY uri;",
+  )
+  .fetch_all(pool)
+  .await
+}
@@ -0,0 +1,66 @@
+#[derive(sqlx::FromRow)]
+pub struct NodeHistoryRow {
+  pub id: i64,
+  pub node_id: i64,
+  pub namespace: String,
+  pub key: Option<String>,
+  pub uri: String,
+  pub data: String,
+  pub deleted_at: Option<i64>,
+  pub updated_at: i64,
+  pub created_at: i64,
+  pub recorded_at: i64,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct EdgeHistoryRow {
+  pub id: i64,
+  pub edge_id: i64,
+  pub namespace: String,
+  pub from_node_id: i64,
+  pub to_node_id: i64,
+  pub uri: String,
+  pub data: Option<String>,
+  pub deleted_at: Option<i64>,
+  pub updated_at: i64,
+  pub created_at: i64,
+  pub recorded_at: i64,
+}
+
+pub async fn set_history_enabled(pool: &sqlx::SqlitePool, enabled: bool) -> sqlx::Result<()> {
+  sqlx::query(
+    "INSERT INTO settings (key, value) VALUES ('history', $1) ON CONFLICT (key) DO UPDATE SET value = excluded.value;",
+  )
+  .bind(enabled.to_string())
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn history_enabled(pool: &sqlx::SqlitePool) -> sqlx::Result<bool> {
+  let value: Option<String> =
+    sqlx::query_scalar("SELECT value FROM settings WHERE key = 'history';")
+      .fetch_optional(pool)
+      .await?;
+  Ok(value.as_deref() == Some("true"))
+}
+
+pub async fn node_history(
+  pool: &sqlx::SqlitePool,
+  node_id: i64,
+) -> sqlx::Result<Vec<NodeHistoryRow>> {
+  sqlx::query_as("SELECT * FROM nodes_history WHERE node_id = $1 ORDER BY recorded_at, id;")
+    .bind(node_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn edge_history(
+  pool: &sqlx::SqlitePool,
+  edge_id: i64,
+) -> sqlx::Result<Vec<EdgeHistoryRow>> {
+  sqlx::query_as("SELECT * FROM edges_history WHERE edge_id = $1 ORDER BY recorded_at, id;")
+    .bind(edge_id)
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,62 @@
+use super::node::NodeRow;
+
+pub async fn add_label<'e, E>(executor: E, node_id: i64, label: &str) -> sqlx::Result<bool>
+where
+  E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+  let result =
+    sqlx::query("INSERT INTO node_labels (node_id, label) VALUES ($1, $2) ON CONFLICT DO NOTHING;")
+      .bind(node_id)
+      .bind(label)
+      .execute(executor)
+      .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+pub async fn remove_label(
+  pool: &sqlx::SqlitePool,
+  node_id: i64,
+  label: &str,
+) -> sqlx::Result<bool> {
+  let result = sqlx::query("DELETE FROM node_labels WHERE node_id = $1 AND label = $2;")
+    .bind(node_id)
+    .bind(label)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+pub async fn node_labels(pool: &sqlx::SqlitePool, node_id: i64) -> sqlx::Result<Vec<String>> {
+  sqlx::query_scalar("SELECT label FROM node_labels WHERE node_id = $1 ORDER BY label;")
+    .bind(node_id)
+    .fetch_all(pool)
+    .await
+}
+
+// (node id, label) pairs of the given nodes
+pub async fn labels_of_nodes(
+  pool: &sqlx::SqlitePool,
+  node_ids: &[i64],
+) -> sqlx::Result<Vec<(i64, String)>> {
+  let mut qb = sqlx::QueryBuilder::<'_, sqlx::Sqlite>::new(
+    "SELECT node_id, label FROM node_labels WHERE node_id IN (",
+  );
+  let mut separated = qb.separated(", ");
+  for node_id in node_ids {
+    separated.push_bind(node_id);
+  }
+  qb.push(") ORDER BY node_id, label;");
+  qb.build_query_as().fetch_all(pool).await
+}
+
+pub async fn nodes_with_label(pool: &sqlx::SqlitePool, label: &str) -> sqlx::Result<Vec<NodeRow>> {
+  sqlx::query_as(
+    r#"SELECT node.* FROM node_labels
+    JOIN nodes node ON node.id = node_labels.node_id
+    WHERE node_labels.label = $1 AND node.deleted_at IS NULL
+    ORDER BY node.id;"#,
+  )
+  .bind(label)
+  .fetch_all(pool)
+  .await
+}
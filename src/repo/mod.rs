@@ -1,3 +1,11 @@
 pub mod edge;
+pub mod history;
+pub mod label;
+pub mod neighbor;
 pub mod node;
 pub mod node_edge;
+pub mod settings;
+
+// the current time in milliseconds since the epoch, as stored in the
+// `*_at` columns
+pub(crate) const NOW_MILLIS_SQL: &str = "CAST(unixepoch('subsec') * 1000 AS INTEGER)";
@@ -0,0 +1,100 @@
+use crate::model::neighbor::Direction;
+
+use super::node::NodeRow;
+
+pub async fn out_neighbors(
+  pool: &sqlx::SqlitePool,
+  node_id: i64,
+  edge_uri: Option<&str>,
+) -> sqlx::Result<Vec<NodeRow>> {
+  let sql = format!(
+    r#"SELECT node.* FROM edges edge
+    JOIN nodes node ON node.id = edge.to_node_id
+    WHERE edge.from_node_id = $1{}
+    AND edge.deleted_at IS NULL AND node.deleted_at IS NULL
+    ORDER BY node.id;"#,
+    uri_condition("edge.uri", "$2", edge_uri)
+  );
+  let mut query = sqlx::query_as(&sql).bind(node_id);
+  if let Some(edge_uri) = edge_uri {
+    query = query.bind(edge_uri);
+  }
+  query.fetch_all(pool).await
+}
+
+pub async fn in_neighbors(
+  pool: &sqlx::SqlitePool,
+  node_id: i64,
+  edge_uri: Option<&str>,
+) -> sqlx::Result<Vec<NodeRow>> {
+  let sql = format!(
+    r#"SELECT node.* FROM edges edge
+    JOIN nodes node ON node.id = edge.from_node_id
+    WHERE edge.to_node_id = $1{}
+    AND edge.deleted_at IS NULL AND node.deleted_at IS NULL
+    ORDER BY node.id;"#,
+    uri_condition("edge.uri", "$2", edge_uri)
+  );
+  let mut query = sqlx::query_as(&sql).bind(node_id);
+  if let Some(edge_uri) = edge_uri {
+    query = query.bind(edge_uri);
+  }
+  query.fetch_all(pool).await
+}
+
+pub async fn degree(
+  pool: &sqlx::SqlitePool,
+  node_id: i64,
+  direction: Direction,
+  edge_uri: Option<&str>,
+) -> sqlx::Result<i64> {
+  let condition = match direction {
+    Direction::Outgoing => "from_node_id = $1",
+    Direction::Incoming => "to_node_id = $1",
+    Direction::Both => "(from_node_id = $1 OR to_node_id = $1)",
+  };
+  let sql = format!(
+    "SELECT COUNT(*) FROM edges WHERE {condition}{} AND deleted_at IS NULL;",
+    uri_condition("uri", "$2", edge_uri)
+  );
+  let mut query = sqlx::query_scalar(&sql).bind(node_id);
+  if let Some(edge_uri) = edge_uri {
+    query = query.bind(edge_uri);
+  }
+  query.fetch_one(pool).await
+}
+
+// (node id, out-degree, in-degree) for every requested node that exists
+pub async fn degrees(
+  pool: &sqlx::SqlitePool,
+  node_ids: &[i64],
+  edge_uri: Option<&str>,
+) -> sqlx::Result<Vec<(i64, i64, i64)>> {
+  let ids = node_ids
+    .iter()
+    .map(ToString::to_string)
+    .collect::<Vec<String>>()
+    .join(",");
+  let uri = uri_condition("uri", "$1", edge_uri);
+  let sql = format!(
+    r#"SELECT node.id,
+      (SELECT COUNT(*) FROM edges WHERE from_node_id = node.id{uri} AND deleted_at IS NULL),
+      (SELECT COUNT(*) FROM edges WHERE to_node_id = node.id{uri} AND deleted_at IS NULL)
+    FROM nodes node WHERE node.id in ({ids});"#
+  );
+  let mut query = sqlx::query_as(&sql);
+  if let Some(edge_uri) = edge_uri {
+    query = query.bind(edge_uri);
+  }
+  query.fetch_all(pool).await
+}
+
+// The uri predicate is left out entirely when no uri is given. Written as
+// `$2 IS NULL OR uri = $2` it would keep SQLite from using the
+// (node id, uri) indexes.
+fn uri_condition(column: &str, parameter: &str, edge_uri: Option<&str>) -> String {
+  match edge_uri {
+    Some(_) => format!(" AND {column} = {parameter}"),
+    None => String::new(),
+  }
+}
@@ -1,17 +1,135 @@
+use super::NOW_MILLIS_SQL;
+
 #[derive(sqlx::FromRow)]
 pub struct NodeRow {
   pub id: i64,
+  pub namespace: String,
+  pub key: Option<String>,
   pub uri: String,
   pub data: String,
+  pub deleted_at: Option<i64>,
   pub updated_at: i64,
   pub created_at: i64,
 }
 
-pub async fn create_node(pool: &sqlx::SqlitePool, uri: &str, data: &str) -> sqlx::Result<NodeRow> {
+pub async fn create_node<'e, E>(executor: E, uri: &str, data: &str) -> sqlx::Result<NodeRow>
+where
+  E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
   sqlx::query_as("INSERT INTO nodes (uri, data) VALUES ($1, $2) RETURNING *;")
     .bind(uri)
     .bind(data)
-    .fetch_one(pool)
+    .fetch_one(executor)
+    .await
+}
+
+pub async fn create_node_with_key<'e, E>(
+  executor: E,
+  namespace: &str,
+  key: &str,
+  uri: &str,
+  data: &str,
+) -> sqlx::Result<NodeRow>
+where
+  E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+  sqlx::query_as(
+    "INSERT INTO nodes (namespace, key, uri, data) VALUES ($1, $2, $3, $4) RETURNING *;",
+  )
+  .bind(namespace)
+  .bind(key)
+  .bind(uri)
+  .bind(data)
+  .fetch_one(executor)
+  .await
+}
+
+// timestamps are milliseconds since the epoch
+#[allow(clippy::too_many_arguments)]
+pub async fn create_node_at<'e, E>(
+  executor: E,
+  namespace: &str,
+  key: Option<&str>,
+  uri: &str,
+  data: &str,
+  created_at: i64,
+  updated_at: i64,
+  deleted_at: Option<i64>,
+) -> sqlx::Result<NodeRow>
+where
+  E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+  sqlx::query_as(
+    "INSERT INTO nodes (namespace, key, uri, data, created_at, updated_at, deleted_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *;",
+  )
+  .bind(namespace)
+  .bind(key)
+  .bind(uri)
+  .bind(data)
+  .bind(created_at)
+  .bind(updated_at)
+  .bind(deleted_at)
+  .fetch_one(executor)
+  .await
+}
+
+pub async fn create_node_in<'e, E>(
+  executor: E,
+  namespace: &str,
+  uri: &str,
+  data: &str,
+) -> sqlx::Result<NodeRow>
+where
+  E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+  sqlx::query_as("INSERT INTO nodes (namespace, uri, data) VALUES ($1, $2, $3) RETURNING *;")
+    .bind(namespace)
+    .bind(uri)
+    .bind(data)
+    .fetch_one(executor)
+    .await
+}
+
+// soft deleted nodes are only returned `with_deleted`
+pub async fn get_node(
+  pool: &sqlx::SqlitePool,
+  id: i64,
+  with_deleted: bool,
+) -> sqlx::Result<Option<NodeRow>> {
+  sqlx::query_as("SELECT * FROM nodes WHERE id = $1 AND ($2 OR deleted_at IS NULL);")
+    .bind(id)
+    .bind(with_deleted)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn get_node_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  id: i64,
+  with_deleted: bool,
+) -> sqlx::Result<Option<NodeRow>> {
+  sqlx::query_as(
+    "SELECT * FROM nodes WHERE id = $1 AND namespace = $2 AND ($3 OR deleted_at IS NULL);",
+  )
+  .bind(id)
+  .bind(namespace)
+  .bind(with_deleted)
+  .fetch_optional(pool)
+  .await
+}
+
+// Keys are unique within a namespace. Soft deleted nodes keep their key but
+// are not returned.
+pub async fn get_node_by_key(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  key: &str,
+) -> sqlx::Result<Option<NodeRow>> {
+  sqlx::query_as("SELECT * FROM nodes WHERE namespace = $1 AND key = $2 AND deleted_at IS NULL;")
+    .bind(namespace)
+    .bind(key)
+    .fetch_optional(pool)
     .await
 }
 
@@ -20,11 +138,29 @@ pub async fn update_node(
   node_id: i64,
   data: &str,
 ) -> sqlx::Result<NodeRow> {
-  sqlx::query_as("UPDATE nodes SET data = $1 WHERE id = $2 RETURNING *;")
-    .bind(data)
-    .bind(node_id)
-    .fetch_one(pool)
-    .await
+  sqlx::query_as(&format!(
+    "UPDATE nodes SET data = $1, updated_at = {NOW_MILLIS_SQL} WHERE id = $2 RETURNING *;"
+  ))
+  .bind(data)
+  .bind(node_id)
+  .fetch_one(pool)
+  .await
+}
+
+pub async fn update_node_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  node_id: i64,
+  data: &str,
+) -> sqlx::Result<NodeRow> {
+  sqlx::query_as(&format!(
+    "UPDATE nodes SET data = $1, updated_at = {NOW_MILLIS_SQL} WHERE id = $2 AND namespace = $3 RETURNING *;"
+  ))
+  .bind(data)
+  .bind(node_id)
+  .bind(namespace)
+  .fetch_one(pool)
+  .await
 }
 
 pub async fn delete_node(pool: &sqlx::SqlitePool, id: i64) -> sqlx::Result<Option<NodeRow>> {
@@ -34,22 +170,302 @@ pub async fn delete_node(pool: &sqlx::SqlitePool, id: i64) -> sqlx::Result<Optio
     .await
 }
 
+pub async fn delete_node_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  id: i64,
+) -> sqlx::Result<Option<NodeRow>> {
+  sqlx::query_as("DELETE FROM nodes WHERE id = $1 AND namespace = $2 RETURNING *;")
+    .bind(id)
+    .bind(namespace)
+    .fetch_optional(pool)
+    .await
+}
+
+// (id, namespace) of the nodes among `node_ids` that are not soft deleted
+pub async fn live_node_namespaces<'e, E>(
+  executor: E,
+  node_ids: &[i64],
+) -> sqlx::Result<Vec<(i64, String)>>
+where
+  E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+  let ids = node_ids
+    .iter()
+    .map(ToString::to_string)
+    .collect::<Vec<String>>()
+    .join(",");
+  sqlx::query_as(&format!(
+    "SELECT id, namespace FROM nodes WHERE id in ({ids}) AND deleted_at IS NULL ORDER BY id;"
+  ))
+  .fetch_all(executor)
+  .await
+}
+
 pub async fn delete_nodes(pool: &sqlx::SqlitePool, node_ids: &[i64]) -> sqlx::Result<Vec<NodeRow>> {
+  delete_nodes_scoped(pool, None, node_ids).await
+}
+
+pub async fn delete_nodes_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  node_ids: &[i64],
+) -> sqlx::Result<Vec<NodeRow>> {
+  delete_nodes_scoped(pool, Some(namespace), node_ids).await
+}
+
+// without a namespace any node matches
+async fn delete_nodes_scoped(
+  pool: &sqlx::SqlitePool,
+  namespace: Option<&str>,
+  node_ids: &[i64],
+) -> sqlx::Result<Vec<NodeRow>> {
   let ids = node_ids
-    .into_iter()
+    .iter()
     .map(ToString::to_string)
     .collect::<Vec<String>>()
     .join(",");
   sqlx::query_as(&format!(
-    "DELETE FROM nodes WHERE id in ({ids}) RETURNING *;"
+    "DELETE FROM nodes WHERE id in ({ids}) AND ($1 IS NULL OR namespace = $1) RETURNING *;"
   ))
+  .bind(namespace)
   .fetch_all(pool)
   .await
 }
 
 pub async fn delete_nodes_by_uri(pool: &sqlx::SqlitePool, uri: &str) -> sqlx::Result<Vec<NodeRow>> {
-  sqlx::query_as("DELETE FROM nodes WHERE uri = $1 RETURNING *;")
+  delete_nodes_by_uri_scoped(pool, None, uri).await
+}
+
+pub async fn delete_nodes_by_uri_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  uri: &str,
+) -> sqlx::Result<Vec<NodeRow>> {
+  delete_nodes_by_uri_scoped(pool, Some(namespace), uri).await
+}
+
+// without a namespace any node matches
+async fn delete_nodes_by_uri_scoped(
+  pool: &sqlx::SqlitePool,
+  namespace: Option<&str>,
+  uri: &str,
+) -> sqlx::Result<Vec<NodeRow>> {
+  sqlx::query_as("DELETE FROM nodes WHERE uri = $1 AND ($2 IS NULL OR namespace = $2) RETURNING *;")
     .bind(uri)
+    .bind(namespace)
     .fetch_all(pool)
     .await
 }
+
+pub async fn soft_delete_node(pool: &sqlx::SqlitePool, id: i64) -> sqlx::Result<Option<NodeRow>> {
+  soft_delete_node_scoped(pool, None, id).await
+}
+
+pub async fn soft_delete_node_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  id: i64,
+) -> sqlx::Result<Option<NodeRow>> {
+  soft_delete_node_scoped(pool, Some(namespace), id).await
+}
+
+// without a namespace any node matches
+async fn soft_delete_node_scoped(
+  pool: &sqlx::SqlitePool,
+  namespace: Option<&str>,
+  id: i64,
+) -> sqlx::Result<Option<NodeRow>> {
+  let mut tx = pool.begin().await?;
+  let row: Option<NodeRow> = sqlx::query_as(&format!(
+    "UPDATE nodes SET deleted_at = {NOW_MILLIS_SQL} WHERE id = $1 AND ($2 IS NULL OR namespace = $2) AND deleted_at IS NULL RETURNING *;"
+  ))
+  .bind(id)
+  .bind(namespace)
+  .fetch_optional(&mut *tx)
+  .await?;
+  if let Some(row) = &row {
+    sqlx::query(
+      "UPDATE edges SET deleted_at = $1 WHERE deleted_at IS NULL AND (from_node_id = $2 OR to_node_id = $2);",
+    )
+    .bind(row.deleted_at)
+    .bind(row.id)
+    .execute(&mut *tx)
+    .await?;
+  }
+  tx.commit().await?;
+  Ok(row)
+}
+
+pub async fn soft_delete_nodes(
+  pool: &sqlx::SqlitePool,
+  node_ids: &[i64],
+) -> sqlx::Result<Vec<NodeRow>> {
+  soft_delete_nodes_scoped(pool, None, node_ids).await
+}
+
+pub async fn soft_delete_nodes_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  node_ids: &[i64],
+) -> sqlx::Result<Vec<NodeRow>> {
+  soft_delete_nodes_scoped(pool, Some(namespace), node_ids).await
+}
+
+// without a namespace any node matches
+async fn soft_delete_nodes_scoped(
+  pool: &sqlx::SqlitePool,
+  namespace: Option<&str>,
+  node_ids: &[i64],
+) -> sqlx::Result<Vec<NodeRow>> {
+  let ids = node_ids
+    .iter()
+    .map(ToString::to_string)
+    .collect::<Vec<String>>()
+    .join(",");
+  let mut tx = pool.begin().await?;
+  let rows: Vec<NodeRow> = sqlx::query_as(&format!(
+    "UPDATE nodes SET deleted_at = {NOW_MILLIS_SQL} WHERE id in ({ids}) AND ($1 IS NULL OR namespace = $1) AND deleted_at IS NULL RETURNING *;"
+  ))
+  .bind(namespace)
+  .fetch_all(&mut *tx)
+  .await?;
+  soft_delete_incident_edges(&mut tx, &rows).await?;
+  tx.commit().await?;
+  Ok(rows)
+}
+
+pub async fn soft_delete_nodes_by_uri(
+  pool: &sqlx::SqlitePool,
+  uri: &str,
+) -> sqlx::Result<Vec<NodeRow>> {
+  soft_delete_nodes_by_uri_scoped(pool, None, uri).await
+}
+
+pub async fn soft_delete_nodes_by_uri_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  uri: &str,
+) -> sqlx::Result<Vec<NodeRow>> {
+  soft_delete_nodes_by_uri_scoped(pool, Some(namespace), uri).await
+}
+
+// without a namespace any node matches
+async fn soft_delete_nodes_by_uri_scoped(
+  pool: &sqlx::SqlitePool,
+  namespace: Option<&str>,
+  uri: &str,
+) -> sqlx::Result<Vec<NodeRow>> {
+  let mut tx = pool.begin().await?;
+  let rows: Vec<NodeRow> = sqlx::query_as(&format!(
+    "UPDATE nodes SET deleted_at = {NOW_MILLIS_SQL} WHERE uri = $1 AND ($2 IS NULL OR namespace = $2) AND deleted_at IS NULL RETURNING *;"
+  ))
+  .bind(uri)
+  .bind(namespace)
+  .fetch_all(&mut *tx)
+  .await?;
+  soft_delete_incident_edges(&mut tx, &rows).await?;
+  tx.commit().await?;
+  Ok(rows)
+}
+
+async fn soft_delete_incident_edges(
+  tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+  rows: &[NodeRow],
+) -> sqlx::Result<()> {
+  let Some(deleted_at) = rows.first().and_then(|row| row.deleted_at) else {
+    return Ok(());
+  };
+  let ids = rows
+    .iter()
+    .map(|row| row.id.to_string())
+    .collect::<Vec<String>>()
+    .join(",");
+  sqlx::query(&format!(
+    "UPDATE edges SET deleted_at = $1 WHERE deleted_at IS NULL AND (from_node_id in ({ids}) OR to_node_id in ({ids}));"
+  ))
+  .bind(deleted_at)
+  .execute(&mut **tx)
+  .await?;
+  Ok(())
+}
+
+pub async fn restore_node(pool: &sqlx::SqlitePool, id: i64) -> sqlx::Result<Option<NodeRow>> {
+  restore_node_scoped(pool, None, id).await
+}
+
+pub async fn restore_node_in(
+  pool: &sqlx::SqlitePool,
+  namespace: &str,
+  id: i64,
+) -> sqlx::Result<Option<NodeRow>> {
+  restore_node_scoped(pool, Some(namespace), id).await
+}
+
+// without a namespace any node matches
+async fn restore_node_scoped(
+  pool: &sqlx::SqlitePool,
+  namespace: Option<&str>,
+  id: i64,
+) -> sqlx::Result<Option<NodeRow>> {
+  let mut tx = pool.begin().await?;
+  let deleted_at: Option<i64> = sqlx::query_scalar(
+    "SELECT deleted_at FROM nodes WHERE id = $1 AND ($2 IS NULL OR namespace = $2) AND deleted_at IS NOT NULL;",
+  )
+  .bind(id)
+  .bind(namespace)
+  .fetch_optional(&mut *tx)
+  .await?;
+  let Some(deleted_at) = deleted_at else {
+    return Ok(None);
+  };
+  let row: NodeRow =
+    sqlx::query_as("UPDATE nodes SET deleted_at = NULL WHERE id = $1 RETURNING *;")
+      .bind(id)
+      .fetch_one(&mut *tx)
+      .await?;
+  // only edges tombstoned together with this node come back, at most one per
+  // pair of nodes, and only if the other end is alive and no newer edge links
+  // the same nodes
+  sqlx::query(
+    r#"UPDATE edges SET deleted_at = NULL
+    WHERE id IN (
+        SELECT MAX(id) FROM edges
+        WHERE deleted_at = $1 AND (from_node_id = $2 OR to_node_id = $2)
+        GROUP BY from_node_id, to_node_id
+      )
+      AND from_node_id IN (SELECT id FROM nodes WHERE deleted_at IS NULL)
+      AND to_node_id IN (SELECT id FROM nodes WHERE deleted_at IS NULL)
+      AND NOT EXISTS (
+        SELECT 1 FROM edges live
+        WHERE live.from_node_id = edges.from_node_id
+          AND live.to_node_id = edges.to_node_id
+          AND live.deleted_at IS NULL
+      );"#,
+  )
+  .bind(deleted_at)
+  .bind(id)
+  .execute(&mut *tx)
+  .await?;
+  tx.commit().await?;
+  Ok(Some(row))
+}
+
+pub async fn purge_deleted_nodes<'e, E>(executor: E, older_than: i64) -> sqlx::Result<u64>
+where
+  E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+  let result = sqlx::query("DELETE FROM nodes WHERE deleted_at IS NOT NULL AND deleted_at <= $1;")
+    .bind(older_than)
+    .execute(executor)
+    .await?;
+  Ok(result.rows_affected())
+}
+
+pub async fn count_nodes_by_uri(pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<(String, i64)>> {
+  sqlx::query_as(
+    "SELECT uri, COUNT(*) FROM nodes WHERE deleted_at IS NULL GROUP BY uri ORDER BY uri;",
+  )
+  .fetch_all(pool)
+  .await
+}
@@ -0,0 +1,17 @@
+pub async fn set_soft_delete_enabled(pool: &sqlx::SqlitePool, enabled: bool) -> sqlx::Result<()> {
+  sqlx::query(
+    "INSERT INTO settings (key, value) VALUES ('soft_delete', $1) ON CONFLICT (key) DO UPDATE SET value = excluded.value;",
+  )
+  .bind(enabled.to_string())
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn soft_delete_enabled(pool: &sqlx::SqlitePool) -> sqlx::Result<bool> {
+  let value: Option<String> =
+    sqlx::query_scalar("SELECT value FROM settings WHERE key = 'soft_delete';")
+      .fetch_optional(pool)
+      .await?;
+  Ok(value.as_deref() == Some("true"))
+}
@@ -0,0 +1,103 @@
+use axum::{
+  extract::{
+    rejection::{JsonRejection, PathRejection, QueryRejection},
+    Path, Query, State,
+  },
+  http::StatusCode,
+  Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::model::edge::{
+  create_edge_with_ids, delete_edge, get_edge_with_deleted, restore_edge, soft_delete_edge,
+  update_edge, Edge,
+};
+
+use super::{error::ServerError, node::DeleteParams};
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateEdge {
+  pub from_node_id: i64,
+  pub to_node_id: i64,
+  pub uri: String,
+  #[serde(default)]
+  pub data: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateEdge {
+  #[serde(default)]
+  pub data: Option<Value>,
+}
+
+// soft deleted edges are returned with `deleted_at` so they can be restored
+pub async fn get(
+  State(pool): State<sqlx::SqlitePool>,
+  id: Result<Path<i64>, PathRejection>,
+) -> Result<Json<Edge<Value>>, ServerError> {
+  let Path(id) = id?;
+  match get_edge_with_deleted(&pool, id).await? {
+    Some(edge) => Ok(Json(edge)),
+    None => Err(ServerError::not_found("edge", id)),
+  }
+}
+
+pub async fn create(
+  State(pool): State<sqlx::SqlitePool>,
+  body: Result<Json<CreateEdge>, JsonRejection>,
+) -> Result<(StatusCode, Json<Edge<Value>>), ServerError> {
+  let Json(body) = body?;
+  let edge = create_edge_with_ids(
+    &pool,
+    body.from_node_id,
+    body.to_node_id,
+    &body.uri,
+    body.data,
+  )
+  .await?;
+  Ok((StatusCode::CREATED, Json(edge)))
+}
+
+pub async fn update(
+  State(pool): State<sqlx::SqlitePool>,
+  id: Result<Path<i64>, PathRejection>,
+  body: Result<Json<UpdateEdge>, JsonRejection>,
+) -> Result<Json<Edge<Value>>, ServerError> {
+  let Path(id) = id?;
+  let Json(body) = body?;
+  match update_edge(&pool, id, body.data).await {
+    Ok(edge) => Ok(Json(edge)),
+    Err(sqlx::Error::RowNotFound) => Err(ServerError::not_found("edge", id)),
+    Err(e) => Err(e.into()),
+  }
+}
+
+pub async fn delete(
+  State(pool): State<sqlx::SqlitePool>,
+  id: Result<Path<i64>, PathRejection>,
+  params: Result<Query<DeleteParams>, QueryRejection>,
+) -> Result<Json<Edge<Value>>, ServerError> {
+  let Path(id) = id?;
+  let Query(params) = params?;
+  let edge = if params.soft {
+    soft_delete_edge(&pool, id).await?
+  } else {
+    delete_edge(&pool, id).await?
+  };
+  match edge {
+    Some(edge) => Ok(Json(edge)),
+    None => Err(ServerError::not_found("edge", id)),
+  }
+}
+
+pub async fn restore(
+  State(pool): State<sqlx::SqlitePool>,
+  id: Result<Path<i64>, PathRejection>,
+) -> Result<Json<Edge<Value>>, ServerError> {
+  let Path(id) = id?;
+  match restore_edge(&pool, id).await? {
+    Some(edge) => Ok(Json(edge)),
+    None => Err(ServerError::not_found("deleted edge", id)),
+  }
+}
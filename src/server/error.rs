@@ -0,0 +1,109 @@
+use std::fmt;
+
+use axum::{
+  extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+  http::StatusCode,
+  response::{IntoResponse, Response},
+  Json,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+  BadRequest,
+  NotFound,
+  Conflict,
+  Internal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorResponse {
+  pub code: ErrorCode,
+  pub message: String,
+}
+
+#[derive(Debug)]
+pub struct ServerError {
+  pub code: ErrorCode,
+  pub message: String,
+}
+
+impl ServerError {
+  pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+    Self {
+      code,
+      message: message.into(),
+    }
+  }
+
+  pub fn not_found(kind: &str, id: i64) -> Self {
+    Self::new(ErrorCode::NotFound, format!("No {kind} with id {id}"))
+  }
+
+  // Errors from running a client's query. SQLite reports unknown columns,
+  // malformed JSON paths and invalid patterns as a plain SQLITE_ERROR.
+  pub fn query(error: sqlx::Error) -> Self {
+    match &error {
+      sqlx::Error::Database(e) if e.code().as_deref() == Some("1") => {
+        Self::new(ErrorCode::BadRequest, error.to_string())
+      }
+      _ => error.into(),
+    }
+  }
+}
+
+impl std::error::Error for ServerError {}
+
+impl fmt::Display for ServerError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.message)
+  }
+}
+
+impl IntoResponse for ServerError {
+  fn into_response(self) -> Response {
+    let status = match self.code {
+      ErrorCode::BadRequest => StatusCode::BAD_REQUEST,
+      ErrorCode::NotFound => StatusCode::NOT_FOUND,
+      ErrorCode::Conflict => StatusCode::CONFLICT,
+      ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let body = ErrorResponse {
+      code: self.code,
+      message: self.message,
+    };
+    (status, Json(body)).into_response()
+  }
+}
+
+impl From<sqlx::Error> for ServerError {
+  fn from(error: sqlx::Error) -> Self {
+    let code = match &error {
+      sqlx::Error::RowNotFound => ErrorCode::NotFound,
+      sqlx::Error::Encode(_) => ErrorCode::BadRequest,
+      sqlx::Error::Database(e) if e.is_unique_violation() => ErrorCode::Conflict,
+      sqlx::Error::Database(e) if e.is_foreign_key_violation() => ErrorCode::BadRequest,
+      _ => ErrorCode::Internal,
+    };
+    Self::new(code, error.to_string())
+  }
+}
+
+impl From<JsonRejection> for ServerError {
+  fn from(rejection: JsonRejection) -> Self {
+    Self::new(ErrorCode::BadRequest, rejection.body_text())
+  }
+}
+
+impl From<PathRejection> for ServerError {
+  fn from(rejection: PathRejection) -> Self {
+    Self::new(ErrorCode::BadRequest, rejection.body_text())
+  }
+}
+
+impl From<QueryRejection> for ServerError {
+  fn from(rejection: QueryRejection) -> Self {
+    Self::new(ErrorCode::BadRequest, rejection.body_text())
+  }
+}
@@ -0,0 +1,46 @@
+pub mod edge;
+pub mod error;
+pub mod node;
+pub mod query;
+
+use axum::{
+  extract::State,
+  routing::{get, post},
+  Json, Router,
+};
+
+use crate::core::database::{uri_counts, UriCounts};
+
+pub use error::{ErrorCode, ErrorResponse, ServerError};
+pub use query::{Page, QueryKind, QueryParams};
+
+pub fn router(pool: sqlx::SqlitePool) -> Router {
+  Router::new()
+    .route("/uris", get(uris))
+    .route("/nodes", post(node::create))
+    .route(
+      "/nodes/{id}",
+      get(node::get).put(node::update).delete(node::delete),
+    )
+    .route("/nodes/{id}/restore", post(node::restore))
+    .route("/edges", post(edge::create))
+    .route(
+      "/edges/{id}",
+      get(edge::get).put(edge::update).delete(edge::delete),
+    )
+    .route("/edges/{id}/restore", post(edge::restore))
+    .route("/query", post(query::query))
+    .with_state(pool)
+}
+
+pub async fn serve<A>(pool: sqlx::SqlitePool, addr: A) -> std::io::Result<()>
+where
+  A: tokio::net::ToSocketAddrs,
+{
+  let listener = tokio::net::TcpListener::bind(addr).await?;
+  axum::serve(listener, router(pool)).await
+}
+
+async fn uris(State(pool): State<sqlx::SqlitePool>) -> Result<Json<UriCounts>, ServerError> {
+  Ok(Json(uri_counts(&pool).await?))
+}
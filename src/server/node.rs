@@ -0,0 +1,98 @@
+use axum::{
+  extract::{
+    rejection::{JsonRejection, PathRejection, QueryRejection},
+    Path, Query, State,
+  },
+  http::StatusCode,
+  Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::model::node::{
+  create_node, delete_node, get_node_with_deleted, restore_node, soft_delete_node, update_node,
+  Node,
+};
+
+use super::error::ServerError;
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateNode {
+  pub uri: String,
+  pub data: Value,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateNode {
+  pub data: Value,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct DeleteParams {
+  #[serde(default)]
+  pub soft: bool,
+}
+
+// soft deleted nodes are returned with `deleted_at` so they can be restored
+pub async fn get(
+  State(pool): State<sqlx::SqlitePool>,
+  id: Result<Path<i64>, PathRejection>,
+) -> Result<Json<Node<Value>>, ServerError> {
+  let Path(id) = id?;
+  match get_node_with_deleted(&pool, id).await? {
+    Some(node) => Ok(Json(node)),
+    None => Err(ServerError::not_found("node", id)),
+  }
+}
+
+pub async fn create(
+  State(pool): State<sqlx::SqlitePool>,
+  body: Result<Json<CreateNode>, JsonRejection>,
+) -> Result<(StatusCode, Json<Node<Value>>), ServerError> {
+  let Json(body) = body?;
+  let node = create_node(&pool, &body.uri, body.data).await?;
+  Ok((StatusCode::CREATED, Json(node)))
+}
+
+pub async fn update(
+  State(pool): State<sqlx::SqlitePool>,
+  id: Result<Path<i64>, PathRejection>,
+  body: Result<Json<UpdateNode>, JsonRejection>,
+) -> Result<Json<Node<Value>>, ServerError> {
+  let Path(id) = id?;
+  let Json(body) = body?;
+  match update_node(&pool, id, body.data).await {
+    Ok(node) => Ok(Json(node)),
+    Err(sqlx::Error::RowNotFound) => Err(ServerError::not_found("node", id)),
+    Err(e) => Err(e.into()),
+  }
+}
+
+pub async fn delete(
+  State(pool): State<sqlx::SqlitePool>,
+  id: Result<Path<i64>, PathRejection>,
+  params: Result<Query<DeleteParams>, QueryRejection>,
+) -> Result<Json<Node<Value>>, ServerError> {
+  let Path(id) = id?;
+  let Query(params) = params?;
+  let node = if params.soft {
+    soft_delete_node(&pool, id).await?
+  } else {
+    delete_node(&pool, id).await?
+  };
+  match node {
+    Some(node) => Ok(Json(node)),
+    None => Err(ServerError::not_found("node", id)),
+  }
+}
+
+pub async fn restore(
+  State(pool): State<sqlx::SqlitePool>,
+  id: Result<Path<i64>, PathRejection>,
+) -> Result<Json<Node<Value>>, ServerError> {
+  let Path(id) = id?;
+  match restore_node(&pool, id).await? {
+    Some(node) => Ok(Json(node)),
+    None => Err(ServerError::not_found("deleted node", id)),
+  }
+}
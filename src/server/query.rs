@@ -0,0 +1,116 @@
+use axum::{
+  extract::{
+    rejection::{JsonRejection, QueryRejection},
+    State,
+  },
+  response::{IntoResponse, Response},
+  Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::query::ScopedQuery;
+
+use super::error::{ErrorCode, ServerError};
+
+const DEFAULT_LIMIT: u64 = 100;
+const MAX_LIMIT: u64 = 1000;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryKind {
+  Nodes,
+  Edges,
+  #[default]
+  NodeEdges,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct QueryParams {
+  #[serde(default)]
+  pub kind: QueryKind,
+  #[serde(default)]
+  pub limit: Option<u64>,
+  #[serde(default)]
+  pub offset: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Page<T> {
+  pub items: Vec<T>,
+  pub limit: u64,
+  pub offset: u64,
+  pub next_offset: Option<u64>,
+}
+
+impl<T> Page<T> {
+  fn new(mut items: Vec<T>, limit: u64, offset: u64) -> Self {
+    // one extra row is fetched to tell whether there is a next page
+    let has_next = items.len() as u64 > limit;
+    items.truncate(limit as usize);
+    Self {
+      items,
+      limit,
+      offset,
+      next_offset: if has_next { Some(offset + limit) } else { None },
+    }
+  }
+}
+
+pub async fn query(
+  State(pool): State<sqlx::SqlitePool>,
+  params: Result<axum::extract::Query<QueryParams>, QueryRejection>,
+  body: Result<Json<ScopedQuery>, JsonRejection>,
+) -> Result<Response, ServerError> {
+  let axum::extract::Query(params) = params?;
+  let Json(query) = body?;
+  let limit = params
+    .limit
+    .or(query.limit)
+    .unwrap_or(DEFAULT_LIMIT)
+    .min(MAX_LIMIT);
+  // an empty page would never advance `next_offset`
+  if limit == 0 {
+    return Err(ServerError::new(
+      ErrorCode::BadRequest,
+      "limit must be at least 1",
+    ));
+  }
+  let offset = params.offset.or(query.offset).unwrap_or(0);
+  if i64::try_from(offset).is_err() {
+    return Err(ServerError::new(
+      ErrorCode::BadRequest,
+      format!("offset {offset} is out of range"),
+    ));
+  }
+  let query = query.limit(limit + 1).offset(offset);
+  Ok(match params.kind {
+    QueryKind::Nodes => Json(Page::new(
+      query
+        .nodes::<Value>(&pool)
+        .await
+        .map_err(ServerError::query)?,
+      limit,
+      offset,
+    ))
+    .into_response(),
+    QueryKind::Edges => Json(Page::new(
+      query
+        .edges::<Value>(&pool)
+        .await
+        .map_err(ServerError::query)?,
+      limit,
+      offset,
+    ))
+    .into_response(),
+    QueryKind::NodeEdges => Json(Page::new(
+      query
+        .node_edges::<Value, Value, Value>(&pool)
+        .await
+        .map_err(ServerError::query)?,
+      limit,
+      offset,
+    ))
+    .into_response(),
+  })
+}
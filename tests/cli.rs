@@ -0,0 +1,159 @@
+use std::{
+  io::Write,
+  path::Path,
+  process::{Command, Output, Stdio},
+};
+
+use serde_json::Value;
+
+fn hypergraphsql(database: &Path, args: &[&str], stdin: Option<&str>) -> Output {
+  let mut child = Command::new(env!("CARGO_BIN_EXE_hypergraphsql"))
+    .arg("--database")
+    .arg(database)
+    .arg("--create")
+    .args(args)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .expect("failed to run hypergraphsql");
+  let mut child_stdin = child.stdin.take().unwrap();
+  if let Some(input) = stdin {
+    child_stdin.write_all(input.as_bytes()).unwrap();
+  }
+  drop(child_stdin);
+  child.wait_with_output().unwrap()
+}
+
+fn json(output: &Output) -> Value {
+  assert!(
+    output.status.success(),
+    "{}",
+    String::from_utf8_lossy(&output.stderr)
+  );
+  serde_json::from_slice(&output.stdout).expect("failed to parse output")
+}
+
+#[test]
+fn test_cli_arguments() {
+  let temp_dir = tempfile::tempdir().unwrap();
+  let database = temp_dir.path().join("test.db");
+
+  let output = hypergraphsql(&database, &["query", "--kind", "vertices"], None);
+  assert!(!output.status.success());
+  assert!(String::from_utf8_lossy(&output.stderr).contains("invalid value 'vertices'"));
+
+  let output = hypergraphsql(&database, &["node", "get"], None);
+  assert!(!output.status.success());
+
+  let node = json(&hypergraphsql(
+    &database,
+    &["node", "create", "user", r#"{"name": "a"}"#],
+    None,
+  ));
+  assert_eq!(node["uri"], "user");
+  assert_eq!(node["data"]["name"], "a");
+
+  let node = json(&hypergraphsql(
+    &database,
+    &["node", "create", "user", "-"],
+    Some(r#"{"name": "b"}"#),
+  ));
+  assert_eq!(node["data"]["name"], "b");
+
+  let output = hypergraphsql(&database, &["node", "get", "42"], None);
+  assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_query() {
+  let temp_dir = tempfile::tempdir().unwrap();
+  let database = temp_dir.path().join("test.db");
+
+  let a = json(&hypergraphsql(
+    &database,
+    &["node", "create", "user", r#"{"name": "a"}"#],
+    None,
+  ));
+  let b = json(&hypergraphsql(
+    &database,
+    &["node", "create", "user", r#"{"name": "b"}"#],
+    None,
+  ));
+  json(&hypergraphsql(
+    &database,
+    &[
+      "edge",
+      "create",
+      &a["id"].to_string(),
+      &b["id"].to_string(),
+      "follows",
+    ],
+    None,
+  ));
+
+  let query = r#"{"node.data": {"name": {"eq": "a"}}}"#;
+  let query_path = temp_dir.path().join("query.json");
+  std::fs::write(&query_path, query).unwrap();
+  let nodes = json(&hypergraphsql(
+    &database,
+    &["query", query_path.to_str().unwrap(), "--kind", "nodes"],
+    None,
+  ));
+  assert_eq!(nodes.as_array().unwrap().len(), 1);
+  assert_eq!(nodes[0]["id"], a["id"]);
+
+  let node_edges = json(&hypergraphsql(
+    &database,
+    &["query", "-"],
+    Some(r#"{"edge.uri": {"eq": "follows"}}"#),
+  ));
+  assert_eq!(node_edges.as_array().unwrap().len(), 1);
+  assert_eq!(node_edges[0]["from_node"]["id"], a["id"]);
+  assert_eq!(node_edges[0]["to_node"]["id"], b["id"]);
+
+  json(&hypergraphsql(
+    &database,
+    &["node", "delete", "--soft", &a["id"].to_string()],
+    None,
+  ));
+  let nodes = json(&hypergraphsql(
+    &database,
+    &["query", "--kind", "nodes"],
+    Some(query),
+  ));
+  assert_eq!(nodes.as_array().unwrap().len(), 0);
+  let nodes = json(&hypergraphsql(
+    &database,
+    &["query", "--kind", "nodes", "--with-deleted"],
+    Some(query),
+  ));
+  assert_eq!(nodes.as_array().unwrap().len(), 1);
+}
+
+// a query file is read from its path even when the path is not UTF-8
+#[cfg(unix)]
+#[test]
+fn test_cli_query_non_utf8_path() {
+  use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+  let temp_dir = tempfile::tempdir().unwrap();
+  let database = temp_dir.path().join("test.db");
+  json(&hypergraphsql(
+    &database,
+    &["node", "create", "user", r#"{"name": "a"}"#],
+    None,
+  ));
+
+  let query_path = temp_dir.path().join(OsStr::from_bytes(b"query-\xff.json"));
+  std::fs::write(&query_path, r#"{"node.uri": {"eq": "user"}}"#).unwrap();
+  let output = Command::new(env!("CARGO_BIN_EXE_hypergraphsql"))
+    .arg("--database")
+    .arg(&database)
+    .args(["query", "--kind", "nodes"])
+    .arg(&query_path)
+    .stdin(Stdio::null())
+    .output()
+    .unwrap();
+  assert_eq!(json(&output).as_array().unwrap().len(), 1);
+}
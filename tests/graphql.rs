@@ -0,0 +1,100 @@
+use hypergraphsql::{graphql::*, *};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_graphql() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+  let a = create_node(&pool, "user", json!({"info": {"name": "a", "age": 30}})).await?;
+  let b = create_node(&pool, "user", json!({"info": {"name": "b", "age": 20}})).await?;
+  let c = create_node(&pool, "user", json!({"info": {"name": "c'd", "age": 10}})).await?;
+  create_edge(&pool, &a, &b, "follows", Some(json!({"since": 2020}))).await?;
+  create_edge(&pool, &a, &c, "follows", Some(json!({"since": 2024}))).await?;
+  create_edge(&pool, &b, &c, "follows", None::<serde_json::Value>).await?;
+
+  let schema = GraphQLSchemaBuilder::new()
+    .node("user", "User")
+    .edge("follows", "Follows", "user", "user")
+    .build(pool.clone())
+    .unwrap();
+
+  let response = schema
+    .execute(format!(
+      r#"{{
+        user(id: {}) {{
+          name: data(path: "info.name")
+          follows(where: {{ data: [{{ path: "since", gt: 2021 }}] }}) {{ to {{ id }} }}
+          followsIncoming {{ from {{ id }} }}
+        }}
+      }}"#,
+      b.id
+    ))
+    .await;
+  assert!(response.errors.is_empty(), "{:?}", response.errors);
+  assert_eq!(
+    response.data.into_json().unwrap(),
+    json!({"user": {"name": "b", "follows": [], "followsIncoming": [{"from": {"id": a.id}}]}})
+  );
+
+  let response = schema
+    .execute(
+      r#"{
+        userList(where: { data: [{ path: "info.age", gte: 20 }] }, limit: 1, offset: 1) { id }
+        named: userList(where: { data: [{ path: "info.name", eq: "c'd" }] }) { id followsIncoming { id } }
+        followsList(where: { fromNodeId: { eq: 1 } }) { id from { id } to { id } }
+        prefixed: userList(where: { data: [{ path: "info.name", startsWith: "c" }, { path: "info.nick", exists: false }] }) { id }
+        recent: userList(where: { createdAt: { gte: "-1h", lte: "now" }, updatedAt: { within: "1d" } }) { id }
+        old: userList(where: { createdAt: { lt: "2000-01-01" } }) { id }
+      }"#,
+    )
+    .await;
+  assert!(response.errors.is_empty(), "{:?}", response.errors);
+  let data = response.data.into_json().unwrap();
+  assert_eq!(data["userList"], json!([{"id": b.id}]));
+  assert_eq!(data["named"][0]["id"], json!(c.id));
+  assert_eq!(
+    data["named"][0]["followsIncoming"]
+      .as_array()
+      .unwrap()
+      .len(),
+    2
+  );
+  assert_eq!(data["followsList"].as_array().unwrap().len(), 2);
+  assert_eq!(data["followsList"][0]["from"]["id"], json!(a.id));
+  assert_eq!(data["prefixed"], json!([{"id": c.id}]));
+  assert_eq!(data["recent"].as_array().unwrap().len(), 3);
+  assert_eq!(data["old"], json!([]));
+
+  // endpoints that are soft deleted resolve to null
+  sqlx::query("UPDATE nodes SET deleted_at = 1 WHERE id = $1;")
+    .bind(c.id)
+    .execute(&pool)
+    .await?;
+  let response = schema
+    .execute(format!(
+      r#"{{ user(id: {}) {{ id }} followsList(where: {{ fromNodeId: {{ eq: {} }} }}) {{ to {{ id }} }} }}"#,
+      c.id, a.id
+    ))
+    .await;
+  assert!(response.errors.is_empty(), "{:?}", response.errors);
+  assert_eq!(
+    response.data.into_json().unwrap(),
+    json!({"user": null, "followsList": [{"to": {"id": b.id}}, {"to": null}]})
+  );
+
+  // list fields are paginated even without a limit
+  for i in 0..1000 {
+    create_node(&pool, "user", json!({"info": {"name": format!("u{i}")}})).await?;
+  }
+  let response = schema
+    .execute("{ userList { id } all: userList(limit: 5000) { id } }")
+    .await;
+  assert!(response.errors.is_empty(), "{:?}", response.errors);
+  let data = response.data.into_json().unwrap();
+  assert_eq!(data["userList"].as_array().unwrap().len(), 100);
+  assert_eq!(data["all"].as_array().unwrap().len(), 1000);
+
+  Ok(())
+}
@@ -0,0 +1,169 @@
+use axum::{
+  body::Body,
+  http::{Method, Request, StatusCode},
+  Router,
+};
+use http_body_util::BodyExt;
+use hypergraphsql::{server::*, *};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+async fn send(
+  router: &Router,
+  method: Method,
+  uri: &str,
+  body: Option<Value>,
+) -> (StatusCode, Value) {
+  let request = Request::builder()
+    .method(method)
+    .uri(uri)
+    .header("content-type", "application/json")
+    .body(match body {
+      Some(body) => Body::from(body.to_string()),
+      None => Body::empty(),
+    })
+    .unwrap();
+  let response = router.clone().oneshot(request).await.unwrap();
+  let status = response.status();
+  let bytes = response.into_body().collect().await.unwrap().to_bytes();
+  let body = if bytes.is_empty() {
+    Value::Null
+  } else {
+    serde_json::from_slice(&bytes).unwrap()
+  };
+  (status, body)
+}
+
+#[tokio::test]
+async fn test_server() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+  let router = router(pool);
+
+  let mut ids = Vec::new();
+  for name in ["a", "b", "c"] {
+    let (status, node) = send(
+      &router,
+      Method::POST,
+      "/nodes",
+      Some(json!({"uri": "user", "data": {"info": {"name": name}}})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    ids.push(node["id"].as_i64().unwrap());
+  }
+
+  let (status, edge) = send(
+    &router,
+    Method::POST,
+    "/edges",
+    Some(json!({"from_node_id": ids[0], "to_node_id": ids[1], "uri": "follows"})),
+  )
+  .await;
+  assert_eq!(status, StatusCode::CREATED);
+  let (status, error) = send(
+    &router,
+    Method::POST,
+    "/edges",
+    Some(json!({"from_node_id": ids[0], "to_node_id": ids[1], "uri": "follows"})),
+  )
+  .await;
+  assert_eq!(status, StatusCode::CONFLICT);
+  assert_eq!(error["code"], "conflict");
+
+  let (status, node) = send(
+    &router,
+    Method::PUT,
+    &format!("/nodes/{}", ids[2]),
+    Some(json!({"data": {"info": {"name": "d"}}})),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(node["data"]["info"]["name"], "d");
+
+  let (status, page) = send(
+    &router,
+    Method::POST,
+    "/query?kind=nodes&limit=2",
+    Some(json!({"node.uri": {"eq": "user"}})),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(page["items"].as_array().unwrap().len(), 2);
+  assert_eq!(page["next_offset"], 2);
+  let (_, page) = send(
+    &router,
+    Method::POST,
+    "/query?kind=nodes&limit=2&offset=2",
+    Some(json!({"node.uri": {"eq": "user"}})),
+  )
+  .await;
+  assert_eq!(page["items"][0]["data"]["info"]["name"], "d");
+  assert_eq!(page["next_offset"], Value::Null);
+
+  let (_, page) = send(&router, Method::POST, "/query", Some(json!({}))).await;
+  assert_eq!(page["items"][0]["edge"]["id"], edge["id"]);
+
+  // node fields do not exist on edges
+  let (status, error) = send(
+    &router,
+    Method::POST,
+    "/query?kind=edges",
+    Some(json!({"node.uri": {"eq": "user"}})),
+  )
+  .await;
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  assert_eq!(error["code"], "bad_request");
+  let (status, error) = send(
+    &router,
+    Method::POST,
+    "/query?kind=nodes",
+    Some(json!({"node.created_at": {"gt": "yesterday"}})),
+  )
+  .await;
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  assert_eq!(error["code"], "bad_request");
+  let (status, _) = send(
+    &router,
+    Method::POST,
+    &format!("/query?offset={}", u64::MAX),
+    Some(json!({})),
+  )
+  .await;
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  let (status, error) = send(&router, Method::POST, "/query?limit=0", Some(json!({}))).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  assert_eq!(error["code"], "bad_request");
+
+  let (status, _) = send(
+    &router,
+    Method::DELETE,
+    &format!("/nodes/{}?soft=true", ids[0]),
+    None,
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK);
+  let (status, edge) = send(
+    &router,
+    Method::GET,
+    &format!("/edges/{}", edge["id"]),
+    None,
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK);
+  assert!(edge["deleted_at"].is_string());
+
+  let (status, error) = send(&router, Method::GET, "/nodes/999", None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+  assert_eq!(error["code"], "not_found");
+  let (status, error) = send(&router, Method::POST, "/nodes", Some(json!({"uri": 1}))).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  assert_eq!(error["code"], "bad_request");
+
+  let (_, counts) = send(&router, Method::GET, "/uris", None).await;
+  assert_eq!(counts["nodes"][0]["count"], 2);
+
+  Ok(())
+}
@@ -1,3 +1,6 @@
+// the original query example keeps its `get(0)` and `&vec![..]` spelling
+#![allow(clippy::get_first, clippy::useless_vec)]
+
 use hypergraphsql::*;
 use serde::{Deserialize, Serialize};
 
@@ -59,3 +62,1775 @@ async fn test_query() -> sqlx::Result<()> {
 
   Ok(())
 }
+
+#[tokio::test]
+async fn test_soft_delete() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let user_a = create_node(&pool, NODE_USER_URI, User::new("a")).await?;
+  let user_b = create_node(&pool, NODE_USER_URI, User::new("b")).await?;
+  let edge = create_edge(&pool, &user_a, &user_b, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+
+  let edges = Query::default().edges::<serde_json::Value>(&pool).await?;
+  assert_eq!(
+    edges.iter().map(|edge| edge.id).collect::<Vec<_>>(),
+    [edge.id]
+  );
+
+  let deleted = soft_delete_node::<User>(&pool, user_a.id).await?.unwrap();
+  assert!(deleted.deleted_at.is_some());
+
+  let query = serde_json::from_value::<Query>(serde_json::json!({
+    "node.uri": {"eq": NODE_USER_URI}
+  }))
+  .expect("failed to parse Query JSON");
+  assert_eq!(query.nodes::<User>(&pool).await?.len(), 1);
+  assert_eq!(
+    query
+      .clone()
+      .with_deleted(true)
+      .nodes::<User>(&pool)
+      .await?
+      .len(),
+    2
+  );
+  assert_eq!(
+    Query::default()
+      .node_edges::<User, User, Follows>(&pool)
+      .await?
+      .len(),
+    0
+  );
+
+  restore_node::<User>(&pool, user_a.id).await?.unwrap();
+  assert_eq!(query.nodes::<User>(&pool).await?.len(), 2);
+  assert_eq!(
+    Query::default()
+      .node_edges::<User, User, Follows>(&pool)
+      .await?
+      .len(),
+    1
+  );
+
+  // a newer edge between the same nodes keeps the old one deleted
+  soft_delete_edge::<Follows>(&pool, edge.id).await?.unwrap();
+  create_edge(&pool, &user_a, &user_b, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+  assert!(restore_edge::<Follows>(&pool, edge.id).await?.is_none());
+
+  soft_delete_node::<User>(&pool, user_b.id).await?.unwrap();
+  assert!(matches!(
+    create_edge(&pool, &user_b, &user_a, EDGE_FOLLOWS_URI, None::<Follows>).await,
+    Err(sqlx::Error::RowNotFound)
+  ));
+  restore_node::<User>(&pool, user_b.id).await?.unwrap();
+
+  // with soft delete on, deletes only tombstone and can be restored
+  enable_soft_delete(&pool).await?;
+  assert!(soft_delete_enabled(&pool).await?);
+  let deleted = delete_nodes_by_uri::<User>(&pool, NODE_USER_URI).await?;
+  assert_eq!(deleted.len(), 2);
+  assert!(deleted.iter().all(|node| node.deleted_at.is_some()));
+  assert!(get_node::<User>(&pool, user_a.id).await?.is_none());
+  assert!(get_node_with_deleted::<User>(&pool, user_a.id)
+    .await?
+    .unwrap()
+    .deleted_at
+    .is_some());
+  restore_node::<User>(&pool, user_a.id).await?.unwrap();
+  assert!(get_node::<User>(&pool, user_a.id).await?.is_some());
+  delete_node::<User>(&pool, user_a.id).await?.unwrap();
+  assert!(get_node_with_deleted::<User>(&pool, user_a.id)
+    .await?
+    .is_some());
+  disable_soft_delete(&pool).await?;
+
+  let acme = Namespace::new(&pool, "acme");
+  let other = acme.create_node(NODE_USER_URI, User::new("c")).await?;
+  assert!(soft_delete_nodes_by_uri::<User>(&pool, NODE_USER_URI)
+    .await?
+    .iter()
+    .all(|node| node.id == other.id));
+  restore_node::<User>(&pool, other.id).await?.unwrap();
+  assert!(Namespace::new(&pool, "globex")
+    .soft_delete_nodes_by_uri::<User>(NODE_USER_URI)
+    .await?
+    .is_empty());
+  assert_eq!(
+    acme
+      .soft_delete_nodes_by_uri::<User>(NODE_USER_URI)
+      .await?
+      .len(),
+    1
+  );
+
+  let purged = purge_deleted(&pool, chrono::Utc::now()).await?;
+  assert_eq!(purged.nodes, 3);
+  assert_eq!(purged.edges, 2);
+  assert_eq!(
+    query.with_deleted(true).nodes::<User>(&pool).await?.len(),
+    0
+  );
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_history() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+  enable_history(&pool).await?;
+  assert!(history_enabled(&pool).await?);
+
+  let user = create_node(&pool, NODE_USER_URI, User::new("a")).await?;
+  tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+  let before_update = chrono::Utc::now();
+  tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+  update_node(&pool, user.id, User::new("b")).await?;
+
+  let history = node_history::<User>(&pool, user.id).await?;
+  assert_eq!(history.len(), 1);
+  assert_eq!(history[0].node.data.info.name, "a");
+
+  let query = serde_json::from_value::<Query>(serde_json::json!({
+    "node.id": {"eq": user.id}
+  }))
+  .expect("failed to parse Query JSON");
+  assert_eq!(query.nodes::<User>(&pool).await?[0].data.info.name, "b");
+  let past = query
+    .clone()
+    .as_of(before_update)
+    .nodes::<User>(&pool)
+    .await?;
+  assert_eq!(past.len(), 1);
+  assert_eq!(past[0].data.info.name, "a");
+
+  soft_delete_node::<User>(&pool, user.id).await?.unwrap();
+  tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+  let while_deleted = chrono::Utc::now();
+  tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+  restore_node::<User>(&pool, user.id).await?.unwrap();
+  assert_eq!(query.nodes::<User>(&pool).await?.len(), 1);
+  assert_eq!(
+    query
+      .clone()
+      .as_of(while_deleted)
+      .nodes::<User>(&pool)
+      .await?
+      .len(),
+    0
+  );
+  let deleted = query
+    .clone()
+    .as_of(while_deleted)
+    .with_deleted(true)
+    .nodes::<User>(&pool)
+    .await?;
+  assert_eq!(deleted.len(), 1);
+  assert!(deleted[0].deleted_at.is_some());
+  assert_eq!(node_history::<User>(&pool, user.id).await?.len(), 3);
+
+  delete_node::<User>(&pool, user.id).await?;
+  assert_eq!(query.nodes::<User>(&pool).await?.len(), 0);
+  assert_eq!(node_history::<User>(&pool, user.id).await?.len(), 4);
+  let past = query.as_of(before_update).nodes::<User>(&pool).await?;
+  assert_eq!(past[0].data.info.name, "a");
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_jsonl() -> sqlx::Result<()> {
+  let source_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let target_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+
+  let source = create(&source_path.as_os_str().to_string_lossy(), false).await?;
+  let user_a = create_node(&source, NODE_USER_URI, User::new("a")).await?;
+  let user_b = create_node(&source, NODE_USER_URI, User::new("b")).await?;
+  create_edge(&source, &user_a, &user_b, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+  add_label(&source, user_a.id, "admin").await?;
+  add_label(&source, user_a.id, "staff").await?;
+
+  let mut buffer = Vec::new();
+  let exported = export_jsonl(&source, &mut buffer, None).await?;
+  assert_eq!(exported.nodes, 2);
+  assert_eq!(exported.edges, 1);
+  assert_eq!(exported.labels, 2);
+
+  let mut jsonl = String::from_utf8(buffer).unwrap();
+  jsonl.push_str("{\"type\":\"edge\",\"from_node_id\":42}\n");
+
+  let target = create(&target_path.as_os_str().to_string_lossy(), false).await?;
+  let imported = import_jsonl(&target, jsonl.as_bytes()).await?;
+  assert_eq!(imported.nodes, 2);
+  assert_eq!(imported.edges, 1);
+  assert_eq!(imported.failures.len(), 1);
+  assert_eq!(imported.failures[0].line, 6);
+
+  let related = Query::default()
+    .node_edges::<User, User, Follows>(&target)
+    .await?;
+  assert_eq!(related.len(), 1);
+  assert_eq!(related[0].from_node.data.info.name, "a");
+  assert_eq!(related[0].to_node.data.info.name, "b");
+  assert_eq!(imported.labels, 2);
+  assert_eq!(
+    node_labels(&target, related[0].from_node.id).await?,
+    ["admin", "staff"]
+  );
+  assert!(node_labels(&target, related[0].to_node.id)
+    .await?
+    .is_empty());
+
+  // soft deleted rows stay deleted when exported with their tombstones
+  soft_delete_node::<User>(&source, user_b.id).await?.unwrap();
+  let mut buffer = Vec::new();
+  let filter = ScopedQuery::default().with_deleted(true);
+  export_jsonl(&source, &mut buffer, Some(&filter)).await?;
+  let restored_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let restored = create(&restored_path.as_os_str().to_string_lossy(), false).await?;
+  let imported = import_jsonl(&restored, buffer.as_slice()).await?;
+  assert_eq!((imported.nodes, imported.edges), (2, 1));
+  assert_eq!(
+    Query::default()
+      .count(&restored, QueryTarget::Nodes)
+      .await?,
+    1
+  );
+  assert_eq!(
+    Query::default()
+      .count(&restored, QueryTarget::Edges)
+      .await?,
+    0
+  );
+  assert_eq!(
+    ScopedQuery::default()
+      .with_deleted(true)
+      .count(&restored, QueryTarget::Edges)
+      .await?,
+    1
+  );
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_graph_export() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let user_a = create_node(&pool, NODE_USER_URI, User::new("a & co")).await?;
+  let user_b = create_node(&pool, NODE_USER_URI, User::new("b")).await?;
+  let follows = create_edge(&pool, &user_a, &user_b, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+
+  let loner = create_node(&pool, NODE_USER_URI, User::new("c")).await?;
+  let subgraph = Subgraph::from_query(&pool, &ScopedQuery::default()).await?;
+  assert_eq!(subgraph.nodes.len(), 3);
+  assert_eq!(subgraph.edges.len(), 1);
+
+  let users: ScopedQuery = serde_json::from_value(serde_json::json!({
+    "node.uri": {"eq": NODE_USER_URI}
+  }))
+  .expect("failed to parse query");
+  let subgraph = Subgraph::from_query(&pool, &users).await?;
+  assert_eq!(subgraph.nodes.len(), 3);
+  assert_eq!(
+    subgraph
+      .edges
+      .iter()
+      .map(|edge| edge.id)
+      .collect::<Vec<_>>(),
+    [follows.id]
+  );
+
+  delete_node::<User>(&pool, loner.id).await?;
+  let subgraph = Subgraph::from_query(&pool, &ScopedQuery::default()).await?;
+  assert_eq!(subgraph.nodes.len(), 2);
+  assert_eq!(
+    subgraph
+      .edges
+      .iter()
+      .map(|edge| edge.id)
+      .collect::<Vec<_>>(),
+    [follows.id]
+  );
+
+  let options = GraphExportOptions::default()
+    .node_attribute(Attribute::new("info.name", AttributeType::String).named("name"));
+
+  let mut graphml = Vec::new();
+  write_graphml(&mut graphml, &subgraph, &options)?;
+  let graphml = String::from_utf8(graphml).unwrap();
+  assert!(graphml.contains(r#"<key id="n0" for="node" attr.name="name" attr.type="string"/>"#));
+  assert!(graphml.contains(r#"<data key="n0">a &amp; co</data>"#));
+  assert!(graphml.contains(&format!(
+    r#"<edge id="e1" source="n{}" target="n{}">"#,
+    user_a.id, user_b.id
+  )));
+
+  let mut gexf = Vec::new();
+  write_gexf(&mut gexf, &subgraph, &options)?;
+  let gexf = String::from_utf8(gexf).unwrap();
+  assert!(gexf.contains(r#"<attribute id="0" title="name" type="string"/>"#));
+  assert!(gexf.contains(r#"<attvalue for="0" value="b"/>"#));
+  assert!(gexf.contains(r#"label="follows""#));
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_dot() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let user_a = create_node(&pool, NODE_USER_URI, User::new("a")).await?;
+  let user_b = create_node(&pool, NODE_USER_URI, User::new("\"b\"")).await?;
+  create_edge(&pool, &user_a, &user_b, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+
+  let related = Query::default()
+    .node_edges::<User, User, Follows>(&pool)
+    .await?;
+  let dot = related.to_dot_with(&DotOptions::default().node_label("info.name"));
+  assert!(dot.starts_with("digraph \"G\" {\n"));
+  assert!(dot.contains(&format!("  n{} [label=\"user\\na\"];", user_a.id)));
+  assert!(dot.contains(&format!("  n{} [label=\"user\\n\\\"b\\\"\"];", user_b.id)));
+  assert!(dot.contains(&format!(
+    "  n{} -> n{} [label=\"follows\"];",
+    user_a.id, user_b.id
+  )));
+  assert!(dot.ends_with("}\n"));
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_cypher() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let user_a = create_node(&pool, NODE_USER_URI, User::new("a")).await?;
+  let user_b = create_node(&pool, NODE_USER_URI, User::new("b")).await?;
+  let user_c = create_node(&pool, NODE_USER_URI, User::new("c")).await?;
+  create_edge(&pool, &user_a, &user_b, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+  create_edge(&pool, &user_b, &user_c, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+
+  let pattern = "MATCH (a:user)-[:follows]->(b:user) WHERE a.info.name = 'a' RETURN b"
+    .parse::<Pattern>()
+    .expect("failed to parse pattern");
+  println!("{}", pattern.sql().unwrap());
+  let rows = pattern.rows(&pool).await?;
+  assert_eq!(rows.len(), 1);
+  assert_eq!(rows[0].node("b").unwrap().id, user_b.id);
+  assert!(rows[0].get("a").is_none());
+
+  let rows = "MATCH (a:user)-[e:follows]->(:user)-[:follows]->(c) RETURN a, e, c"
+    .parse::<Pattern>()
+    .expect("failed to parse pattern")
+    .rows(&pool)
+    .await?;
+  assert_eq!(rows.len(), 1);
+  assert_eq!(rows[0].node("a").unwrap().data["info"]["name"], "a");
+  assert_eq!(rows[0].edge("e").unwrap().to_node_id, user_b.id);
+  assert_eq!(rows[0].node("c").unwrap().id, user_c.id);
+
+  let rows = "MATCH (b)<-[:follows]-(a) WHERE b.info.name IN ['b', 'c'] AND NOT a.info.name STARTS WITH 'b' RETURN a"
+    .parse::<Pattern>()
+    .expect("failed to parse pattern")
+    .rows(&pool)
+    .await?;
+  assert_eq!(rows.len(), 1);
+  assert_eq!(rows[0].node("a").unwrap().id, user_a.id);
+
+  let error = "MATCH (a:user)-[:follows]->(b RETURN b"
+    .parse::<Pattern>()
+    .unwrap_err();
+  assert_eq!(error.offset, 30);
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_pattern() -> sqlx::Result<()> {
+  #[derive(Serialize, Deserialize)]
+  struct Post {
+    title: String,
+  }
+
+  #[derive(Deserialize)]
+  struct Row {
+    a: Node<User>,
+    b: Node<User>,
+    p: Node<Post>,
+  }
+
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let user_a = create_node(&pool, NODE_USER_URI, User::new("a")).await?;
+  let user_b = create_node(&pool, NODE_USER_URI, User::new("b")).await?;
+  let user_c = create_node(&pool, NODE_USER_URI, User::new("c")).await?;
+  let post = create_node(
+    &pool,
+    "post",
+    Post {
+      title: "hello".to_owned(),
+    },
+  )
+  .await?;
+  create_edge(&pool, &user_a, &user_b, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+  create_edge(&pool, &user_b, &user_c, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+  create_edge(&pool, &user_c, &user_a, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+  create_edge(&pool, &user_b, &post, "authored", None::<Follows>).await?;
+
+  let chain = Pattern::new()
+    .node("a", Some(NODE_USER_URI))
+    .edge(None, "a", "b", Some(EDGE_FOLLOWS_URI))
+    .edge(None, "b", "p", Some("authored"))
+    .filter(
+      "a",
+      "data",
+      serde_json::from_value(serde_json::json!({"info.name": {"eq": "a"}}))
+        .expect("failed to parse QueryExpr JSON"),
+    );
+  let rows = chain.typed_rows::<Row>(&pool).await?;
+  assert_eq!(rows.len(), 1);
+  assert_eq!(rows[0].a.data.info.name, "a");
+  assert_eq!(rows[0].b.data.info.name, "b");
+  assert_eq!(rows[0].p.data.title, "hello");
+
+  let triangle = Pattern::new()
+    .edge(Some("ab"), "a", "b", Some(EDGE_FOLLOWS_URI))
+    .edge(Some("bc"), "b", "c", Some(EDGE_FOLLOWS_URI))
+    .edge(Some("ca"), "c", "a", Some(EDGE_FOLLOWS_URI))
+    .returning(&["a"]);
+  assert_eq!(triangle.rows(&pool).await?.len(), 3);
+
+  let star = Pattern::new()
+    .edge(None, "b", "x", None)
+    .edge(None, "b", "y", None)
+    .returning(&["b"]);
+  let rows = star.rows(&pool).await?;
+  assert_eq!(rows.len(), 2);
+  assert!(rows
+    .iter()
+    .all(|row| row.node("b").unwrap().id == user_b.id));
+
+  let unknown =
+    Pattern::new()
+      .node("a", None)
+      .filter("a", "from_node_id", QueryExpr::Value(QueryValue::Null));
+  assert!(matches!(
+    unknown.sql(),
+    Err(PatternError::UnknownColumn(_, _))
+  ));
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_get_and_uri_counts() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let user_a = create_node(&pool, NODE_USER_URI, User::new("a")).await?;
+  let user_b = create_node(&pool, NODE_USER_URI, User::new("b")).await?;
+  let edge = create_edge(&pool, &user_a, &user_b, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+
+  let node = get_node::<User>(&pool, user_a.id).await?.unwrap();
+  assert_eq!(node.data.info.name, "a");
+  assert!(get_node::<User>(&pool, user_b.id + 1).await?.is_none());
+  assert_eq!(
+    get_edge::<Follows>(&pool, edge.id)
+      .await?
+      .unwrap()
+      .to_node_id,
+    user_b.id
+  );
+
+  soft_delete_edge::<Follows>(&pool, edge.id).await?;
+  let counts = uri_counts(&pool).await?;
+  assert_eq!(
+    counts.nodes,
+    vec![UriCount {
+      uri: NODE_USER_URI.to_owned(),
+      count: 2
+    }]
+  );
+  assert!(counts.edges.is_empty());
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_stream() -> sqlx::Result<()> {
+  use futures_util::TryStreamExt;
+
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  // more rows than a single stream batch
+  sqlx::query(
+    r#"WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 2500)
+    INSERT INTO nodes (uri, data) SELECT ?, json_object('info', json_object('name', 'n' || i)) FROM n"#,
+  )
+  .bind(NODE_USER_URI)
+  .execute(&pool)
+  .await?;
+  let user_a = create_node(&pool, NODE_USER_URI, User::new("a")).await?;
+  let user_b = create_node(&pool, NODE_USER_URI, User::new("b")).await?;
+  create_edge(&pool, &user_a, &user_b, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+
+  let nodes: Vec<Node<User>> = Query::default()
+    .nodes_stream::<User>(&pool)
+    .try_collect()
+    .await?;
+  assert_eq!(nodes.len(), 2502);
+  assert!(nodes.windows(2).all(|w| w[0].id < w[1].id));
+
+  let query: Query =
+    serde_json::from_str(r#"{"node.id": {"gt": 1000}}"#).expect("failed to parse query");
+  let ids: Vec<i64> = query
+    .limit(10)
+    .offset(5)
+    .nodes_stream::<User>(&pool)
+    .map_ok(|node| node.id)
+    .try_collect()
+    .await?;
+  assert_eq!(ids, (1006..1016).collect::<Vec<_>>());
+
+  let node_edges: Vec<NodeEdge<User, User, Follows>> = Query::default()
+    .node_edges_stream(&pool)
+    .try_collect()
+    .await?;
+  assert_eq!(node_edges.len(), 1);
+  assert_eq!(node_edges[0].from_node.data.info.name, "a");
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_count_and_exists() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let user_a = create_node(&pool, NODE_USER_URI, User::new("a")).await?;
+  let user_b = create_node(&pool, NODE_USER_URI, User::new("b")).await?;
+  let user_c = create_node(&pool, NODE_USER_URI, User::new("c")).await?;
+  create_edge(&pool, &user_b, &user_a, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+  let edge = create_edge(&pool, &user_c, &user_a, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+
+  let followers: Query = serde_json::from_str(&format!(
+    r#"{{"edge.uri": {{"eq": "{EDGE_FOLLOWS_URI}"}}, "to_node.id": {{"eq": {}}}}}"#,
+    user_a.id
+  ))
+  .expect("failed to parse query");
+  assert_eq!(followers.count(&pool, QueryTarget::NodeEdges).await?, 2);
+  assert_eq!(
+    followers
+      .clone()
+      .limit(1)
+      .count(&pool, QueryTarget::NodeEdges)
+      .await?,
+    1
+  );
+  assert!(followers.exists(&pool, QueryTarget::NodeEdges).await?);
+  assert_eq!(Query::default().count(&pool, QueryTarget::Nodes).await?, 3);
+
+  soft_delete_edge::<Follows>(&pool, edge.id).await?;
+  assert_eq!(followers.count(&pool, QueryTarget::NodeEdges).await?, 1);
+  assert_eq!(
+    followers
+      .clone()
+      .with_deleted(true)
+      .count(&pool, QueryTarget::NodeEdges)
+      .await?,
+    2
+  );
+
+  let following: Query = serde_json::from_str(&format!(
+    r#"{{"edge.from_node_id": {{"eq": {}}}}}"#,
+    user_a.id
+  ))
+  .expect("failed to parse query");
+  assert!(!following.exists(&pool, QueryTarget::Edges).await?);
+  assert_eq!(Query::default().count(&pool, QueryTarget::Edges).await?, 1);
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_update_and_delete_by_query() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let user_a = create_node(&pool, NODE_USER_URI, User::new("a")).await?;
+  let user_b = create_node(&pool, NODE_USER_URI, User::new("b")).await?;
+  let user_c = create_node(&pool, NODE_USER_URI, User::new("c")).await?;
+  create_edge(&pool, &user_a, &user_b, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+  create_edge(&pool, &user_c, &user_b, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+
+  let not_a: Query = serde_json::from_str(r#"{"node.data": {"info.name": {"neq": "a"}}}"#)
+    .expect("failed to parse query");
+  let nodes = not_a
+    .update_nodes::<_, serde_json::Value>(
+      &pool,
+      DataUpdate::Patch(serde_json::json!({"info": {"active": false}})),
+    )
+    .await?;
+  assert_eq!(nodes.len(), 2);
+  let node = get_node::<serde_json::Value>(&pool, user_b.id)
+    .await?
+    .unwrap();
+  assert_eq!(
+    node.data,
+    serde_json::json!({"info": {"name": "b", "active": false}})
+  );
+  assert_eq!(
+    get_node::<serde_json::Value>(&pool, user_a.id)
+      .await?
+      .unwrap()
+      .data,
+    serde_json::json!({"info": {"name": "a"}})
+  );
+
+  let edges = Query::default()
+    .update_edges::<_, serde_json::Value>(&pool, DataUpdate::Patch(serde_json::json!({"w": 1})))
+    .await?;
+  assert_eq!(edges.len(), 2);
+  assert!(edges
+    .iter()
+    .all(|edge| edge.data == Some(serde_json::json!({"w": 1}))));
+
+  let from_c: Query = serde_json::from_str(&format!(
+    r#"{{"edge.from_node_id": {{"eq": {}}}}}"#,
+    user_c.id
+  ))
+  .expect("failed to parse query");
+  let deleted = from_c.delete_edges::<serde_json::Value>(&pool).await?;
+  assert_eq!(deleted.len(), 1);
+  assert_eq!(Query::default().count(&pool, QueryTarget::Edges).await?, 1);
+
+  let replaced = not_a
+    .update_nodes::<_, User>(&pool, DataUpdate::Replace(User::new("x")))
+    .await?;
+  assert!(replaced.iter().all(|node| node.data.info.name == "x"));
+  let deleted = not_a.delete_nodes::<User>(&pool).await?;
+  assert_eq!(deleted.len(), 2);
+  assert_eq!(Query::default().count(&pool, QueryTarget::Nodes).await?, 1);
+  // edges of deleted nodes cascade
+  assert_eq!(Query::default().count(&pool, QueryTarget::Edges).await?, 0);
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_neighbors_and_degrees() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let user_a = create_node(&pool, NODE_USER_URI, User::new("a")).await?;
+  let user_b = create_node(&pool, NODE_USER_URI, User::new("b")).await?;
+  let user_c = create_node(&pool, NODE_USER_URI, User::new("c")).await?;
+  create_edge(&pool, &user_a, &user_b, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+  create_edge(&pool, &user_a, &user_c, "blocks", None::<Follows>).await?;
+  let edge = create_edge(&pool, &user_c, &user_a, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+
+  let following = out_neighbors::<User>(&pool, user_a.id, Some(EDGE_FOLLOWS_URI)).await?;
+  assert_eq!(following.len(), 1);
+  assert_eq!(following[0].data.info.name, "b");
+  assert_eq!(
+    out_neighbors::<User>(&pool, user_a.id, None).await?.len(),
+    2
+  );
+  let followers = in_neighbors::<User>(&pool, user_a.id, Some(EDGE_FOLLOWS_URI)).await?;
+  assert_eq!(followers[0].id, user_c.id);
+
+  assert_eq!(
+    degree(&pool, user_a.id, Direction::Outgoing, None).await?,
+    2
+  );
+  assert_eq!(
+    degree(&pool, user_a.id, Direction::Both, Some(EDGE_FOLLOWS_URI)).await?,
+    2
+  );
+
+  soft_delete_edge::<Follows>(&pool, edge.id).await?;
+  assert!(in_neighbors::<User>(&pool, user_a.id, None)
+    .await?
+    .is_empty());
+  let degrees = degrees(&pool, &[user_a.id, user_b.id, user_c.id + 1], None).await?;
+  assert_eq!(degrees.len(), 2);
+  assert_eq!(
+    degrees[&user_a.id],
+    Degree {
+      outgoing: 2,
+      incoming: 0
+    }
+  );
+  assert_eq!(
+    degrees[&user_b.id],
+    Degree {
+      outgoing: 0,
+      incoming: 1
+    }
+  );
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_algo() -> sqlx::Result<()> {
+  use hypergraphsql::algo::*;
+
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let mut users = Vec::new();
+  for name in ["a", "b", "c", "d", "e"] {
+    users.push(create_node(&pool, NODE_USER_URI, User::new(name)).await?);
+  }
+  // a -> b -> c -> a is a cycle, c -> d, e -> d and a blocked edge outside the projection
+  for (from, to) in [(0, 1), (1, 2), (2, 0), (2, 3), (4, 3)] {
+    create_edge(
+      &pool,
+      &users[from],
+      &users[to],
+      EDGE_FOLLOWS_URI,
+      None::<Follows>,
+    )
+    .await?;
+  }
+  create_edge(&pool, &users[3], &users[4], "blocks", None::<Follows>).await?;
+
+  let query: ScopedQuery =
+    serde_json::from_str(r#"{"edge.uri": {"eq": "follows"}}"#).expect("failed to parse query");
+  let graph = Graph::load(&pool, &query).await?;
+  assert_eq!(graph.node_count(), 5);
+  assert_eq!(graph.edge_count(), 5);
+
+  let ranks = pagerank(&graph, PageRankOptions::default());
+  assert!((ranks.values().sum::<f64>() - 1.0).abs() < 1e-6);
+  assert!(ranks[&users[3].id] > ranks[&users[4].id]);
+
+  let weak = weakly_connected_components(&graph);
+  assert!(users.iter().all(|user| weak[&user.id] == 0));
+  let strong = strongly_connected_components(&graph);
+  assert_eq!(strong[&users[0].id], strong[&users[1].id]);
+  assert_eq!(strong[&users[0].id], strong[&users[2].id]);
+  assert_ne!(strong[&users[0].id], strong[&users[3].id]);
+  assert_ne!(strong[&users[3].id], strong[&users[4].id]);
+
+  let degrees = degree_centrality(&graph, Direction::Incoming);
+  assert_eq!(degrees[&users[3].id], 0.5);
+  let betweenness = betweenness_centrality(&graph);
+  // c lies on the only shortest paths a -> d, b -> d and b -> a
+  assert_eq!(betweenness[&users[2].id], 3.0);
+  assert_eq!(betweenness[&users[3].id], 0.0);
+
+  let graph = Graph::from_edges([(1, 2), (2, 3), (3, 1), (3, 4), (4, 1), (1, 1)]);
+  let triangles = triangle_count(&graph);
+  assert_eq!(triangles.total, 2);
+  assert_eq!(triangles.per_node[&1], 2);
+  assert_eq!(triangles.per_node[&2], 1);
+
+  assert_eq!(write_scores(&pool, &ranks, "scores.pagerank").await?, 5);
+  let node = get_node::<serde_json::Value>(&pool, users[3].id)
+    .await?
+    .unwrap();
+  assert_eq!(node.data["info"]["name"], "d");
+  assert_eq!(
+    node.data["scores"]["pagerank"].as_f64(),
+    Some(ranks[&users[3].id])
+  );
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_community() -> sqlx::Result<()> {
+  use hypergraphsql::algo::*;
+
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let mut users = Vec::new();
+  for i in 0..8 {
+    users.push(create_node(&pool, NODE_USER_URI, User::new(format!("u{i}"))).await?);
+  }
+  // two groups of four that all follow each other, joined by a single edge
+  for group in [0, 4] {
+    for from in group..group + 4 {
+      for to in from + 1..group + 4 {
+        create_edge(
+          &pool,
+          &users[from],
+          &users[to],
+          EDGE_FOLLOWS_URI,
+          None::<Follows>,
+        )
+        .await?;
+      }
+    }
+  }
+  create_edge(
+    &pool,
+    &users[3],
+    &users[4],
+    EDGE_FOLLOWS_URI,
+    None::<Follows>,
+  )
+  .await?;
+
+  let graph = Graph::load(&pool, &ScopedQuery::default()).await?;
+  for communities in [
+    louvain(&graph, LouvainOptions::default()),
+    label_propagation(&graph, LabelPropagationOptions::default()),
+  ] {
+    assert!(users[..4]
+      .iter()
+      .all(|user| communities[&user.id] == communities[&users[0].id]));
+    assert!(users[4..]
+      .iter()
+      .all(|user| communities[&user.id] == communities[&users[4].id]));
+    assert_ne!(communities[&users[0].id], communities[&users[4].id]);
+  }
+
+  let communities = louvain(&graph, LouvainOptions::default());
+  let community_nodes =
+    write_community_nodes(&pool, &communities, "community", "member_of").await?;
+  assert_eq!(community_nodes.len(), 2);
+  let members = in_neighbors::<User>(
+    &pool,
+    community_nodes[&communities[&users[5].id]],
+    Some("member_of"),
+  )
+  .await?;
+  assert_eq!(
+    members.iter().map(|node| node.id).collect::<Vec<_>>(),
+    users[4..].iter().map(|node| node.id).collect::<Vec<_>>()
+  );
+
+  // soft deleted members are skipped and namespaces are kept
+  let acme = Namespace::new(&pool, "acme");
+  let x = acme.create_node(NODE_USER_URI, User::new("x")).await?;
+  let y = acme.create_node(NODE_USER_URI, User::new("y")).await?;
+  let z = acme.create_node(NODE_USER_URI, User::new("z")).await?;
+  soft_delete_node::<User>(&pool, users[4].id).await?.unwrap();
+  let communities = std::collections::HashMap::from([
+    (users[4].id, 0),
+    (users[5].id, 0),
+    (users[7].id, 1),
+    (x.id, 2),
+    (y.id, 2),
+    (users[6].id, 3),
+    (z.id, 3),
+  ]);
+  soft_delete_node::<User>(&pool, users[7].id).await?.unwrap();
+  let community_nodes =
+    write_community_nodes(&pool, &communities, "community", "member_of").await?;
+  assert!(!community_nodes.contains_key(&1));
+  let member_ids =
+    |members: Vec<Node<User>>| members.iter().map(|node| node.id).collect::<Vec<_>>();
+  assert_eq!(
+    member_ids(in_neighbors(&pool, community_nodes[&0], Some("member_of")).await?),
+    [users[5].id]
+  );
+  assert!(acme
+    .get_node::<serde_json::Value>(community_nodes[&2])
+    .await?
+    .is_some());
+  assert_eq!(
+    member_ids(in_neighbors(&pool, community_nodes[&2], Some("member_of")).await?),
+    [x.id, y.id]
+  );
+  assert_eq!(
+    get_node::<serde_json::Value>(&pool, community_nodes[&3])
+      .await?
+      .unwrap()
+      .namespace,
+    ""
+  );
+  assert_eq!(
+    member_ids(in_neighbors(&pool, community_nodes[&3], Some("member_of")).await?),
+    [users[6].id, z.id]
+  );
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_weighted_paths() -> sqlx::Result<()> {
+  use hypergraphsql::algo::*;
+
+  #[derive(Serialize, Deserialize)]
+  struct City {
+    name: String,
+    x: f64,
+    y: f64,
+  }
+
+  #[derive(Serialize, Deserialize)]
+  struct Road {
+    distance: f64,
+  }
+
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let mut cities = Vec::new();
+  for (name, x, y) in [
+    ("a", 0.0, 0.0),
+    ("b", 1.0, 0.0),
+    ("c", 2.0, 0.0),
+    ("d", 1.0, 5.0),
+  ] {
+    let city = City {
+      name: name.to_owned(),
+      x,
+      y,
+    };
+    cities.push(create_node(&pool, "city", city).await?);
+  }
+  for (from, to, distance) in [
+    (0, 1, 1.0),
+    (1, 2, 1.5),
+    (0, 3, 5.0),
+    (3, 2, 5.0),
+    (0, 2, 3.0),
+  ] {
+    create_edge(
+      &pool,
+      &cities[from],
+      &cities[to],
+      "road",
+      Some(Road { distance }),
+    )
+    .await?;
+  }
+
+  let query: ScopedQuery =
+    serde_json::from_str(r#"{"edge.uri": {"eq": "road"}}"#).expect("failed to parse query");
+  let graph = Graph::load_weighted(&pool, &query, "distance").await?;
+
+  let path = shortest_path::<City, Road>(&pool, &graph, cities[0].id, cities[2].id)
+    .await?
+    .unwrap();
+  assert_eq!(path.cost, 2.5);
+  assert_eq!(
+    path
+      .nodes
+      .iter()
+      .map(|node| node.data.name.as_str())
+      .collect::<Vec<_>>(),
+    ["a", "b", "c"]
+  );
+  assert_eq!(path.edges.len(), 2);
+  assert_eq!(path.edges[1].data.as_ref().unwrap().distance, 1.5);
+
+  let path = a_star::<City, Road, _>(&pool, &graph, cities[0].id, cities[2].id, |a, b| {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+  })
+  .await?
+  .unwrap();
+  assert_eq!(path.cost, 2.5);
+  assert_eq!(path.nodes.len(), 3);
+
+  assert!(dijkstra(&graph, cities[2].id, cities[0].id).is_none());
+  let unweighted = Graph::load(&pool, &query).await?;
+  assert_eq!(
+    dijkstra(&unweighted, cities[0].id, cities[2].id)
+      .unwrap()
+      .cost,
+    1.0
+  );
+
+  create_edge(
+    &pool,
+    &cities[2],
+    &cities[0],
+    "road",
+    Some(Road { distance: -1.0 }),
+  )
+  .await?;
+  assert!(Graph::load_weighted(&pool, &query, "distance")
+    .await
+    .is_err());
+
+  let towns = [
+    create_node(&pool, "town", serde_json::json!({})).await?,
+    create_node(&pool, "town", serde_json::json!({})).await?,
+    create_node(&pool, "town", serde_json::json!({})).await?,
+  ];
+  create_edge(
+    &pool,
+    &towns[0],
+    &towns[1],
+    "lane",
+    Some(serde_json::json!({"distance": 5})),
+  )
+  .await?;
+  create_edge(
+    &pool,
+    &towns[1],
+    &towns[2],
+    "lane",
+    Some(serde_json::json!({"distance": 2})),
+  )
+  .await?;
+  let query: ScopedQuery =
+    serde_json::from_str(r#"{"edge.uri": {"eq": "lane"}}"#).expect("failed to parse query");
+  let graph = Graph::load_weighted(&pool, &query, "distance").await?;
+  let path = dijkstra(&graph, towns[0].id, towns[2].id).unwrap();
+  assert_eq!(path.cost, 7.0);
+
+  create_edge(
+    &pool,
+    &towns[2],
+    &towns[0],
+    "lane",
+    Some(serde_json::json!({"distance": "far"})),
+  )
+  .await?;
+  assert!(Graph::load_weighted(&pool, &query, "distance")
+    .await
+    .is_err());
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_subgraph_copy() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+  let copy_path = tempfile::NamedTempFile::with_suffix("-copy.db")?.into_temp_path();
+  let copy_filename = copy_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let mut users = Vec::new();
+  for name in ["a", "b", "c", "d", "e"] {
+    users.push(create_node(&pool, NODE_USER_URI, User::new(name)).await?);
+  }
+  // a -> b -> c -> d, e -> a and b -> a
+  for (from, to) in [(0, 1), (1, 2), (2, 3), (4, 0), (1, 0)] {
+    create_edge(
+      &pool,
+      &users[from],
+      &users[to],
+      EDGE_FOLLOWS_URI,
+      None::<Follows>,
+    )
+    .await?;
+  }
+
+  let query: ScopedQuery = serde_json::from_str(r#"{"node.data": {"info.name": {"eq": "a"}}}"#)
+    .expect("failed to parse query");
+  let subgraph = Subgraph::neighborhood(&pool, &query, 2, Direction::Outgoing).await?;
+  let mut names: Vec<_> = subgraph
+    .nodes
+    .iter()
+    .map(|node| node.data["info"]["name"].as_str().unwrap())
+    .collect();
+  names.sort_unstable();
+  assert_eq!(names, ["a", "b", "c"]);
+  assert_eq!(subgraph.edges.len(), 3);
+  let subgraph = Subgraph::neighborhood(&pool, &query, 1, Direction::Both).await?;
+  assert_eq!(subgraph.nodes.len(), 3);
+
+  add_label(&pool, users[4].id, "admin").await?;
+  let subgraph = Subgraph::neighborhood(&pool, &query, 1, Direction::Both).await?;
+  assert_eq!(subgraph.labels[&users[4].id], ["admin"]);
+
+  let copy = create(&copy_filename, false).await?;
+  create_node(&copy, NODE_USER_URI, User::new("existing")).await?;
+  let mapping = subgraph.copy_to(&copy).await?;
+  assert_eq!(mapping.nodes.len(), 3);
+  assert_eq!(mapping.edges.len(), 3);
+  let copied = get_node::<User>(&copy, mapping.nodes[&users[4].id])
+    .await?
+    .unwrap();
+  assert_eq!(copied.data.info.name, "e");
+  let followers = in_neighbors::<User>(&copy, mapping.nodes[&users[0].id], None).await?;
+  assert_eq!(followers.len(), 2);
+  assert_eq!(
+    node_labels(&copy, mapping.nodes[&users[4].id]).await?,
+    ["admin"]
+  );
+
+  // the walk only passes soft deleted nodes when the query includes them
+  soft_delete_node::<User>(&pool, users[1].id).await?;
+  let subgraph = Subgraph::neighborhood(&pool, &query, 2, Direction::Outgoing).await?;
+  assert_eq!(subgraph.nodes.len(), 1);
+  assert!(subgraph.edges.is_empty());
+  let subgraph = Subgraph::neighborhood(
+    &pool,
+    &query.clone().with_deleted(true),
+    2,
+    Direction::Outgoing,
+  )
+  .await?;
+  assert_eq!(subgraph.nodes.len(), 3);
+  assert_eq!(subgraph.edges.len(), 3);
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_namespace() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let acme = Namespace::new(&pool, "acme");
+  let globex = Namespace::new(&pool, "globex");
+  let a = acme.create_node(NODE_USER_URI, User::new("a")).await?;
+  let b = acme.create_node(NODE_USER_URI, User::new("b")).await?;
+  let c = globex.create_node(NODE_USER_URI, User::new("c")).await?;
+  let global = create_node(&pool, NODE_USER_URI, User::new("global")).await?;
+
+  let edge = acme
+    .create_edge(a.id, b.id, EDGE_FOLLOWS_URI, None::<Follows>)
+    .await?;
+  assert_eq!(
+    (a.namespace.as_str(), global.namespace.as_str()),
+    ("acme", "")
+  );
+  assert_eq!(edge.namespace, "acme");
+  assert!(acme
+    .create_edge(a.id, c.id, EDGE_FOLLOWS_URI, None::<Follows>)
+    .await
+    .is_err());
+  assert!(
+    create_edge(&pool, &global, &a, EDGE_FOLLOWS_URI, None::<Follows>)
+      .await
+      .is_err()
+  );
+  let cross = acme
+    .clone()
+    .allow_cross_namespace(true)
+    .create_edge(a.id, c.id, EDGE_FOLLOWS_URI, None::<Follows>)
+    .await?;
+  assert!(globex
+    .clone()
+    .allow_cross_namespace(true)
+    .create_edge(a.id, b.id, EDGE_FOLLOWS_URI, None::<Follows>)
+    .await
+    .is_err());
+
+  let nodes = acme.query(Query::default()).nodes::<User>(&pool).await?;
+  assert_eq!(
+    nodes
+      .iter()
+      .map(|node| node.data.info.name.as_str())
+      .collect::<Vec<_>>(),
+    ["a", "b"]
+  );
+  assert_eq!(Query::default().count(&pool, QueryTarget::Nodes).await?, 4);
+  assert_eq!(
+    acme
+      .query(Query::default())
+      .count(&pool, QueryTarget::NodeEdges)
+      .await?,
+    1
+  );
+  let rows = acme
+    .pattern("MATCH (x)-[:follows]->(y) RETURN y".parse().unwrap())
+    .rows(&pool)
+    .await?;
+  assert_eq!(rows.len(), 1);
+  assert_eq!(rows[0].node("y").unwrap().id, b.id);
+
+  assert!(globex.get_node::<User>(a.id).await?.is_none());
+  assert!(globex.get_edge::<Follows>(edge.id).await?.is_none());
+  assert!(globex.update_node(a.id, User::new("x")).await.is_err());
+  assert!(globex.delete_node::<User>(a.id).await?.is_none());
+  assert!(globex.soft_delete_node::<User>(a.id).await?.is_none());
+  assert!(globex.soft_delete_edge::<Follows>(edge.id).await?.is_none());
+  assert!(acme.soft_delete_edge::<Follows>(edge.id).await?.is_some());
+  assert!(globex.restore_edge::<Follows>(edge.id).await?.is_none());
+  assert!(acme.restore_edge::<Follows>(edge.id).await?.is_some());
+  assert_eq!(
+    acme
+      .get_edge::<Follows>(cross.id)
+      .await?
+      .unwrap()
+      .to_node_id,
+    c.id
+  );
+  assert_eq!(
+    acme
+      .update_node(b.id, User::new("bb"))
+      .await?
+      .data
+      .info
+      .name,
+    "bb"
+  );
+
+  // namespaces and cross namespace edges survive an export and import
+  let mut buffer = Vec::new();
+  export_jsonl(&pool, &mut buffer, None).await?;
+  let copy_path = tempfile::NamedTempFile::with_suffix("-copy.db")?.into_temp_path();
+  let copy = create(&copy_path.as_os_str().to_string_lossy(), false).await?;
+  let imported = import_jsonl(&copy, buffer.as_slice()).await?;
+  assert!(imported.failures.is_empty());
+  assert_eq!((imported.nodes, imported.edges), (4, 2));
+  assert_eq!(
+    Namespace::new(&copy, "acme")
+      .query(Query::default())
+      .count(&copy, QueryTarget::Edges)
+      .await?,
+    2
+  );
+  let jsonl = String::from_utf8(buffer).unwrap();
+  assert_eq!(jsonl.matches("\"cross_namespace\":true").count(), 1);
+  let copy_path = tempfile::NamedTempFile::with_suffix("-copy.db")?.into_temp_path();
+  let copy = create(&copy_path.as_os_str().to_string_lossy(), false).await?;
+  let imported = import_jsonl(
+    &copy,
+    jsonl.replace(",\"cross_namespace\":true", "").as_bytes(),
+  )
+  .await?;
+  assert_eq!((imported.edges, imported.failures.len()), (1, 1));
+
+  // a namespace filter exports only the edges between its own nodes
+  let mut buffer = Vec::new();
+  let exported = export_jsonl(&pool, &mut buffer, Some(&acme.query(Query::default()))).await?;
+  assert_eq!((exported.nodes, exported.edges), (2, 1));
+  let edge_ids = buffer
+    .split(|&byte| byte == b'\n')
+    .filter_map(|line| match serde_json::from_slice(line) {
+      Ok(Record::Edge(record)) => Some(record.edge.id),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(edge_ids, [edge.id]);
+
+  let subgraph = Subgraph::from_query(&pool, &acme.query(Query::default())).await?;
+  let copy_path = tempfile::NamedTempFile::with_suffix("-copy.db")?.into_temp_path();
+  let copy = create(&copy_path.as_os_str().to_string_lossy(), false).await?;
+  let mapping = subgraph.copy_to(&copy).await?;
+  let copied = Namespace::new(&copy, "acme")
+    .get_node::<User>(mapping.nodes[&a.id])
+    .await?
+    .unwrap();
+  assert_eq!(copied.namespace, "acme");
+
+  let deleted = globex
+    .query(Query::default())
+    .delete_nodes::<User>(&pool)
+    .await?;
+  assert_eq!(deleted.len(), 1);
+  assert_eq!(Query::default().count(&pool, QueryTarget::Nodes).await?, 3);
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_node_keys() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+  let copy_path = tempfile::NamedTempFile::with_suffix("-copy.db")?.into_temp_path();
+  let copy_filename = copy_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let a = create_node_with_key(&pool, Some("user-a"), NODE_USER_URI, User::new("a")).await?;
+  let b = create_node_with_key(&pool, None, NODE_USER_URI, User::new("b")).await?;
+  let c = create_node(&pool, NODE_USER_URI, User::new("c")).await?;
+  assert_eq!(a.key.as_deref(), Some("user-a"));
+  let b_key = b.key.clone().unwrap();
+  assert_eq!(b_key.len(), 36);
+  assert_eq!(&b_key[14..15], "7");
+  assert!(c.key.is_none());
+  assert!(
+    create_node_with_key(&pool, Some("user-a"), NODE_USER_URI, User::new("x"))
+      .await
+      .is_err()
+  );
+
+  let found = get_node_by_key::<User>(&pool, &b_key).await?.unwrap();
+  assert_eq!(found.id, b.id);
+  assert!(get_node_by_key::<User>(&pool, "missing").await?.is_none());
+
+  let edge =
+    create_edge_by_keys(&pool, "user-a", &b_key, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+  assert_eq!((edge.from_node_id, edge.to_node_id), (a.id, b.id));
+  assert!(create_edge_by_keys(
+    &pool,
+    "user-a",
+    "missing",
+    EDGE_FOLLOWS_URI,
+    None::<Follows>
+  )
+  .await
+  .is_err());
+
+  let query: Query =
+    serde_json::from_str(r#"{"node.key": {"eq": "user-a"}}"#).expect("failed to parse query");
+  let nodes = query.nodes::<User>(&pool).await?;
+  assert_eq!(nodes.len(), 1);
+  assert_eq!(nodes[0].id, a.id);
+  let query: Query = serde_json::from_str(&format!(r#"{{"to_node.key": {{"eq": "{b_key}"}}}}"#))
+    .expect("failed to parse query");
+  let node_edges = query.node_edges::<User, User, Follows>(&pool).await?;
+  assert_eq!(node_edges.len(), 1);
+  assert_eq!(node_edges[0].from_node.key.as_deref(), Some("user-a"));
+
+  // keys survive a copy, so the same node cannot be copied twice
+  let query: ScopedQuery =
+    serde_json::from_str(r#"{"node.key": {"eq": "user-a"}}"#).expect("failed to parse query");
+  let subgraph = Subgraph::neighborhood(&pool, &query, 1, Direction::Outgoing).await?;
+  let copy = create(&copy_filename, false).await?;
+  subgraph.copy_to(&copy).await?;
+  assert_eq!(
+    get_node_by_key::<User>(&copy, &b_key)
+      .await?
+      .unwrap()
+      .data
+      .info
+      .name,
+    "b"
+  );
+  assert!(subgraph.copy_to(&copy).await.is_err());
+
+  // keys are unique per namespace and looked up in their own namespace
+  let acme = Namespace::new(&pool, "acme");
+  let acme_a = acme
+    .create_node_with_key(Some("user-a"), NODE_USER_URI, User::new("acme a"))
+    .await?;
+  let acme_b = acme
+    .create_node_with_key(Some("user-b"), NODE_USER_URI, User::new("acme b"))
+    .await?;
+  assert_eq!(
+    get_node_by_key::<User>(&pool, "user-a").await?.unwrap().id,
+    a.id
+  );
+  assert_eq!(
+    acme.get_node_by_key::<User>("user-a").await?.unwrap().id,
+    acme_a.id
+  );
+  assert!(acme.get_node_by_key::<User>(&b_key).await?.is_none());
+  let edge = acme
+    .create_edge_by_keys("user-a", "user-b", EDGE_FOLLOWS_URI, None::<Follows>)
+    .await?;
+  assert_eq!((edge.from_node_id, edge.to_node_id), (acme_a.id, acme_b.id));
+  assert_eq!(edge.namespace, "acme");
+  assert!(acme
+    .create_edge_by_keys("user-a", &b_key, EDGE_FOLLOWS_URI, None::<Follows>)
+    .await
+    .is_err());
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_labels() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let a = create_node(&pool, NODE_USER_URI, User::new("a")).await?;
+  let b = create_node(&pool, NODE_USER_URI, User::new("b")).await?;
+  let c = create_node(&pool, NODE_USER_URI, User::new("c")).await?;
+  create_edge(&pool, &a, &b, EDGE_FOLLOWS_URI, None::<Follows>).await?;
+
+  assert!(add_label(&pool, a.id, "admin").await?);
+  assert!(add_label(&pool, a.id, "seller").await?);
+  assert!(!add_label(&pool, a.id, "admin").await?);
+  assert!(add_label(&pool, b.id, "seller").await?);
+  assert!(add_label(&pool, c.id, "admin").await?);
+  assert!(add_label(&pool, 1000, "admin").await.is_err());
+  assert_eq!(node_labels(&pool, a.id).await?, ["admin", "seller"]);
+
+  let ids = |nodes: Vec<Node<User>>| nodes.iter().map(|node| node.id).collect::<Vec<_>>();
+  assert_eq!(ids(nodes_with_label(&pool, "admin").await?), [a.id, c.id]);
+
+  let query: Query = serde_json::from_str(r#"{"node.labels": {"contains": "seller"}}"#)
+    .expect("failed to parse query");
+  assert_eq!(ids(query.nodes(&pool).await?), [a.id, b.id]);
+  let query: Query = serde_json::from_str(
+    r#"{"node.labels": {"and": [{"contains": "admin"}, {"contains": "seller"}]}}"#,
+  )
+  .expect("failed to parse query");
+  assert_eq!(ids(query.nodes(&pool).await?), [a.id]);
+  let query: Query =
+    serde_json::from_str(r#"{"node.labels": {"not": {"in": ["admin", "seller"]}}}"#)
+      .expect("failed to parse query");
+  assert!(query.nodes::<User>(&pool).await?.is_empty());
+  let query: Query = serde_json::from_str(
+    r#"{"from_node.labels": {"contains": "admin"}, "to_node.labels": {"contains": "seller"}}"#,
+  )
+  .expect("failed to parse query");
+  assert_eq!(query.count(&pool, QueryTarget::NodeEdges).await?, 1);
+
+  assert!(remove_label(&pool, a.id, "admin").await?);
+  assert!(!remove_label(&pool, a.id, "admin").await?);
+  assert_eq!(ids(nodes_with_label(&pool, "admin").await?), [c.id]);
+  soft_delete_node::<User>(&pool, c.id).await?;
+  assert!(nodes_with_label::<User>(&pool, "admin").await?.is_empty());
+  delete_node::<User>(&pool, b.id).await?;
+  assert_eq!(ids(nodes_with_label(&pool, "seller").await?), [a.id]);
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_timestamps() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+  let copy_path = tempfile::NamedTempFile::with_suffix("-copy.db")?.into_temp_path();
+  let copy_filename = copy_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let user = create_node(&pool, NODE_USER_URI, User::new("a")).await?;
+  tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+  let updated = update_node(&pool, user.id, User::new("b")).await?;
+  assert!(updated.updated_at > user.created_at);
+  assert!(updated.updated_at - user.created_at < chrono::Duration::seconds(1));
+
+  let created_at = "2024-01-02T03:04:05.678Z"
+    .parse::<chrono::DateTime<chrono::Utc>>()
+    .unwrap();
+  let updated_at = created_at + chrono::Duration::milliseconds(1);
+  let imported =
+    create_node_at(&pool, NODE_USER_URI, User::new("c"), created_at, updated_at).await?;
+  let found = get_node::<User>(&pool, imported.id).await?.unwrap();
+  assert_eq!(found.created_at, created_at);
+  assert_eq!(found.updated_at, updated_at);
+  let edge = create_edge_at(
+    &pool,
+    imported.id,
+    user.id,
+    EDGE_FOLLOWS_URI,
+    None::<Follows>,
+    created_at,
+    created_at,
+  )
+  .await?;
+  assert_eq!(
+    get_edge::<Follows>(&pool, edge.id)
+      .await?
+      .unwrap()
+      .created_at,
+    created_at
+  );
+
+  let query: Query = serde_json::from_value(serde_json::json!({
+    "node.created_at": {"lt": created_at.timestamp_millis() + 1}
+  }))
+  .expect("failed to parse query");
+  assert_eq!(query.nodes::<User>(&pool).await?.len(), 1);
+
+  let mut buffer = Vec::new();
+  export_jsonl(&pool, &mut buffer, None).await?;
+  let copy = create(&copy_filename, false).await?;
+  import_jsonl(&copy, buffer.as_slice()).await?;
+  let copied = Query::default()
+    .node_edges::<User, User, Follows>(&copy)
+    .await?;
+  assert_eq!(copied.len(), 1);
+  assert_eq!(copied[0].from_node.created_at, created_at);
+  assert_eq!(copied[0].from_node.updated_at, updated_at);
+  assert_eq!(copied[0].to_node.updated_at, updated.updated_at);
+  assert_eq!(copied[0].edge.created_at, created_at);
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_time_filters() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let now = chrono::Utc::now();
+  let old_time = "2020-01-01T12:00:00Z"
+    .parse::<chrono::DateTime<chrono::Utc>>()
+    .unwrap();
+  let recent_time = now - chrono::Duration::hours(1);
+  let old = create_node_at(
+    &pool,
+    "event",
+    serde_json::json!({"due": "2024-03-01T10:00:00Z"}),
+    old_time,
+    old_time,
+  )
+  .await?;
+  let recent = create_node_at(
+    &pool,
+    "event",
+    serde_json::json!({"due": "2024-02-15T01:00:00+05:00"}),
+    recent_time,
+    recent_time,
+  )
+  .await?;
+  let later = create_node(
+    &pool,
+    "event",
+    serde_json::json!({"due": "2024-05-01 00:00:00"}),
+  )
+  .await?;
+
+  let ids = |value: serde_json::Value| {
+    let pool = pool.clone();
+    async move {
+      let query: Query = serde_json::from_value(value).expect("failed to parse query");
+      let nodes = query.nodes::<serde_json::Value>(&pool).await?;
+      Ok::<_, sqlx::Error>(nodes.iter().map(|node| node.id).collect::<Vec<_>>())
+    }
+  };
+
+  assert_eq!(
+    ids(serde_json::json!({"node.created_at": {"gt": "2021-01-01T00:00:00Z"}})).await?,
+    [recent.id, later.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.created_at": {"eq": "2020-01-01T13:00:00+01:00"}})).await?,
+    [old.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.created_at": {"within": "1d"}})).await?,
+    [recent.id, later.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.created_at": {"within": "30m"}})).await?,
+    [later.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.created_at": {"lt": "now-1d"}})).await?,
+    [old.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.updated_at": {"between": ["2019-12-31", "2020-01-02"]}})).await?,
+    [old.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.id": {"between": [recent.id, later.id]}})).await?,
+    [recent.id, later.id]
+  );
+
+  // dates in data compare as instants, not as strings
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"due": {"lt": "2024-02-15"}}})).await?,
+    [recent.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({
+      "node.data": {"due": {"between": ["2024-02-01", "2024-04-01T00:00:00+02:00"]}}
+    }))
+    .await?,
+    [old.id, recent.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"due": {"within": "1d"}}})).await?,
+    Vec::<i64>::new()
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"due": {"eq": "2024-03-01T10:00:00Z"}}})).await?,
+    [old.id]
+  );
+
+  let duration: QueryDuration = serde_json::from_str(r#""1h30m""#).unwrap();
+  assert_eq!(duration.millis(), 5_400_000);
+  assert!(serde_json::from_str::<QueryDuration>(r#""7x""#).is_err());
+
+  // timestamp columns only compare with times, and only they or data take
+  // time ranges
+  for (value, message) in [
+    (
+      serde_json::json!({"node.created_at": {"gt": "yesterday"}}),
+      r#"Invalid time "yesterday""#,
+    ),
+    (
+      serde_json::json!({"node.created_at": {"lt": "now+9223372036854775000ms"}}),
+      r#"Invalid time "now+9223372036854775000ms""#,
+    ),
+    (
+      serde_json::json!({"node.uri": {"within": "1d"}}),
+      "node.uri does not hold times",
+    ),
+    (
+      serde_json::json!({"node.uri": {"between": ["2020-01-01", "2021-01-01"]}}),
+      "node.uri does not hold times",
+    ),
+  ] {
+    let error = serde_json::from_value::<Query>(value.clone())
+      .err()
+      .unwrap();
+    assert_eq!(error.to_string(), message);
+    // built in code, the query renders as matching nothing and fails to run
+    let query = Query(serde_json::from_value(value).expect("failed to parse conditions"));
+    assert_eq!(query.validate().unwrap_err().to_string(), message);
+    assert!(query.sql().ends_with("NULL"));
+    let error = query.nodes::<serde_json::Value>(&pool).await.err().unwrap();
+    assert!(matches!(error, sqlx::Error::Encode(_)));
+  }
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_query_ops() -> sqlx::Result<()> {
+  let temp_path = tempfile::NamedTempFile::with_suffix("-test.db")?.into_temp_path();
+  let filename = temp_path.as_os_str().to_string_lossy();
+
+  let pool = create(&filename, false).await?;
+
+  let a = create_node_with_key(
+    &pool,
+    Some("a"),
+    "person",
+    serde_json::json!({"name": "Alice", "tags": ["admin", "dev"], "nick": null, "bio": "loves rust"}),
+  )
+  .await?;
+  let b = create_node(
+    &pool,
+    "person",
+    serde_json::json!({"name": "bob", "tags": ["dev"], "bio": "rustacean"}),
+  )
+  .await?;
+  let c = create_node(&pool, "person", serde_json::json!({"name": "Carol"})).await?;
+
+  let ids = |value: serde_json::Value| {
+    let pool = pool.clone();
+    async move {
+      let query: Query = serde_json::from_value(value).expect("failed to parse query");
+      let nodes = query.nodes::<serde_json::Value>(&pool).await?;
+      Ok::<_, sqlx::Error>(nodes.iter().map(|node| node.id).collect::<Vec<_>>())
+    }
+  };
+
+  assert_eq!(
+    ids(serde_json::json!({"node.key": {"eq": null}})).await?,
+    [b.id, c.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.key": {"neq": null}})).await?,
+    [a.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.key": {"is_null": true}})).await?,
+    [b.id, c.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.key": {"is_null": false}})).await?,
+    [a.id]
+  );
+
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"nick": {"exists": true}}})).await?,
+    [a.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"nick": {"exists": false}}})).await?,
+    [b.id, c.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"nick": {"is_null": true}}})).await?,
+    [a.id, b.id, c.id]
+  );
+
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"tags": {"contains": "dev"}}})).await?,
+    [a.id, b.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"tags": {"contains": "admin"}}})).await?,
+    [a.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"bio": {"contains": "rust"}}})).await?,
+    [a.id, b.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"name": {"starts_with": "Al"}}})).await?,
+    [a.id]
+  );
+  assert!(
+    ids(serde_json::json!({"node.data": {"name": {"starts_with": "al"}}}))
+      .await?
+      .is_empty()
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"name": {"ends_with": "ol"}}})).await?,
+    [c.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"name": {"ilike": "ALICE"}}})).await?,
+    [a.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"name": {"regex": "^[A-Z]"}}})).await?,
+    [a.id, c.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.data": {"name": {"between": ["B", "D"]}}})).await?,
+    [c.id]
+  );
+  assert_eq!(
+    ids(serde_json::json!({"node.uri": {"regex": "^per"}})).await?,
+    [a.id, b.id, c.id]
+  );
+
+  // the string operators match the same rows in both query languages
+  for (field, op, cypher, expected) in [
+    (
+      "tags",
+      "contains",
+      "a.tags CONTAINS 'dev'",
+      vec![a.id, b.id],
+    ),
+    ("tags", "contains", "a.tags CONTAINS 'ad'", vec![]),
+    ("bio", "contains", "a.bio CONTAINS 'rust'", vec![a.id, b.id]),
+    ("name", "starts_with", "a.name STARTS WITH 'Al'", vec![a.id]),
+    ("name", "ends_with", "a.name ENDS WITH 'ol'", vec![c.id]),
+    ("name", "ends_with", "a.name ENDS WITH 'xCarol'", vec![]),
+  ] {
+    let value = cypher.rsplit('\'').nth(1).unwrap();
+    let query = ids(serde_json::json!({"node.data": {field: {op: value}}})).await?;
+    let rows = format!("MATCH (a:person) WHERE {cypher} RETURN a")
+      .parse::<Pattern>()
+      .expect("failed to parse pattern")
+      .rows(&pool)
+      .await?;
+    let mut matched: Vec<i64> = rows.iter().map(|row| row.node("a").unwrap().id).collect();
+    matched.sort_unstable();
+    assert_eq!(query, expected, "{cypher}");
+    assert_eq!(matched, expected, "{cypher}");
+  }
+
+  Ok(())
+}